@@ -0,0 +1,22 @@
+#[macro_use]
+extern crate criterion;
+extern crate ksuid;
+
+use criterion::{black_box, Criterion};
+use ksuid::KSUID;
+
+fn bench_encode(c: &mut Criterion) {
+    let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+    c.bench_function("to_base62", |b| b.iter(|| black_box(&uid).to_base62()));
+    c.bench_function("to_base62_array", |b| b.iter(|| black_box(&uid).to_base62_array()));
+    // The largest value needs the most division passes.
+    let max = KSUID::from_bytes(&[0xff; 20]).unwrap();
+    c.bench_function("to_base62_array_max", |b| b.iter(|| black_box(&max).to_base62_array()));
+}
+
+fn bench_decode(c: &mut Criterion) {
+    c.bench_function("from_base62", |b| b.iter(|| KSUID::from_base62(black_box("0ujsszwN8NRY24YaXiTIE2VWDTS"))));
+}
+
+criterion_group!(benches, bench_encode, bench_decode);
+criterion_main!(benches);
@@ -0,0 +1,24 @@
+#[macro_use]
+extern crate criterion;
+extern crate ksuid;
+
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use ksuid::KSUID;
+
+fn bench_sort(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort");
+    for &len in [10_000usize, 1_000_000].iter() {
+        // KSUID::new() ids all share the current second, the common case in compaction inputs.
+        let ids: Vec<KSUID> = (0..len).map(|_| KSUID::new()).collect();
+        group.bench_with_input(BenchmarkId::new("radix", len), &ids, |b, ids| {
+            b.iter_batched_ref(|| ids.clone(), |ids| ksuid::sort::sort_unstable(ids), BatchSize::LargeInput)
+        });
+        group.bench_with_input(BenchmarkId::new("slice_sort_unstable", len), &ids, |b, ids| {
+            b.iter_batched_ref(|| ids.clone(), |ids| ids.sort_unstable(), BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_sort);
+criterion_main!(benches);
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+// Parsing untrusted text must return Ok or Err, never panic, and anything accepted must
+// re-encode to the same canonical string.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        if let Ok(id) = ksuid::KSUID::from_base62(text) {
+            assert_eq!(id.to_base62(), &text[..27]);
+        }
+    }
+});
@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    match ksuid::KSUID::from_bytes(data) {
+        Ok(id) => assert_eq!(id.as_bytes(), &data[..20]),
+        Err(_) => assert!(data.len() < 20),
+    }
+});
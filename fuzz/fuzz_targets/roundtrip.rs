@@ -0,0 +1,9 @@
+#![no_main]
+use ksuid::KSUID;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|id: KSUID| {
+    let encoded = id.to_base62();
+    assert_eq!(encoded.len(), 27);
+    assert_eq!(KSUID::from_base62(&encoded).unwrap(), id);
+});
@@ -0,0 +1,58 @@
+//! Node.js bindings, built with napi-rs, so Node services share this crate's generation and
+//! validation instead of a separate npm implementation. Ids cross the boundary as base62
+//! strings; anything that parses them throws with the same message `KSUID::from_base62` gives.
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use ksuid::KSUID;
+
+fn parse_id(id: &str) -> Result<KSUID> {
+    KSUID::from_base62(id).map_err(|err| Error::new(Status::InvalidArg, err.to_string()))
+}
+
+/// Mint a new id.
+#[napi]
+pub fn generate() -> String {
+    KSUID::new().to_base62()
+}
+
+/// Whether `id` is a valid base62 id.
+#[napi]
+pub fn is_valid(id: String) -> bool {
+    ksuid::is_valid(&id)
+}
+
+/// Validate `id` and return its 20 raw bytes.
+#[napi]
+pub fn parse(id: String) -> Result<Buffer> {
+    Ok(parse_id(&id)?.as_bytes().to_vec().into())
+}
+
+/// Encode 20 raw bytes as a base62 id.
+#[napi]
+pub fn from_bytes(bytes: Buffer) -> Result<String> {
+    if bytes.len() != KSUID::BYTE_LENGTH {
+        return Err(Error::new(Status::InvalidArg, format!("expected {} bytes, got {}", KSUID::BYTE_LENGTH, bytes.len())));
+    }
+    Ok(KSUID::from_bytes(&bytes).unwrap().to_base62())
+}
+
+/// Compare two ids by creation time then payload: -1, 0 or 1, for `Array.prototype.sort`.
+#[napi]
+pub fn compare(a: String, b: String) -> Result<i32> {
+    Ok(match parse_id(&a)?.cmp(&parse_id(&b)?) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    })
+}
+
+/// The id's creation time, to the second, as a `Date`.
+#[napi]
+pub fn timestamp(id: String) -> Result<DateTime<Utc>> {
+    Ok(parse_id(&id)?.timestamp())
+}
@@ -0,0 +1,22 @@
+const assert = require('node:assert');
+const test = require('node:test');
+const ksuid = require('..');
+
+test('generate round trips through bytes', () => {
+  const id = ksuid.generate();
+  assert.ok(ksuid.isValid(id));
+  const bytes = ksuid.parse(id);
+  assert.strictEqual(bytes.length, 20);
+  assert.strictEqual(ksuid.fromBytes(bytes), id);
+});
+
+test('matches the Rust test vectors', () => {
+  assert.strictEqual(ksuid.timestamp('0ujsszwN8NRY24YaXiTIE2VWDTS').toISOString(), '2017-10-10T03:52:37.000Z');
+  assert.strictEqual(ksuid.compare('0ujsszwN8NRY24YaXiTIE2VWDTS', '0ujtsYcgvSTl8PAuAdqWYSMnLOv'), -1);
+});
+
+test('rejects what the Rust parser rejects', () => {
+  assert.ok(!ksuid.isValid('aWgEPTl1tmebfsQzFP4bxwgy80W'));
+  assert.throws(() => ksuid.parse('0ujsszwN8NRY24YaXiTIE2VWDT'), /length 26, expected 27/);
+  assert.throws(() => ksuid.fromBytes(Buffer.alloc(3)), /expected 20 bytes/);
+});
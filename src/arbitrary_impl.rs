@@ -0,0 +1,28 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+use ksuid::KSUID;
+
+/// Builds a `KSUID` from the next 20 bytes of fuzzer input, so every byte pattern (including
+/// timestamps far outside "now") is reachable.
+impl<'a> Arbitrary<'a> for KSUID {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(KSUID(u.arbitrary()?))
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (20, Some(20))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arbitrary_consumes_twenty_bytes() {
+        let data: Vec<u8> = (0..25).collect();
+        let mut u = Unstructured::new(&data);
+        let id = KSUID::arbitrary(&mut u).unwrap();
+        assert_eq!(id.as_bytes(), &data[..20]);
+        assert_eq!(u.len(), 5);
+    }
+}
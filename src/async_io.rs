@@ -0,0 +1,151 @@
+use io::{invalid_data, parse_text, ENCODED_LENGTH};
+use ksuid::KSUID;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Future returned by `AsyncReadKsuidExt::read_ksuid` and `read_ksuid_base62`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct ReadKsuid<'a, R: ?Sized> {
+    reader: &'a mut R,
+    buf: [u8; ENCODED_LENGTH],
+    len: usize,
+    filled: usize,
+}
+
+impl<'a, R: AsyncRead + Unpin + ?Sized> Future for ReadKsuid<'a, R> {
+    type Output = io::Result<KSUID>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        while this.filled < this.len {
+            let mut buf = ReadBuf::new(&mut this.buf[this.filled..this.len]);
+            match Pin::new(&mut *this.reader).poll_read(cx, &mut buf) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Ready(Ok(())) => {
+                    let n = buf.filled().len();
+                    if n == 0 {
+                        return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+                    }
+                    this.filled += n;
+                }
+            }
+        }
+        Poll::Ready(if this.len == ENCODED_LENGTH {
+            parse_text(&this.buf)
+        } else {
+            KSUID::from_bytes(&this.buf[..this.len]).map_err(invalid_data)
+        })
+    }
+}
+
+/// Future returned by `AsyncWriteKsuidExt::write_ksuid` and `write_ksuid_base62`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct WriteKsuid<'a, W: ?Sized> {
+    writer: &'a mut W,
+    buf: [u8; ENCODED_LENGTH],
+    len: usize,
+    written: usize,
+}
+
+impl<'a, W: AsyncWrite + Unpin + ?Sized> Future for WriteKsuid<'a, W> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        while this.written < this.len {
+            match Pin::new(&mut *this.writer).poll_write(cx, &this.buf[this.written..this.len]) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(n)) => this.written += n,
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Async counterpart of `ReadKsuidExt` for tokio's `AsyncRead`. Reads go straight into a small
+/// buffer inside the returned future, with no intermediate allocation.
+/// # Example
+/// ```
+/// extern crate ksuid;
+/// extern crate tokio;
+/// use ksuid::{AsyncReadKsuidExt, AsyncWriteKsuidExt, KSUID};
+///
+/// let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// let uid = KSUID::new();
+/// let mut buf = Vec::new();
+/// rt.block_on(buf.write_ksuid(&uid)).unwrap();
+/// let mut reader = buf.as_slice();
+/// assert_eq!(rt.block_on(reader.read_ksuid()).unwrap(), uid);
+/// ```
+pub trait AsyncReadKsuidExt: AsyncRead {
+    /// Read exactly 20 raw bytes as a `KSUID`.
+    fn read_ksuid(&mut self) -> ReadKsuid<'_, Self> where Self: Unpin {
+        ReadKsuid { reader: self, buf: [0; ENCODED_LENGTH], len: 20, filled: 0 }
+    }
+
+    /// Read exactly 27 bytes of base62 text and parse them. Malformed text is reported as
+    /// `io::ErrorKind::InvalidData`.
+    fn read_ksuid_base62(&mut self) -> ReadKsuid<'_, Self> where Self: Unpin {
+        ReadKsuid { reader: self, buf: [0; ENCODED_LENGTH], len: ENCODED_LENGTH, filled: 0 }
+    }
+}
+
+impl<R: AsyncRead + ?Sized> AsyncReadKsuidExt for R {}
+
+/// Async counterpart of `WriteKsuidExt` for tokio's `AsyncWrite`.
+pub trait AsyncWriteKsuidExt: AsyncWrite {
+    /// Write the 20 raw bytes of a `KSUID`.
+    fn write_ksuid(&mut self, id: &KSUID) -> WriteKsuid<'_, Self> where Self: Unpin {
+        let mut buf = [0; ENCODED_LENGTH];
+        buf[..20].copy_from_slice(id.as_bytes());
+        WriteKsuid { writer: self, buf, len: 20, written: 0 }
+    }
+
+    /// Write the 27 byte base62 text form of a `KSUID`.
+    fn write_ksuid_base62(&mut self, id: &KSUID) -> WriteKsuid<'_, Self> where Self: Unpin {
+        let mut buf = [0; ENCODED_LENGTH];
+        buf.copy_from_slice(id.to_base62().as_bytes());
+        WriteKsuid { writer: self, buf, len: ENCODED_LENGTH, written: 0 }
+    }
+}
+
+impl<W: AsyncWrite + ?Sized> AsyncWriteKsuidExt for W {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::{Builder, Runtime};
+
+    fn runtime() -> Runtime {
+        Builder::new_current_thread().build().unwrap()
+    }
+
+    #[test]
+    fn test_async_roundtrip() {
+        let rt = runtime();
+        let uid = KSUID::new();
+        let mut buf = Vec::new();
+        rt.block_on(buf.write_ksuid_base62(&uid)).unwrap();
+        rt.block_on(buf.write_ksuid(&uid)).unwrap();
+        assert_eq!(buf.len(), 47);
+        let mut reader = buf.as_slice();
+        assert_eq!(rt.block_on(reader.read_ksuid_base62()).unwrap(), uid);
+        assert_eq!(rt.block_on(reader.read_ksuid()).unwrap(), uid);
+        assert_eq!(rt.block_on(reader.read_ksuid()).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_async_invalid_text() {
+        let rt = runtime();
+        let mut reader: &[u8] = &[b'z'; 27];
+        assert_eq!(rt.block_on(reader.read_ksuid_base62()).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
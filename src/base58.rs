@@ -0,0 +1,180 @@
+use byteorder::{BigEndian, ByteOrder};
+use errors;
+use ksuid::{BYTE_LENGTH, KSUID};
+use std::str;
+
+// The Bitcoin alphabet: base62 without the easily confused `0`, `O`, `I` and `l`. It is in
+// ascending ASCII order, so fixed width encodings sort like the ids they encode.
+const BASE58_CHARS: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE: u64 = 58;
+
+/// Length of the base58 form: 58^28 is the first power of 58 above 2^160.
+pub(crate) const ENCODED_LENGTH: usize = 28;
+
+const LIMBS: usize = BYTE_LENGTH / 4;
+
+const INVALID: u8 = 0xff;
+const DECODE_TABLE: [u8; 256] = build_decode_table();
+
+const fn build_decode_table() -> [u8; 256] {
+    let mut table = [INVALID; 256];
+    let mut i = 0;
+    while i < BASE58_CHARS.len() {
+        table[BASE58_CHARS[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+fn encode(src: &[u8; BYTE_LENGTH]) -> [u8; ENCODED_LENGTH] {
+    let mut limbs = [0u32; LIMBS];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        *limb = BigEndian::read_u32(&src[i * 4..]);
+    }
+    let mut dst = [BASE58_CHARS[0]; ENCODED_LENGTH];
+    // Schoolbook division by 58, least significant digit first. Only 28 digits, so the simple
+    // version is plenty; this isn't on anyone's hot path the way base62 is.
+    for d in dst.iter_mut().rev() {
+        let mut remainder = 0u64;
+        for limb in limbs.iter_mut() {
+            let value = u64::from(*limb) | (remainder << 32);
+            *limb = (value / BASE) as u32;
+            remainder = value % BASE;
+        }
+        *d = BASE58_CHARS[remainder as usize];
+    }
+    dst
+}
+
+fn decode(src: &[u8]) -> Result<[u8; BYTE_LENGTH], errors::KSUIDError> {
+    let lossy = || String::from_utf8_lossy(src).into_owned();
+    if src.len() != ENCODED_LENGTH {
+        return Err(errors::KSUIDError::InvalidBase58Length { value: lossy(), expected: ENCODED_LENGTH, actual: src.len() });
+    }
+    let mut limbs = [0u32; LIMBS];
+    for (position, &c) in src.iter().enumerate() {
+        let digit = DECODE_TABLE[c as usize];
+        if digit == INVALID {
+            return Err(errors::KSUIDError::InvalidBase58Character { value: lossy(), position });
+        }
+        // limbs = limbs * 58 + digit, watching for a carry out of the top limb.
+        let mut carry = u64::from(digit);
+        for limb in limbs.iter_mut().rev() {
+            let value = u64::from(*limb) * BASE + carry;
+            *limb = value as u32;
+            carry = value >> 32;
+        }
+        if carry != 0 {
+            return Err(errors::KSUIDError::Base58Overflow { value: lossy() });
+        }
+    }
+    let mut bytes = [0u8; BYTE_LENGTH];
+    for (i, limb) in limbs.iter().enumerate() {
+        BigEndian::write_u32(&mut bytes[i * 4..], *limb);
+    }
+    Ok(bytes)
+}
+
+/// Standard (Bitcoin, "base58btc") encoding: one `1` per leading zero byte, then the value in
+/// as few digits as it needs. Unlike the fixed width form this round trips with other base58
+/// implementations, but doesn't sort.
+pub(crate) fn encode_btc(src: &[u8; BYTE_LENGTH]) -> String {
+    let fixed = encode(src);
+    let zero_bytes = src.iter().take_while(|&&b| b == 0).count();
+    let value_digits = fixed.iter().skip_while(|&&d| d == BASE58_CHARS[0]);
+    let mut encoded = String::with_capacity(ENCODED_LENGTH);
+    encoded.extend((0..zero_bytes).map(|_| BASE58_CHARS[0] as char));
+    encoded.extend(value_digits.map(|&d| d as char));
+    encoded
+}
+
+/// Inverse of `encode_btc`, or `None` if `src` isn't base58btc for exactly 20 bytes.
+pub(crate) fn decode_btc(src: &str) -> Option<[u8; BYTE_LENGTH]> {
+    let zero_bytes = src.bytes().take_while(|&b| b == BASE58_CHARS[0]).count();
+    let value = &src.as_bytes()[zero_bytes..];
+    if value.len() > ENCODED_LENGTH || zero_bytes > BYTE_LENGTH {
+        return None;
+    }
+    let mut padded = [BASE58_CHARS[0]; ENCODED_LENGTH];
+    padded[ENCODED_LENGTH - value.len()..].copy_from_slice(value);
+    let bytes = decode(&padded).ok()?;
+    // The value has to fill exactly the bytes after the leading zeros.
+    if bytes.iter().take_while(|&&b| b == 0).count() != zero_bytes {
+        return None;
+    }
+    Some(bytes)
+}
+
+/// Base58 text form, using the Bitcoin alphabet, for contexts where `0`/`O` and `I`/`l` get
+/// confused, like printed labels. The encoding is fixed width (28 characters, left padded with
+/// `1`, the zero digit) so that, as with base62, string order is id order.
+///
+/// The padding differs from Bitcoin's base58, where each leading `1` stands for a leading zero
+/// byte: generic base58 decoders will produce extra zero bytes for padded ids.
+impl KSUID {
+
+    /// Encode as 28 characters of base58.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+    /// let label = uid.to_base58();
+    /// assert_eq!(label.len(), 28);
+    /// assert!(!label.contains(|c| c == '0' || c == 'O' || c == 'I' || c == 'l'));
+    /// assert_eq!(KSUID::from_base58(&label).unwrap(), uid);
+    /// ```
+    pub fn to_base58(&self) -> String {
+        // The alphabet is ASCII, so this can't fail.
+        str::from_utf8(&encode(&self.0)).unwrap().to_owned()
+    }
+
+    /// Parse the 28 character base58 form. Wrong lengths, characters outside the alphabet
+    /// (including `0`, `O`, `I` and `l`) and values too large for 20 bytes are errors.
+    pub fn from_base58(string: &str) -> Result<Self, errors::KSUIDError> {
+        decode(string.as_bytes()).map(KSUID)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base58_roundtrip() {
+        for uid in [KSUID::nil(), KSUID([0xff; 20]), KSUID::new()].iter() {
+            let encoded = uid.to_base58();
+            assert_eq!(encoded.len(), ENCODED_LENGTH);
+            assert_eq!(KSUID::from_base58(&encoded).unwrap(), *uid);
+        }
+        assert_eq!(KSUID::nil().to_base58(), "1111111111111111111111111111");
+    }
+
+    #[test]
+    fn test_base58_known_value() {
+        // 0x01 followed by 19 zero bytes is 2^152; checked independently with Python.
+        let mut bytes = [0u8; 20];
+        bytes[0] = 1;
+        assert_eq!(KSUID(bytes).to_base58(), "11osEoy933LkHyyBcgjE7v81Kvmd");
+    }
+
+    #[test]
+    fn test_base58_order() {
+        let mut ids: Vec<KSUID> = (0..1000).map(|_| KSUID::new()).collect();
+        ids.push(KSUID::nil());
+        ids.push(KSUID([0xff; 20]));
+        let mut by_text = ids.clone();
+        by_text.sort_by_key(|uid| uid.to_base58());
+        ids.sort();
+        assert_eq!(by_text, ids);
+    }
+
+    #[test]
+    fn test_base58_rejects() {
+        let kind = |s: &str| KSUID::from_base58(s).unwrap_err().kind();
+        let valid = KSUID::new().to_base58();
+        assert_eq!(kind(&valid[1..]), errors::KSUIDErrorKind::InvalidLength);
+        assert_eq!(kind(&valid.replacen(&valid[5..6], "0", 1)), errors::KSUIDErrorKind::InvalidCharacter);
+        assert_eq!(kind("zzzzzzzzzzzzzzzzzzzzzzzzzzzz"), errors::KSUIDErrorKind::Overflow);
+    }
+}
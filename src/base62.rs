@@ -6,83 +6,146 @@ const BASE: u64 = 62;
 
 const BASE62_CHARS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
 
-const UPPERCASE_OFFSET: u8 = 10;
-const LOWERCASE_OFFSET: u8 = 36;
+// The largest power of 62 that fits in a u32, and its exponent.
+const CHUNK_DIGITS: usize = 5;
+const BASE_POW_CHUNK: u64 = BASE * BASE * BASE * BASE * BASE;
 
+// Marks bytes outside the alphabet in `DECODE_TABLE`.
+const INVALID: u8 = 0xff;
 
-/// Calculate the actual numerical value of a base62 character.
-fn base62_value(digit: &u8) -> u8 {
-    if *digit >= b'0' && *digit <= b'9' {
-        digit - b'0'
-    } else if *digit >= b'A' && *digit <= b'Z' {
-        UPPERCASE_OFFSET + (digit - b'A')
-    } else {
-        LOWERCASE_OFFSET + (digit - b'a')
+// Maps every byte to its base62 value, or `INVALID`. Indexing this replaces a chain of range
+// checks per character on the decode hot path.
+const DECODE_TABLE: [u8; 256] = build_decode_table();
+
+const fn build_decode_table() -> [u8; 256] {
+    let mut table = [INVALID; 256];
+    let mut i = 0;
+    while i < BASE62_CHARS.len() {
+        table[BASE62_CHARS[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+/// Calculate the actual numerical value of a base62 character, or `None` if the byte is not part
+/// of the base62 alphabet.
+fn base62_value(digit: u8) -> Option<u8> {
+    match DECODE_TABLE[digit as usize] {
+        INVALID => None,
+        value => Some(value),
     }
 }
 
 
+// Upper bounds for the generic encode/decode routines. These comfortably cover every KSUID
+// variant in the crate and let us keep the working buffers on the stack.
+const MAX_BYTES: usize = 64;
+const MAX_ENCODED: usize = 128;
+
 /// encode the given 20 byte array into a heap allocated base62 string.
-/// The method used is a bit.. odd for rust. This is directly ported from the segmentio/ksuid
-/// golang version which does a bunch of performance hacks. In order to avoid thinking about it
-/// too much I've replicated that method wholesale.
 pub fn encode(src: &[u8; 20]) -> String {
+    String::from_utf8(encode_array(src).to_vec()).unwrap()
+}
+
+/// encode the given 20 byte array into a stack allocated array of base62 ASCII characters.
+pub fn encode_array(src: &[u8; 20]) -> [u8; 27] {
+    let mut dst = [0u8; 27];
+    encode_into(src, &mut dst);
+    dst
+}
+
+/// encode an arbitrary byte slice (whose length is a multiple of 4) into a base62 string of
+/// exactly `encoded_len` characters, left padded with '0'.
+pub fn encode_bytes(src: &[u8], encoded_len: usize) -> String {
+    let mut dst: Vec<u8> = iter::repeat_n(b'0', encoded_len).collect();
+    encode_into(src, &mut dst);
+    String::from_utf8(dst).unwrap()
+}
+
+/// encode an arbitrary byte slice (whose length is a multiple of 4) into `dst` as base62 ASCII,
+/// left padded with '0' to the full width of `dst`.
+/// This started life as a direct port of segmentio/ksuid's golang encoder, which does schoolbook
+/// long division by 62 over u32 limbs, one output digit per pass. We now divide by 62^5 instead,
+/// which still fits a u32 so each step stays a u64 by u32 division, and peel five digits off
+/// every remainder: a 20 byte ksuid takes 6 passes over the limbs rather than 27.
+pub fn encode_into(src: &[u8], dst: &mut [u8]) {
+    assert!(src.len().is_multiple_of(4) && src.len() <= MAX_BYTES);
     let src_base = 4294967296;
-    let dst_base = BASE;
-
-    let mut dst: Vec<u8> = iter::repeat(b'0').take(27).collect();
-
-    // As per the golang version, this is an O(n^2) problem, but we take N from 27 down to
-    // 5 by collescing the bytes into 5 unsigned 32bit integers.
-    let mut parts: [u32; 5] = [
-        BigEndian::read_u32(&src[0..]),
-        BigEndian::read_u32(&src[4..]),
-        BigEndian::read_u32(&src[8..]),
-        BigEndian::read_u32(&src[12..]),
-        BigEndian::read_u32(&src[16..]),
-    ];
-
-    // This horrible C-ish code is to avoid allocating extra heap allocations to store each step in
-    // the reducation. Instead we track several different offsets. I'm sorry it's come to this.
+
+    for d in dst.iter_mut() {
+        *d = b'0';
+    }
+
+    let mut parts = [0u32; MAX_BYTES / 4];
+    let mut parts_len = src.len() / 4;
+    for (i, part) in parts.iter_mut().take(parts_len).enumerate() {
+        *part = BigEndian::read_u32(&src[i * 4..]);
+    }
+
+    // Same offset juggling as the original: the quotient of each pass is written back over the
+    // front of `parts` so nothing is allocated.
     let mut bq_index;
-    let mut parts_len = 5;
     let mut n = dst.len();
     let mut remainder;
-    while parts_len > 0 {
+    while parts_len > 0 && n > 0 {
         bq_index = 0;
         remainder = 0;
         for p_index in 0..parts_len {
             let value = u64::from(parts[p_index]) + (remainder * src_base);
-            let digit = value / dst_base;
-            remainder = value % dst_base;
+            let digit = value / BASE_POW_CHUNK;
+            remainder = value % BASE_POW_CHUNK;
             if bq_index > 0 || digit != 0 {
                 parts[bq_index] = digit as u32;
                 bq_index += 1;
             }
         }
-        n -= 1;
-        dst[n] = BASE62_CHARS[remainder as usize];
+        // The remainder holds the next CHUNK_DIGITS digits, least significant first. Any that
+        // fall off the front of `dst` are leading zeros once the value fits.
+        for _ in 0..CHUNK_DIGITS {
+            if n == 0 {
+                break;
+            }
+            n -= 1;
+            dst[n] = BASE62_CHARS[(remainder % BASE) as usize];
+            remainder /= BASE;
+        }
         parts_len = bq_index;
     }
-    String::from_utf8(dst).unwrap()
 }
 
-/// Decode a base64 encoded string into a vector of bytes. Once again, this is ripped wholesale
-/// from segmentio/ksuid. It has the same basic structure, but reverses the encode operation.
+/// Decode a base62 encoded string into a vector of bytes.
 pub fn decode(src: &str) -> Result<Vec<u8>, errors::KSUIDError> {
+    decode_bytes(src.as_bytes(), 27, 20)
+}
+
+/// Decode exactly `encoded_len` base62 characters into `byte_len` bytes. Once again, this is
+/// ripped wholesale from segmentio/ksuid. It has the same basic structure, but reverses the
+/// encode operation.
+///
+/// Any input yields `Ok` or an error, never a panic: the length is checked before anything is
+/// indexed and every byte is validated against the alphabet, so multibyte UTF-8 and arbitrary
+/// binary are rejected up front.
+pub fn decode_bytes(src: &[u8], encoded_len: usize, byte_len: usize) -> Result<Vec<u8>, errors::KSUIDError> {
+    assert!(encoded_len <= MAX_ENCODED);
     let src_base = BASE;
     let dst_base = 4294967296;
 
-    if src.len() < 27 {
-        return Err(errors::KSUIDError::InvalidBase62Character{value: src.to_owned()});
+    if src.len() != encoded_len {
+        return Err(errors::KSUIDError::InvalidBase62Length{
+            value: String::from_utf8_lossy(src).into_owned(),
+            expected: encoded_len,
+            actual: src.len(),
+        });
     }
 
-    let mut result: Vec<u8> = iter::repeat(0).take(20).collect();
+    let mut result: Vec<u8> = iter::repeat_n(0, byte_len).collect();
     // I stack allocate the fool
-    let mut parts: [u8;27] = [0; 27];
+    let mut parts = [0u8; MAX_ENCODED];
     let mut parts_len = 0;
-    for (i, b) in src.as_bytes().iter().map(base62_value).enumerate().take(27) {
-        parts[i] = b;
+    for (i, &c) in src.iter().enumerate() {
+        parts[i] = base62_value(c).ok_or_else(|| {
+            errors::KSUIDError::InvalidBase62Character{value: String::from_utf8_lossy(src).into_owned(), position: i}
+        })?;
         parts_len += 1;
     }
 
@@ -104,7 +167,8 @@ pub fn decode(src: &str) -> Result<Vec<u8>, errors::KSUIDError> {
             }
         }
         if n < 4 {
-            return Err(errors::KSUIDError::InvalidBase62Length{value: src.to_owned()});
+            // The value doesn't fit in byte_len bytes.
+            return Err(errors::KSUIDError::Base62Overflow{value: String::from_utf8_lossy(src).into_owned()});
         }
 
         result[n-4] = (remainder >> 24) as u8;
@@ -124,6 +188,64 @@ mod tests {
     use rand;
     use rand::Rng;
 
+    // The original one-digit-per-pass encoder, kept as the reference the chunked one must match.
+    fn encode_reference(src: &[u8], dst: &mut [u8]) {
+        for d in dst.iter_mut() {
+            *d = b'0';
+        }
+        let mut parts: Vec<u32> = src.chunks(4).map(BigEndian::read_u32).collect();
+        let mut n = dst.len();
+        while !parts.is_empty() {
+            let mut quotient = Vec::new();
+            let mut remainder = 0u64;
+            for &part in &parts {
+                let value = u64::from(part) + (remainder << 32);
+                let digit = value / BASE;
+                remainder = value % BASE;
+                if !quotient.is_empty() || digit != 0 {
+                    quotient.push(digit as u32);
+                }
+            }
+            n -= 1;
+            dst[n] = BASE62_CHARS[remainder as usize];
+            parts = quotient;
+        }
+    }
+
+    #[test]
+    fn b62_encode_matches_reference() {
+        let mut rng = rand::thread_rng();
+        let mut inputs = vec![vec![0u8; 20], vec![0xff; 20], vec![0xff; 36], vec![0; 4]];
+        for len in [4, 20, 36, 64].iter() {
+            for _ in 0..200 {
+                let mut bytes = vec![0u8; *len];
+                rng.fill_bytes(&mut bytes);
+                // Vary the magnitude so every pass count gets exercised.
+                let zeros = rng.gen_range(0, *len);
+                for b in &mut bytes[..zeros] {
+                    *b = 0;
+                }
+                inputs.push(bytes);
+            }
+        }
+        for bytes in inputs {
+            let width = match bytes.len() { 20 => 27, 36 => 49, len => len * 2 };
+            let mut expected = vec![0u8; width];
+            let mut actual = vec![0u8; width];
+            encode_reference(&bytes, &mut expected);
+            encode_into(&bytes, &mut actual);
+            assert_eq!(actual, expected, "{:?}", bytes);
+        }
+    }
+
+    #[test]
+    fn b62_decode_table() {
+        for b in 0..=255u8 {
+            let expected = BASE62_CHARS.iter().position(|&c| c == b).map(|i| i as u8);
+            assert_eq!(base62_value(b), expected, "byte {}", b);
+        }
+    }
+
     #[test]
     fn b62_roundtrip() {
         let mut bytes = [0u8; 20];
@@ -133,6 +255,53 @@ mod tests {
         assert_eq!(decoded.as_slice(), &bytes);
     }
 
+    #[test]
+    fn b62_roundtrip_wide() {
+        let mut bytes = [0u8; 36];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let encoded = encode_bytes(&bytes, 49);
+        assert_eq!(encoded.len(), 49);
+        let decoded = decode_bytes(encoded.as_bytes(), 49, 36).unwrap();
+        assert_eq!(decoded.as_slice(), &bytes[..]);
+    }
+
+    #[test]
+    fn b62_decode_rejects_bad_lengths() {
+        let valid = encode(&[7u8; 20]);
+        for len in [0, 1, 26, 28, 54, 1000].iter() {
+            let input: String = valid.chars().cycle().take(*len).collect();
+            assert!(decode(&input).is_err(), "length {}", len);
+        }
+    }
+
+    #[test]
+    fn b62_decode_never_panics() {
+        // Substitute every possible byte at every position of a valid encoding.
+        let valid = encode(&[0x5a; 20]).into_bytes();
+        for pos in 0..valid.len() {
+            for b in 0..=255u8 {
+                let mut input = valid.clone();
+                input[pos] = b;
+                match decode_bytes(&input, 27, 20) {
+                    Ok(bytes) => {
+                        let mut arr = [0u8; 20];
+                        arr.copy_from_slice(&bytes);
+                        assert_eq!(encode(&arr).into_bytes(), input);
+                    }
+                    Err(_) => assert!(base62_value(b).is_none() || pos == 0),
+                }
+            }
+        }
+        // Multibyte UTF-8 that adds up to 27 bytes, or 27 chars that exceed it.
+        assert!(decode("é0000000000000000000000000").is_err());
+        assert!(decode("é00000000000000000000000000").is_err());
+        assert!(decode("💯00000000000000000000000").is_err());
+        // Values that overflow 160 bits.
+        assert!(decode("aWgEPTl1tmebfsQzFP4bxwgy80V").is_ok());
+        assert!(decode("aWgEPTl1tmebfsQzFP4bxwgy80W").is_err());
+        assert!(decode("zzzzzzzzzzzzzzzzzzzzzzzzzzz").is_err());
+    }
+
     #[bench]
     fn bench_b62_encode(b: &mut Bencher) {
         let mut bytes = [0u8; 20];
@@ -0,0 +1,123 @@
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use byteorder::{BigEndian, ByteOrder};
+use ksuid::{ceil_ksuid_time, BYTE_LENGTH, KSUID, TIMESTAMP_LENGTH};
+use std::collections::{btree_map, btree_set, BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::ops::{Bound, Range};
+
+/// Time range lookups on ordered collections keyed by `KSUID`. Since ids sort by their
+/// timestamp, the ids created within a range of times are a contiguous run of keys, and the
+/// standard `range` iterator finds it in logarithmic time.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::{Duration, Utc};
+/// use ksuid::{KsuidRangeExt, KSUID};
+/// use std::collections::BTreeMap;
+///
+/// let mut cache = BTreeMap::new();
+/// cache.insert(KSUID::new(), "fresh");
+/// let now = Utc::now();
+/// let recent: Vec<_> = cache.range_by_time(now - Duration::minutes(5)..now + Duration::seconds(1)).collect();
+/// assert_eq!(recent.len(), 1);
+/// ```
+pub trait KsuidRangeExt {
+    /// The iterator `range_by_time` returns.
+    type Iter<'a> where Self: 'a;
+
+    /// Iterate, in order, over the entries whose ids were created in `times`. Ids only carry
+    /// whole seconds, so an id is in the range if its second is, matching
+    /// `sort::range_by_time`: a fractional start skips the second it falls in, and a fractional
+    /// end includes it. An empty or reversed range yields nothing.
+    fn range_by_time(&self, times: Range<DateTime<Utc>>) -> Self::Iter<'_>;
+}
+
+// Key bounds covering the ids created in `times`. The start is never after the end, since
+// `range` panics on reversed bounds.
+fn key_bounds(times: &Range<DateTime<Utc>>) -> (Bound<KSUID>, Bound<KSUID>) {
+    // The smallest id of a whole second from `ceil_ksuid_time`, or `None` past the last one.
+    let first_of = |seconds: i64| {
+        let seconds = u32::try_from(seconds).ok()?;
+        let mut bytes = [0u8; BYTE_LENGTH];
+        BigEndian::write_u32(&mut bytes[..TIMESTAMP_LENGTH], seconds);
+        Some(KSUID(bytes))
+    };
+    let start = match first_of(ceil_ksuid_time(times.start)) {
+        Some(start) => start,
+        None => return (Bound::Excluded(KSUID([0xff; BYTE_LENGTH])), Bound::Unbounded),
+    };
+    match first_of(ceil_ksuid_time(times.end)) {
+        Some(end) => (Bound::Included(start), Bound::Excluded(start.max(end))),
+        None => (Bound::Included(start), Bound::Unbounded),
+    }
+}
+
+impl<V> KsuidRangeExt for BTreeMap<KSUID, V> {
+    type Iter<'a> = btree_map::Range<'a, KSUID, V> where V: 'a;
+
+    fn range_by_time(&self, times: Range<DateTime<Utc>>) -> Self::Iter<'_> {
+        self.range(key_bounds(&times))
+    }
+}
+
+impl KsuidRangeExt for BTreeSet<KSUID> {
+    type Iter<'a> = btree_set::Range<'a, KSUID>;
+
+    fn range_by_time(&self, times: Range<DateTime<Utc>>) -> Self::Iter<'_> {
+        self.range(key_bounds(&times))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use ksuid::from_ksuid_time;
+
+    fn at(seconds: i64, payload: u8) -> KSUID {
+        KSUID::from_parts(DateTime::from_timestamp(seconds, 0).unwrap(), &[payload; 16]).unwrap()
+    }
+
+    #[test]
+    fn test_range_by_time() {
+        let t = |seconds| DateTime::from_timestamp(seconds, 0).unwrap();
+        let set: BTreeSet<KSUID> = vec![at(1_600_000_000, 0), at(1_600_000_000, 0xff), at(1_600_000_001, 0), at(1_600_000_002, 9)]
+            .into_iter()
+            .collect();
+        assert_eq!(set.range_by_time(t(1_600_000_000)..t(1_600_000_002)).count(), 3);
+        assert_eq!(set.range_by_time(t(1_600_000_001)..t(1_600_000_003)).count(), 2);
+        // A fractional start skips its second, a fractional end includes its second.
+        let half = Duration::milliseconds(500);
+        assert_eq!(set.range_by_time(t(1_600_000_000) + half..t(1_600_000_001) + half).count(), 1);
+        assert_eq!(set.range_by_time(t(1_600_000_001)..t(1_600_000_001)).count(), 0);
+        assert_eq!(set.range_by_time(t(1_600_000_002)..t(1_600_000_000)).count(), 0);
+
+        let map: BTreeMap<KSUID, usize> = set.iter().cloned().zip(0..).collect();
+        let values: Vec<usize> = map.range_by_time(t(1_600_000_001)..t(1_600_000_002)).map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![2]);
+    }
+
+    #[test]
+    fn test_range_by_time_matches_sort() {
+        let t = |seconds| DateTime::from_timestamp(seconds, 0).unwrap();
+        let ids: Vec<KSUID> = (0..4).flat_map(|s| vec![at(1_600_000_000 + s, 0), at(1_600_000_000 + s, 0xff)]).collect();
+        let set: BTreeSet<KSUID> = ids.iter().cloned().collect();
+        let half = Duration::milliseconds(500);
+        for &(start, end) in [(half, Duration::seconds(1) + half), (Duration::zero(), half), (half, Duration::seconds(3))].iter() {
+            let times = t(1_600_000_000) + start..t(1_600_000_000) + end;
+            let expected = &ids[::sort::range_by_time(&ids, times.clone())];
+            assert_eq!(set.range_by_time(times).cloned().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_range_by_time_clamps() {
+        let last = from_ksuid_time(u32::MAX);
+        let set: BTreeSet<KSUID> = vec![KSUID([0; 20]), KSUID([0xff; 20])].into_iter().collect();
+        assert_eq!(set.range_by_time(from_ksuid_time(0) - Duration::days(1)..from_ksuid_time(1)).count(), 1);
+        assert_eq!(set.range_by_time(last..last + Duration::days(1)).count(), 1);
+        assert_eq!(set.range_by_time(last + Duration::days(1)..last + Duration::days(2)).count(), 0);
+    }
+}
@@ -0,0 +1,93 @@
+//! CBOR representation for use with `#[serde(with = "ksuid::cbor")]` and `ciborium`: the 20 raw
+//! bytes as a CBOR byte string wrapped in tag `CBOR_TAG`. Deserializing accepts the value with
+//! or without the tag, but rejects any other tag.
+//!
+//! ```
+//! # extern crate ciborium;
+//! # extern crate ksuid;
+//! # extern crate serde;
+//! use ksuid::KSUID;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Reading {
+//!     #[serde(with = "ksuid::cbor")]
+//!     id: KSUID,
+//!     celsius: f32,
+//! }
+//!
+//! # fn main() {
+//! let reading = Reading { id: KSUID::new(), celsius: 21.5 };
+//! let mut encoded = Vec::new();
+//! ciborium::into_writer(&reading, &mut encoded).unwrap();
+//! let decoded: Reading = ciborium::from_reader(&encoded[..]).unwrap();
+//! assert_eq!(decoded.id, reading.id);
+//! # }
+//! ```
+//!
+//! The tag only survives serializers that understand `ciborium`'s tag encoding; elsewhere the id
+//! comes out as plain bytes under an internal wrapper, so keep this to CBOR fields.
+
+use ciborium::tag::Accepted;
+use ksuid::KSUID;
+use raw_bytes::RawBytes;
+use serde_crate::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The tag written ahead of every id. No tag is registered for KSUIDs with IANA; this one is
+/// from the first come, first served range (it spells "ksui" in ASCII) and is not registered
+/// either, so check it against the other tags your decoders know about.
+pub const CBOR_TAG: u64 = 0x6b73_7569;
+
+/// Serialize `uid` as a tagged CBOR byte string.
+pub fn serialize<S: Serializer>(uid: &KSUID, serializer: S) -> Result<S::Ok, S::Error> {
+    Accepted::<_, CBOR_TAG>(RawBytes(uid.0)).serialize(serializer)
+}
+
+/// Deserialize a 20 byte CBOR byte string, tagged with `CBOR_TAG` or untagged.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<KSUID, D::Error> {
+    let Accepted::<RawBytes, CBOR_TAG>(bytes) = Accepted::deserialize(deserializer)?;
+    Ok(KSUID(bytes.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ciborium::tag::Required;
+    use ciborium::value::Value;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Wrapper(#[serde(with = "super")] KSUID);
+
+    fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        ciborium::into_writer(value, &mut encoded).unwrap();
+        encoded
+    }
+
+    #[test]
+    fn test_cbor_tagged_bytes() {
+        let uid = KSUID::new();
+        let encoded = encode(&Wrapper(uid));
+        // Tag 0x6b737569 (major type 6, 4 byte argument), then a 20 byte byte string.
+        assert_eq!(encoded[..5], [0xda, 0x6b, 0x73, 0x75, 0x69]);
+        assert_eq!(encoded[5], 0x54);
+        assert_eq!(encoded[6..], uid.0);
+        let value: Value = ciborium::from_reader(&encoded[..]).unwrap();
+        assert_eq!(value, Value::Tag(CBOR_TAG, Box::new(Value::Bytes(uid.0.to_vec()))));
+        assert_eq!(ciborium::from_reader::<Wrapper, _>(&encoded[..]).unwrap(), Wrapper(uid));
+    }
+
+    #[test]
+    fn test_cbor_untagged_and_foreign_tags() {
+        let uid = KSUID::new();
+        let untagged = encode(&Value::Bytes(uid.0.to_vec()));
+        assert_eq!(ciborium::from_reader::<Wrapper, _>(&untagged[..]).unwrap(), Wrapper(uid));
+
+        let foreign = encode(&Required::<_, 37>(Value::Bytes(uid.0.to_vec())));
+        assert!(ciborium::from_reader::<Wrapper, _>(&foreign[..]).is_err());
+        let short = encode(&Value::Bytes(vec![0; 16]));
+        assert!(ciborium::from_reader::<Wrapper, _>(&short[..]).is_err());
+        let text = encode(&Value::Text(uid.to_base62()));
+        assert!(ciborium::from_reader::<Wrapper, _>(&text[..]).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(feature = "chacha")]
+use rand_chacha::ChaCha20Rng;
+#[cfg(feature = "chacha")]
+use rand_core::{OsRng, RngCore, SeedableRng};
+#[cfg(feature = "chacha")]
+use std::fmt;
+
+/// Configuration for `GeneratorOptions::chacha20`.
+#[cfg(feature = "chacha")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChaCha20Options {
+    /// Reseed from the operating system after this many bytes of output. Must be nonzero. The
+    /// default of 64 KiB, about 4000 ids, matches `rand`'s thread generator.
+    pub reseed_interval: u64,
+}
+
+/// Configuration for `GeneratorOptions::chacha20`, which can only be set with the `chacha`
+/// feature. Without it this type has no values, so the field is always `None`, but code naming
+/// the field keeps compiling when another crate enables the feature.
+#[cfg(not(feature = "chacha"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChaCha20Options {
+    _private: (),
+}
+
+#[cfg(feature = "chacha")]
+impl Default for ChaCha20Options {
+    fn default() -> Self {
+        ChaCha20Options { reseed_interval: 64 * 1024 }
+    }
+}
+
+// A ChaCha20 stream seeded from `OsRng`, replaced with a freshly seeded one every
+// `reseed_interval` bytes.
+#[cfg(feature = "chacha")]
+pub(crate) struct ChaChaSource {
+    rng: ChaCha20Rng,
+    reseed_interval: u64,
+    until_reseed: u64,
+}
+
+#[cfg(feature = "chacha")]
+impl ChaChaSource {
+    pub(crate) fn new(options: ChaCha20Options) -> Self {
+        assert!(options.reseed_interval > 0, "chacha20 reseed interval must be nonzero");
+        ChaChaSource {
+            rng: ChaCha20Rng::from_rng(OsRng).expect("failed to seed chacha20 from the OS"),
+            reseed_interval: options.reseed_interval,
+            until_reseed: options.reseed_interval,
+        }
+    }
+
+    pub(crate) fn fill(&mut self, dst: &mut [u8]) {
+        let len = dst.len() as u64;
+        if len > self.until_reseed {
+            self.rng = ChaCha20Rng::from_rng(OsRng).expect("failed to reseed chacha20 from the OS");
+            self.until_reseed = self.reseed_interval;
+        }
+        self.rng.fill_bytes(dst);
+        self.until_reseed = self.until_reseed.saturating_sub(len);
+    }
+}
+
+// Keep the generator state out of logs.
+#[cfg(feature = "chacha")]
+impl fmt::Debug for ChaChaSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChaChaSource")
+            .field("reseed_interval", &self.reseed_interval)
+            .field("until_reseed", &self.until_reseed)
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "chacha"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chacha_reseeds() {
+        let mut source = ChaChaSource::new(ChaCha20Options { reseed_interval: 32 });
+        let seed = source.rng.get_seed();
+        let mut bytes = [0u8; 16];
+        source.fill(&mut bytes);
+        source.fill(&mut bytes);
+        assert_eq!(source.rng.get_seed(), seed);
+        assert_eq!(source.until_reseed, 0);
+        source.fill(&mut bytes);
+        assert_ne!(source.rng.get_seed(), seed);
+        assert_eq!(source.until_reseed, 16);
+    }
+}
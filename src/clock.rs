@@ -0,0 +1,42 @@
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use std::fmt;
+use std::sync::Arc;
+
+/// A wall clock reading earlier than one a `Generator` already minted with, passed to
+/// `ClockRegressionPolicy::Callback`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockRegression {
+    /// The latest wall clock time the generator has minted with.
+    pub previous: DateTime<Utc>,
+    /// The current wall clock time, after any `timestamp_granularity` rounding.
+    pub now: DateTime<Utc>,
+}
+
+/// What a `Generator` does when the wall clock reads earlier than it did for a previous id, e.g.
+/// after NTP steps the clock back. Set through `GeneratorOptions::clock_regression`.
+#[derive(Clone, Default)]
+pub enum ClockRegressionPolicy {
+    /// Mint with the wall clock as usual, so new ids may sort before earlier ones. With the
+    /// `counter` or `hlc` options the timestamp is held regardless.
+    #[default]
+    Ignore,
+    /// Keep issuing the latest timestamp until the clock catches up.
+    Hold,
+    /// Refuse to mint: `try_next_id` and `try_next_batch` fail with
+    /// `KSUIDError::ClockRegression`, while `next_id` and `next_batch` panic.
+    Error,
+    /// Call the function, e.g. to log or alert, then hold the latest timestamp as `Hold` does.
+    Callback(Arc<dyn Fn(&ClockRegression) + Send + Sync>),
+}
+
+impl fmt::Debug for ClockRegressionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ClockRegressionPolicy::Ignore => f.write_str("Ignore"),
+            ClockRegressionPolicy::Hold => f.write_str("Hold"),
+            ClockRegressionPolicy::Error => f.write_str("Error"),
+            ClockRegressionPolicy::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
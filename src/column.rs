@@ -0,0 +1,181 @@
+use byteorder::{BigEndian, ByteOrder};
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use ksuid::{ceil_ksuid_time, BYTE_LENGTH, KSUID, TIMESTAMP_LENGTH};
+use std::iter::FromIterator;
+use std::ops::Range;
+
+const PAYLOAD_LENGTH: usize = BYTE_LENGTH - TIMESTAMP_LENGTH;
+
+/// Columnar (struct of arrays) storage for many `KSUID`s: timestamps and payloads live in two
+/// separate contiguous arrays. Scans that only look at creation time, like time-range filters,
+/// read 4 bytes per id instead of 20 and run over a plain `[u32]` the compiler can vectorize.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::Duration;
+/// use ksuid::{KsuidColumn, KSUID};
+///
+/// let now = KSUID::new().timestamp();
+/// let ids: Vec<KSUID> = (0..10)
+///     .map(|h| KSUID::from_parts(now - Duration::hours(h), &[h as u8; 16]).unwrap())
+///     .collect();
+/// let column = KsuidColumn::from(&ids[..]);
+/// let recent = column.filter_time_range(now - Duration::hours(3)..now + Duration::seconds(1));
+/// assert_eq!(recent.to_vec(), &ids[..4]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KsuidColumn {
+    timestamps: Vec<u32>,
+    payloads: Vec<[u8; PAYLOAD_LENGTH]>,
+}
+
+impl KsuidColumn {
+
+    /// Create an empty column.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty column with room for `capacity` ids.
+    pub fn with_capacity(capacity: usize) -> Self {
+        KsuidColumn {
+            timestamps: Vec::with_capacity(capacity),
+            payloads: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Append an id.
+    pub fn push(&mut self, uid: KSUID) {
+        self.timestamps.push(BigEndian::read_u32(&uid.0));
+        let mut payload = [0u8; PAYLOAD_LENGTH];
+        payload.copy_from_slice(uid.payload());
+        self.payloads.push(payload);
+    }
+
+    /// Return the id at `index`, if there is one.
+    pub fn get(&self, index: usize) -> Option<KSUID> {
+        let timestamp = *self.timestamps.get(index)?;
+        let mut bytes = [0u8; BYTE_LENGTH];
+        BigEndian::write_u32(&mut bytes, timestamp);
+        bytes[TIMESTAMP_LENGTH..].copy_from_slice(&self.payloads[index]);
+        Some(KSUID(bytes))
+    }
+
+    /// The number of ids in the column.
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    /// Whether the column holds no ids.
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+
+    /// The raw timestamp column, in seconds since `EPOCH_START` as stored in each id.
+    pub fn timestamps(&self) -> &[u32] {
+        &self.timestamps
+    }
+
+    /// The payload column.
+    pub fn payloads(&self) -> &[[u8; 16]] {
+        &self.payloads
+    }
+
+    /// Iterate over the ids, reassembling each one.
+    pub fn iter(&self) -> impl Iterator<Item = KSUID> + '_ {
+        (0..self.len()).map(move |i| self.get(i).unwrap())
+    }
+
+    /// Reassemble every id into a row oriented `Vec`.
+    pub fn to_vec(&self) -> Vec<KSUID> {
+        self.iter().collect()
+    }
+
+    /// Return, for every id, whether it was created within `range` (end exclusive). Only the
+    /// timestamp column is read, in a branch free loop.
+    pub fn time_range_mask(&self, range: Range<DateTime<Utc>>) -> Vec<bool> {
+        let start = ceil_ksuid_time(range.start);
+        let end = ceil_ksuid_time(range.end);
+        self.timestamps.iter().map(|&t| (i64::from(t) >= start) & (i64::from(t) < end)).collect()
+    }
+
+    /// Return a new column holding just the ids created within `range` (end exclusive), in their
+    /// original order.
+    pub fn filter_time_range(&self, range: Range<DateTime<Utc>>) -> KsuidColumn {
+        let mask = self.time_range_mask(range);
+        let mut filtered = KsuidColumn::new();
+        for (i, _) in mask.iter().enumerate().filter(|&(_, &keep)| keep) {
+            filtered.timestamps.push(self.timestamps[i]);
+            filtered.payloads.push(self.payloads[i]);
+        }
+        filtered
+    }
+}
+
+impl<'a> From<&'a [KSUID]> for KsuidColumn {
+    fn from(ids: &'a [KSUID]) -> Self {
+        ids.iter().cloned().collect()
+    }
+}
+
+impl<'a> From<&'a KsuidColumn> for Vec<KSUID> {
+    fn from(column: &'a KsuidColumn) -> Self {
+        column.to_vec()
+    }
+}
+
+impl FromIterator<KSUID> for KsuidColumn {
+    fn from_iter<I: IntoIterator<Item = KSUID>>(iter: I) -> Self {
+        let mut column = KsuidColumn::new();
+        column.extend(iter);
+        column
+    }
+}
+
+impl Extend<KSUID> for KsuidColumn {
+    fn extend<I: IntoIterator<Item = KSUID>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.timestamps.reserve(iter.size_hint().0);
+        self.payloads.reserve(iter.size_hint().0);
+        for uid in iter {
+            self.push(uid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use ksuid::from_ksuid_time;
+
+    #[test]
+    fn test_column_roundtrip() {
+        let ids: Vec<KSUID> = (0..100).map(|_| KSUID::new()).collect();
+        let column = KsuidColumn::from(&ids[..]);
+        assert_eq!(column.len(), 100);
+        assert_eq!(column.to_vec(), ids);
+        assert_eq!(column.get(7), Some(ids[7]));
+        assert_eq!(column.get(100), None);
+        assert_eq!(column.payloads()[3], ids[3].payload());
+    }
+
+    #[test]
+    fn test_column_time_range() {
+        let ids: Vec<KSUID> = [0u32, 10, 20, 30, u32::MAX].iter().map(|&t| {
+            KSUID::from_parts(from_ksuid_time(t), &[0xAB; 16]).unwrap()
+        }).collect();
+        let column: KsuidColumn = ids.iter().cloned().collect();
+        let at = |t: u32| from_ksuid_time(t);
+        assert_eq!(column.time_range_mask(at(10)..at(30)), vec![false, true, true, false, false]);
+        // Fractional bounds round up to the next whole second.
+        let mask = column.time_range_mask(at(9) + Duration::milliseconds(1)..at(20) + Duration::milliseconds(1));
+        assert_eq!(mask, vec![false, true, true, false, false]);
+        // Bounds outside the representable range clamp rather than wrap.
+        let all = column.filter_time_range(at(0) - Duration::days(1)..at(u32::MAX) + Duration::days(1));
+        assert_eq!(all, column);
+        assert!(column.filter_time_range(at(30)..at(10)).is_empty());
+    }
+}
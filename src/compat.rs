@@ -0,0 +1,100 @@
+//! Cross-implementation compatibility vectors. Each vector pairs the raw bytes of a KSUID with
+//! its base62 encoding and unix timestamp, so any implementation (in any language) can be checked
+//! for byte-for-byte parity with this crate and with segmentio/ksuid, the reference
+//! implementation.
+use ksuid::KSUID;
+
+/// A known `(bytes, base62, timestamp)` triple.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TestVector {
+    /// Where the vector comes from.
+    pub source: &'static str,
+    /// The 20 raw bytes.
+    pub bytes: [u8; 20],
+    /// The canonical 27 character base62 encoding.
+    pub base62: &'static str,
+    /// The embedded timestamp as unix seconds.
+    pub unix_timestamp: i64,
+}
+
+const SEGMENTIO: &str = "segmentio/ksuid";
+const BOUNDARY: &str = "boundary value, checked against an arbitrary-precision reference";
+
+macro_rules! hex {
+    ($s:expr) => {{
+        let s: &[u8] = $s;
+        let mut out = [0u8; 20];
+        let mut i = 0;
+        while i < 20 {
+            out[i] = (nibble(s[2 * i]) << 4) | nibble(s[2 * i + 1]);
+            i += 1;
+        }
+        out
+    }};
+}
+
+const fn nibble(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        _ => c - b'a' + 10,
+    }
+}
+
+/// The vector corpus. The segmentio/ksuid vectors are ids that appear in that project's
+/// documentation and tests (its nil and max values, and sample output of its CLI); the boundary
+/// vectors pin down padding, carries and the extremes of the value range. Raw bytes were cross
+/// checked with an independent arbitrary-precision base62 conversion.
+pub const VECTORS: &[TestVector] = &[
+    TestVector { source: SEGMENTIO, bytes: hex!(b"0000000000000000000000000000000000000000"), base62: "000000000000000000000000000", unix_timestamp: 1400000000 },
+    TestVector { source: SEGMENTIO, bytes: hex!(b"ffffffffffffffffffffffffffffffffffffffff"), base62: "aWgEPTl1tmebfsQzFP4bxwgy80V", unix_timestamp: 5694967295 },
+    TestVector { source: SEGMENTIO, bytes: hex!(b"0669f7efb5a1cd34b5f99d1154fb6853345c9735"), base62: "0ujtsYcgvSTl8PAuAdqWYSMnLOv", unix_timestamp: 1507608047 },
+    TestVector { source: SEGMENTIO, bytes: hex!(b"0669f60567ad536455c1813d788f57ca54679412"), base62: "0ujsswThIGTUYm2K8FjOOfXtY1K", unix_timestamp: 1507607557 },
+    TestVector { source: SEGMENTIO, bytes: hex!(b"0669f605d9719f3234fe964b6376cb6b9a2e5e86"), base62: "0ujsszwN8NRY24YaXiTIE2VWDTS", unix_timestamp: 1507607557 },
+    TestVector { source: SEGMENTIO, bytes: hex!(b"0669f6058f96a1c8738e34e4cf0b7d88e79e35f6"), base62: "0ujssxh0cECutqzMgbtXSGnjorm", unix_timestamp: 1507607557 },
+    TestVector { source: SEGMENTIO, bytes: hex!(b"0669f605d0e72e811585c272ad73baf04759d4ad"), base62: "0ujsszgFvbiEr7CDgE3z8MAUPFt", unix_timestamp: 1507607557 },
+    TestVector { source: SEGMENTIO, bytes: hex!(b"06cf1b0dfd2403e4e412c7a80ad1b2ec08e46291"), base62: "0yEaNH85uGuB4bz7EoWhX228k65", unix_timestamp: 1514236173 },
+    TestVector { source: BOUNDARY, bytes: hex!(b"000102030405060708090a0b0c0d0e0f10111213"), base62: "0029sS3yqsuDyR1vGFhTuuRAq1b", unix_timestamp: 1400066051 },
+    TestVector { source: BOUNDARY, bytes: hex!(b"ffffffff00000000000000000000000000000000"), base62: "aWgEPLxxrZOFaOlDVFHTB3ZiQOO", unix_timestamp: 5694967295 },
+    TestVector { source: BOUNDARY, bytes: hex!(b"00000000ffffffffffffffffffffffffffffffff"), base62: "000007n42DGM5Tflk9n8mt7Fhc7", unix_timestamp: 1400000000 },
+    TestVector { source: BOUNDARY, bytes: hex!(b"0000000000000000000000000000000000000001"), base62: "000000000000000000000000001", unix_timestamp: 1400000000 },
+    TestVector { source: BOUNDARY, bytes: hex!(b"000000000000000000000000000000000000003e"), base62: "000000000000000000000000010", unix_timestamp: 1400000000 },
+    TestVector { source: BOUNDARY, bytes: hex!(b"8000000000000000000000000000000000000000"), base62: "IGL7CjsVwtKIpwDUchXIyyLU40G", unix_timestamp: 3547483648 },
+];
+
+/// Check this crate against every vector in `VECTORS`: encoding, decoding and the timestamp
+/// accessor. Returns the first vector that disagrees.
+/// # Example
+/// ```
+/// assert!(ksuid::compat::verify_vectors().is_ok());
+/// ```
+pub fn verify_vectors() -> Result<(), &'static TestVector> {
+    for vector in VECTORS {
+        let id = KSUID(vector.bytes);
+        let ok = id.to_base62() == vector.base62
+            && KSUID::from_base62(vector.base62).ok() == Some(id)
+            && id.timestamp().timestamp() == vector.unix_timestamp;
+        if !ok {
+            return Err(vector);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors() {
+        assert_eq!(verify_vectors(), Ok(()));
+    }
+
+    #[test]
+    fn test_vectors_sorted_consistently() {
+        for a in VECTORS {
+            for b in VECTORS {
+                assert_eq!(a.bytes.cmp(&b.bytes), a.base62.cmp(b.base62));
+            }
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Streaming conversion of id columns between representations, for data migrations: CSV with a
+//! header row, or newline delimited JSON objects. Every other field passes through untouched.
+//!
+//! Parsing is strict: base62 must be exactly 27 characters, hex exactly 40 digits in a single
+//! case, raw exactly 20 bytes. The one exception is absent values, an empty CSV field or a JSON
+//! `null`, which are passed through as is. A record that fails to convert is left out of the
+//! output and reported in the returned `ConversionReport`, and conversion carries on with the
+//! next one; only I/O errors stop it.
+//!
+//! ```
+//! use ksuid::convert::{convert_csv, ConvertOptions, Representation};
+//!
+//! let input = "id,name\n0ujsszwN8NRY24YaXiTIE2VWDTS,first\nnot-an-id,second\n";
+//! let mut output = Vec::new();
+//! let options = ConvertOptions {
+//!     fields: vec!["id".to_owned()],
+//!     from: Representation::Base62,
+//!     to: Representation::Hex,
+//! };
+//! let report = convert_csv(input.as_bytes(), &mut output, &options).unwrap();
+//! assert_eq!(String::from_utf8(output).unwrap(), "id,name\n0669f605d9719f3234fe964b6376cb6b9a2e5e86,first\n");
+//! assert_eq!(report.written, 1);
+//! assert_eq!(report.errors[0].line(), 3);
+//! ```
+use csv;
+use errors;
+use failure;
+use ksuid::{BYTE_LENGTH, KSUID};
+use multibase;
+use serde_json::{self, Value};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::str;
+
+/// A form an id can take in a field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Representation {
+    /// The 27 character base62 form.
+    Base62,
+    /// 40 hex digits. All lowercase or all uppercase is accepted; output is lowercase.
+    Hex,
+    /// The 20 raw bytes: the field's bytes as they are in CSV, an array of 20 numbers in JSON.
+    Raw,
+}
+
+impl Representation {
+    fn parse(self, bytes: &[u8]) -> Result<KSUID, errors::KSUIDError> {
+        match self {
+            Representation::Base62 => KSUID::from_base62_bytes(bytes),
+            Representation::Hex => str::from_utf8(bytes)
+                .ok()
+                .and_then(multibase::decode_hex)
+                .map(KSUID)
+                .ok_or_else(|| errors::KSUIDError::InvalidHex { value: String::from_utf8_lossy(bytes).into_owned() }),
+            Representation::Raw if bytes.len() == BYTE_LENGTH => KSUID::from_bytes(bytes),
+            Representation::Raw => Err(errors::KSUIDError::InvalidByteLength { expected: BYTE_LENGTH, actual: bytes.len() }),
+        }
+    }
+
+    fn encode(self, id: &KSUID) -> Vec<u8> {
+        match self {
+            Representation::Base62 => id.to_base62_array().to_vec(),
+            Representation::Hex => multibase::encode_hex(&id.0).into_bytes(),
+            Representation::Raw => id.0.to_vec(),
+        }
+    }
+
+    // `None` if the value has the wrong JSON type for this representation.
+    fn parse_json(self, value: &Value) -> Option<Result<KSUID, errors::KSUIDError>> {
+        match self {
+            Representation::Base62 | Representation::Hex => value.as_str().map(|s| self.parse(s.as_bytes())),
+            Representation::Raw => {
+                let bytes = value
+                    .as_array()?
+                    .iter()
+                    .map(|b| b.as_u64().filter(|&b| b <= 0xff).map(|b| b as u8))
+                    .collect::<Option<Vec<u8>>>()?;
+                Some(self.parse(&bytes))
+            }
+        }
+    }
+
+    fn to_json(self, id: &KSUID) -> Value {
+        match self {
+            Representation::Base62 => Value::String(id.to_base62()),
+            Representation::Hex => Value::String(multibase::encode_hex(&id.0)),
+            Representation::Raw => Value::Array(id.0.iter().map(|&b| Value::from(b)).collect()),
+        }
+    }
+}
+
+/// What to convert: the named fields (CSV header names or top level JSON keys), from one
+/// representation to another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConvertOptions {
+    pub fields: Vec<String>,
+    pub from: Representation,
+    pub to: Representation,
+}
+
+/// A record that couldn't be converted and was left out of the output. Lines are counted from
+/// 1, including any CSV header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecordError {
+    /// The record couldn't be read: invalid CSV, or an NDJSON line that isn't a JSON object.
+    Malformed { line: u64, reason: String },
+    /// The record has no such field.
+    MissingField { line: u64, field: String },
+    /// A JSON field held the wrong type for the source representation, e.g. a number where a
+    /// base62 string was expected.
+    UnexpectedType { line: u64, field: String },
+    /// A field didn't parse in the source representation.
+    InvalidId { line: u64, field: String, error: errors::KSUIDError },
+}
+
+impl RecordError {
+    /// Return the line the record starts on.
+    pub fn line(&self) -> u64 {
+        match *self {
+            RecordError::Malformed { line, .. }
+            | RecordError::MissingField { line, .. }
+            | RecordError::UnexpectedType { line, .. }
+            | RecordError::InvalidId { line, .. } => line,
+        }
+    }
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecordError::Malformed { line, ref reason } => write!(f, "line {}: malformed record: {}", line, reason),
+            RecordError::MissingField { line, ref field } => write!(f, "line {}: missing field {:?}", line, field),
+            RecordError::UnexpectedType { line, ref field } => write!(f, "line {}: field {:?} has the wrong type", line, field),
+            RecordError::InvalidId { line, ref field, ref error } => write!(f, "line {}: field {:?}: {}", line, field, error),
+        }
+    }
+}
+
+impl failure::Fail for RecordError {}
+
+/// The outcome of a conversion.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    /// Records read, not counting a CSV header or blank NDJSON lines.
+    pub records: u64,
+    /// Records converted and written.
+    pub written: u64,
+    /// Every record that was left out, in input order.
+    pub errors: Vec<RecordError>,
+}
+
+/// Convert the `options.fields` columns of CSV read from `input`, writing the result to
+/// `output`. The first row must be a header naming the columns; it is copied to the output.
+/// Fails with `io::ErrorKind::InvalidInput` before writing anything if a field isn't in the
+/// header.
+pub fn convert_csv<R: Read, W: Write>(input: R, output: W, options: &ConvertOptions) -> io::Result<ConversionReport> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(input);
+    let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(output);
+    let headers = reader.byte_headers()?.clone();
+    let columns = options
+        .fields
+        .iter()
+        .map(|field| {
+            headers
+                .iter()
+                .position(|header| header == field.as_bytes())
+                .map(|index| (index, field))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("no column named {:?}", field)))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    writer.write_byte_record(&headers)?;
+
+    let mut report = ConversionReport::default();
+    let mut record = csv::ByteRecord::new();
+    loop {
+        match reader.read_byte_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => {}
+            Err(err) if !err.is_io_error() => {
+                report.records += 1;
+                let line = err.position().map_or(0, |p| p.line());
+                report.errors.push(RecordError::Malformed { line, reason: err.to_string() });
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        }
+        report.records += 1;
+        let line = record.position().map_or(0, |p| p.line());
+        match convert_csv_record(&record, &columns, options, line) {
+            Ok(converted) => {
+                writer.write_byte_record(&converted)?;
+                report.written += 1;
+            }
+            Err(err) => report.errors.push(err),
+        }
+    }
+    writer.flush()?;
+    Ok(report)
+}
+
+fn convert_csv_record(
+    record: &csv::ByteRecord,
+    columns: &[(usize, &String)],
+    options: &ConvertOptions,
+    line: u64,
+) -> Result<csv::ByteRecord, RecordError> {
+    let mut fields: Vec<Vec<u8>> = record.iter().map(|field| field.to_vec()).collect();
+    for &(index, field) in columns {
+        let value = fields.get_mut(index).ok_or_else(|| RecordError::MissingField { line, field: field.clone() })?;
+        if value.is_empty() {
+            continue;
+        }
+        let id = options
+            .from
+            .parse(value)
+            .map_err(|error| RecordError::InvalidId { line, field: field.clone(), error })?;
+        *value = options.to.encode(&id);
+    }
+    Ok(csv::ByteRecord::from(fields))
+}
+
+/// Convert the `options.fields` keys of newline delimited JSON objects read from `input`,
+/// writing one object per line to `output`. Blank lines are skipped. Objects are re-serialized,
+/// so insignificant whitespace is not preserved.
+pub fn convert_ndjson<R: Read, W: Write>(input: R, mut output: W, options: &ConvertOptions) -> io::Result<ConversionReport> {
+    let mut report = ConversionReport::default();
+    for (index, bytes) in BufReader::new(input).split(b'\n').enumerate() {
+        let bytes = bytes?;
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        report.records += 1;
+        match convert_json_line(&bytes, options, index as u64 + 1) {
+            Ok(value) => {
+                serde_json::to_writer(&mut output, &value)?;
+                output.write_all(b"\n")?;
+                report.written += 1;
+            }
+            Err(err) => report.errors.push(err),
+        }
+    }
+    output.flush()?;
+    Ok(report)
+}
+
+fn convert_json_line(bytes: &[u8], options: &ConvertOptions, line: u64) -> Result<Value, RecordError> {
+    let mut value: Value = serde_json::from_slice(bytes).map_err(|err| RecordError::Malformed { line, reason: err.to_string() })?;
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| RecordError::Malformed { line, reason: "not a JSON object".to_owned() })?;
+    for field in &options.fields {
+        let slot = object.get_mut(field).ok_or_else(|| RecordError::MissingField { line, field: field.clone() })?;
+        if slot.is_null() {
+            continue;
+        }
+        let id = options
+            .from
+            .parse_json(slot)
+            .ok_or_else(|| RecordError::UnexpectedType { line, field: field.clone() })?
+            .map_err(|error| RecordError::InvalidId { line, field: field.clone(), error })?;
+        *slot = options.to.to_json(&id);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UID: &str = "0ujsszwN8NRY24YaXiTIE2VWDTS";
+    const HEX: &str = "0669f605d9719f3234fe964b6376cb6b9a2e5e86";
+
+    fn options(fields: &[&str], from: Representation, to: Representation) -> ConvertOptions {
+        ConvertOptions { fields: fields.iter().map(|f| f.to_string()).collect(), from, to }
+    }
+
+    fn csv(input: &[u8], options: &ConvertOptions) -> (Vec<u8>, ConversionReport) {
+        let mut output = Vec::new();
+        let report = convert_csv(input, &mut output, options).unwrap();
+        (output, report)
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let input = format!("id,parent,note\n{},,\"a, b\"\n{},{},x\n", UID, UID, UID);
+        let (hex, report) = csv(input.as_bytes(), &options(&["id", "parent"], Representation::Base62, Representation::Hex));
+        assert_eq!(report, ConversionReport { records: 2, written: 2, errors: vec![] });
+        assert_eq!(String::from_utf8(hex.clone()).unwrap(), format!("id,parent,note\n{},,\"a, b\"\n{},{},x\n", HEX, HEX, HEX));
+        let (raw, _) = csv(&hex, &options(&["id", "parent"], Representation::Hex, Representation::Raw));
+        let (back, report) = csv(&raw, &options(&["id", "parent"], Representation::Raw, Representation::Base62));
+        assert!(report.errors.is_empty());
+        assert_eq!(String::from_utf8(back).unwrap(), input);
+    }
+
+    #[test]
+    fn test_csv_record_errors() {
+        let input = format!("note,id\nok,{}\nshort\nbad,{}\nupper,{}\n", UID, &UID[1..], HEX.to_uppercase());
+        let (output, report) = csv(input.as_bytes(), &options(&["id"], Representation::Base62, Representation::Hex));
+        assert_eq!(String::from_utf8(output).unwrap(), format!("note,id\nok,{}\n", HEX));
+        assert_eq!((report.records, report.written), (4, 1));
+        assert_eq!(report.errors[0], RecordError::MissingField { line: 3, field: "id".to_owned() });
+        assert_eq!(report.errors[1].line(), 4);
+        match report.errors[2] {
+            RecordError::InvalidId { line: 5, ref error, .. } => assert_eq!(error.kind(), errors::KSUIDErrorKind::InvalidLength),
+            ref other => panic!("unexpected {:?}", other),
+        }
+        let (_, report) = csv(b"id\nABCDEF\n", &options(&["id"], Representation::Hex, Representation::Base62));
+        assert_eq!(report.errors[0].to_string(), "line 2: field \"id\": not a 40 digit hex ksuid");
+
+        let err = convert_csv(&b"a,b\n"[..], Vec::new(), &options(&["id"], Representation::Hex, Representation::Raw)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let input = format!(
+            "{{\"id\":\"{}\",\"n\":1,\"parent\":null}}\n\n[1]\n{{\"id\":7,\"parent\":null}}\n{{\"n\":2}}\n{{\"id\":\"{}\",\"parent\":\"{}\"}}\r\n",
+            UID, UID, UID
+        );
+        let mut output = Vec::new();
+        let opts = options(&["id", "parent"], Representation::Base62, Representation::Raw);
+        let report = convert_ndjson(input.as_bytes(), &mut output, &opts).unwrap();
+        assert_eq!((report.records, report.written), (5, 2));
+        assert_eq!(report.errors.iter().map(RecordError::line).collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(report.errors[1], RecordError::UnexpectedType { line: 4, field: "id".to_owned() });
+
+        let lines: Vec<Value> = output.split(|&b| b == b'\n').filter(|l| !l.is_empty()).map(|l| serde_json::from_slice(l).unwrap()).collect();
+        let raw = Representation::Raw.to_json(&KSUID::from_base62(UID).unwrap());
+        assert_eq!(lines[0], json!({"id": raw.clone(), "n": 1, "parent": null}));
+        assert_eq!(lines[1], json!({"id": raw.clone(), "parent": raw}));
+
+        let mut back = Vec::new();
+        convert_ndjson(&output[..], &mut back, &options(&["id"], Representation::Raw, Representation::Base62)).unwrap();
+        assert!(String::from_utf8(back).unwrap().starts_with(&format!("{{\"id\":\"{}\"", UID)));
+    }
+}
@@ -0,0 +1,329 @@
+use base62;
+use errors;
+use ksuid::{BYTE_LENGTH, KSUID};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::ops::{Bound, RangeBounds, RangeInclusive};
+use std::str;
+
+/// A keyset pagination cursor for listings ordered by `KSUID`: either "the ids after this one"
+/// or "the ids before this one". Both bounds are exclusive, so the id a cursor was made from is
+/// never repeated on the next page.
+///
+/// The string form, from `KSUID::after_cursor`, `KSUID::before_cursor` or `Display`, is a
+/// direction character followed by the base62 id. Clients should treat it as opaque.
+/// # Example
+/// ```
+/// use ksuid::{PageCursor, KSUID};
+/// use std::collections::BTreeSet;
+///
+/// let ids: BTreeSet<KSUID> = (0..10).map(|_| KSUID::new()).collect();
+/// let first_page: Vec<KSUID> = ids.iter().take(4).cloned().collect();
+/// // Hand the client a cursor, then resume from it on their next request.
+/// let token = first_page.last().unwrap().after_cursor();
+/// let cursor: PageCursor = token.parse().unwrap();
+/// let second_page: Vec<KSUID> = ids.range(cursor).take(4).cloned().collect();
+/// assert_eq!(second_page[0], *ids.iter().nth(4).unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PageCursor {
+    /// Ids sorting strictly after the given one.
+    After(KSUID),
+    /// Ids sorting strictly before the given one.
+    Before(KSUID),
+}
+
+const AFTER: char = 'a';
+const BEFORE: char = 'b';
+
+impl PageCursor {
+    /// Parse a cursor produced by `Display`, `KSUID::after_cursor` or `KSUID::before_cursor`.
+    pub fn parse(s: &str) -> Result<Self, errors::KSUIDError> {
+        let invalid = || errors::KSUIDError::InvalidCursor { value: s.to_owned() };
+        let mut chars = s.chars();
+        let direction = chars.next().ok_or_else(invalid)?;
+        let id = KSUID::from_base62(chars.as_str()).map_err(|_| invalid())?;
+        match direction {
+            AFTER => Ok(PageCursor::After(id)),
+            BEFORE => Ok(PageCursor::Before(id)),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Return the id the cursor was made from.
+    pub fn id(&self) -> KSUID {
+        match *self {
+            PageCursor::After(id) | PageCursor::Before(id) => id,
+        }
+    }
+
+    /// Return the page as an inclusive range of ids, for stores that only take inclusive
+    /// bounds (e.g. `BETWEEN`), or `None` if no id can be on it: nothing sorts after the largest
+    /// id or before the nil id.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// let cursor = uid.after_cursor().parse::<ksuid::PageCursor>().unwrap();
+    /// assert_eq!(*cursor.inclusive_range().unwrap().start(), uid.next().unwrap());
+    /// ```
+    pub fn inclusive_range(&self) -> Option<RangeInclusive<KSUID>> {
+        match *self {
+            PageCursor::After(id) => id.next().map(|first| first..=KSUID([0xff; 20])),
+            PageCursor::Before(id) => id.prev().map(|last| KSUID::nil()..=last),
+        }
+    }
+}
+
+/// Exclusive bounds, so a cursor can be passed straight to `BTreeMap::range` and
+/// `BTreeSet::range`, or checked with `contains`.
+impl RangeBounds<KSUID> for PageCursor {
+    fn start_bound(&self) -> Bound<&KSUID> {
+        match *self {
+            PageCursor::After(ref id) => Bound::Excluded(id),
+            PageCursor::Before(_) => Bound::Unbounded,
+        }
+    }
+
+    fn end_bound(&self) -> Bound<&KSUID> {
+        match *self {
+            PageCursor::After(_) => Bound::Unbounded,
+            PageCursor::Before(ref id) => Bound::Excluded(id),
+        }
+    }
+}
+
+impl fmt::Display for PageCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let direction = match *self {
+            PageCursor::After(_) => AFTER,
+            PageCursor::Before(_) => BEFORE,
+        };
+        write!(f, "{}{}", direction, self.id())
+    }
+}
+
+impl str::FromStr for PageCursor {
+    type Err = errors::KSUIDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PageCursor::parse(s)
+    }
+}
+
+// Token layout: version, state length, id, state, zero padding to a multiple of four bytes (the
+// base62 encoder works in u32 limbs), then a checksum over everything before it.
+const TOKEN_VERSION: u8 = 1;
+const HEADER_LENGTH: usize = 2;
+const CHECKSUM_LENGTH: usize = 4;
+
+fn padded_length(state_length: usize) -> usize {
+    (HEADER_LENGTH + BYTE_LENGTH + state_length + CHECKSUM_LENGTH).div_ceil(4) * 4
+}
+
+// Base62 digits needed for `bytes` bytes.
+fn encoded_length(bytes: usize) -> usize {
+    (bytes as f64 * 8.0 / 62f64.log2()).ceil() as usize
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LENGTH] {
+    let digest = Sha256::new().chain_update(b"ksuid-cursor-v1").chain_update(bytes).finalize();
+    let mut sum = [0u8; CHECKSUM_LENGTH];
+    sum.copy_from_slice(&digest[..CHECKSUM_LENGTH]);
+    sum
+}
+
+/// A pagination cursor carrying a `KSUID` plus a small blob of caller state, such as the sort
+/// direction or a secondary sort key, encoded together as one base62 token. The crate doesn't
+/// interpret the state.
+///
+/// Tokens end in a checksum, so `parse` rejects truncated, corrupted or hand edited tokens. The
+/// checksum isn't keyed: it catches accidents, not a client deliberately forging a cursor, so
+/// don't put anything in the state the client mustn't be able to choose.
+/// # Example
+/// ```
+/// use ksuid::{Cursor, KSUID};
+///
+/// let cursor = Cursor::new(KSUID::new(), b"desc");
+/// let token = cursor.to_token();
+/// let parsed = Cursor::parse(&token).unwrap();
+/// assert_eq!(parsed.state(), b"desc");
+/// assert_eq!(parsed, cursor);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cursor {
+    id: KSUID,
+    state: Vec<u8>,
+}
+
+impl Cursor {
+    /// The most state a cursor can carry, in bytes.
+    pub const MAX_STATE_LENGTH: usize = 32;
+
+    /// Create a cursor at `id` carrying `state`. Panics if `state` is longer than
+    /// `MAX_STATE_LENGTH`.
+    pub fn new(id: KSUID, state: &[u8]) -> Self {
+        assert!(state.len() <= Self::MAX_STATE_LENGTH, "cursor state is at most {} bytes", Self::MAX_STATE_LENGTH);
+        Cursor { id, state: state.to_vec() }
+    }
+
+    /// Return the id the cursor points at.
+    pub fn id(&self) -> KSUID {
+        self.id
+    }
+
+    /// Return the caller state the cursor carries.
+    pub fn state(&self) -> &[u8] {
+        &self.state
+    }
+
+    /// Encode the cursor as a base62 token. Tokens grow with the state, from 38 characters with
+    /// none to 81 at `MAX_STATE_LENGTH`.
+    pub fn to_token(&self) -> String {
+        let length = padded_length(self.state.len());
+        let mut bytes = vec![0u8; length];
+        bytes[0] = TOKEN_VERSION;
+        bytes[1] = self.state.len() as u8;
+        bytes[HEADER_LENGTH..HEADER_LENGTH + BYTE_LENGTH].copy_from_slice(&self.id.0);
+        bytes[HEADER_LENGTH + BYTE_LENGTH..][..self.state.len()].copy_from_slice(&self.state);
+        let sum = checksum(&bytes[..length - CHECKSUM_LENGTH]);
+        bytes[length - CHECKSUM_LENGTH..].copy_from_slice(&sum);
+        base62::encode_bytes(&bytes, encoded_length(length))
+    }
+
+    /// Parse a token produced by `to_token`, failing with `KSUIDError::InvalidCursor` if it is
+    /// malformed or its checksum doesn't match.
+    pub fn parse(token: &str) -> Result<Self, errors::KSUIDError> {
+        let invalid = || errors::KSUIDError::InvalidCursor { value: token.to_owned() };
+        let length = (0..=Self::MAX_STATE_LENGTH)
+            .map(padded_length)
+            .find(|&length| encoded_length(length) == token.len())
+            .ok_or_else(invalid)?;
+        let bytes = base62::decode_bytes(token.as_bytes(), token.len(), length).map_err(|_| invalid())?;
+        let (body, sum) = bytes.split_at(length - CHECKSUM_LENGTH);
+        let state_length = usize::from(body[1]);
+        // Lengths just past the maximum pad to the same size, so check the bound separately.
+        if state_length > Self::MAX_STATE_LENGTH {
+            return Err(invalid());
+        }
+        if body[0] != TOKEN_VERSION || padded_length(state_length) != length || checksum(body) != sum {
+            return Err(invalid());
+        }
+        let state = &body[HEADER_LENGTH + BYTE_LENGTH..];
+        if state[state_length..].iter().any(|&b| b != 0) {
+            return Err(invalid());
+        }
+        Ok(Cursor {
+            id: KSUID::from_bytes(&body[HEADER_LENGTH..]).map_err(|_| invalid())?,
+            state: state[..state_length].to_vec(),
+        })
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_token())
+    }
+}
+
+impl str::FromStr for Cursor {
+    type Err = errors::KSUIDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Cursor::parse(s)
+    }
+}
+
+impl KSUID {
+    /// Return a cursor for the page of ids after this one, typically the last id of the page
+    /// just served. See `PageCursor`.
+    pub fn after_cursor(&self) -> String {
+        PageCursor::After(*self).to_string()
+    }
+
+    /// Return a cursor for the page of ids before this one, typically the first id of the page
+    /// just served, for paging backwards. See `PageCursor`.
+    pub fn before_cursor(&self) -> String {
+        PageCursor::Before(*self).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use errors::KSUIDErrorKind;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let uid = KSUID::new();
+        assert_eq!(PageCursor::parse(&uid.after_cursor()), Ok(PageCursor::After(uid)));
+        assert_eq!(PageCursor::parse(&uid.before_cursor()), Ok(PageCursor::Before(uid)));
+        assert_eq!(uid.after_cursor().len(), 28);
+        for bad in &["", "a", "x0ujsszwN8NRY24YaXiTIE2VWDTS", "a0ujsszwN8NRY24YaXiTIE2VWDT", "é0ujsszwN8NRY24YaXiTIE2VWDTS"] {
+            let err = PageCursor::parse(bad).unwrap_err();
+            assert_eq!(err.kind(), KSUIDErrorKind::InvalidFormat);
+            assert_eq!(err.input(), Some(*bad));
+        }
+    }
+
+    #[test]
+    fn test_cursor_bounds_are_exclusive() {
+        let map: BTreeMap<KSUID, usize> = (0..6u8).map(|i| (KSUID([i; 20]), usize::from(i))).collect();
+        let pivot = KSUID([3; 20]);
+        let after: Vec<usize> = map.range(PageCursor::After(pivot)).map(|(_, v)| *v).collect();
+        let before: Vec<usize> = map.range(PageCursor::Before(pivot)).map(|(_, v)| *v).collect();
+        assert_eq!(after, vec![4, 5]);
+        assert_eq!(before, vec![0, 1, 2]);
+        let inclusive = PageCursor::After(pivot).inclusive_range().unwrap();
+        assert_eq!(map.range(inclusive).count(), 2);
+        assert!(!PageCursor::After(pivot).contains(&pivot));
+        assert_eq!(PageCursor::After(KSUID([0xff; 20])).inclusive_range(), None);
+        assert_eq!(PageCursor::Before(KSUID::nil()).inclusive_range(), None);
+    }
+
+    #[test]
+    fn test_cursor_token_round_trip() {
+        let uid = KSUID::new();
+        for length in 0..=Cursor::MAX_STATE_LENGTH {
+            let state: Vec<u8> = (0..length as u8).map(|b| b.wrapping_mul(37)).collect();
+            let cursor = Cursor::new(uid, &state);
+            let token = cursor.to_token();
+            assert_eq!(token.len(), encoded_length(padded_length(length)));
+            assert_eq!(token.parse::<Cursor>().unwrap(), cursor);
+        }
+        assert_eq!(Cursor::new(uid, b"").to_token().len(), 38);
+        assert_eq!(Cursor::new(uid, &[0xff; 32]).to_token().len(), 81);
+        assert_eq!(Cursor::parse(&Cursor::new(KSUID([0xff; 20]), &[0xff; 32]).to_token()).unwrap().id(), KSUID([0xff; 20]));
+    }
+
+    #[test]
+    fn test_cursor_token_rejects_tampering() {
+        let token = Cursor::new(KSUID::new(), b"asc:42").to_token();
+        let mut chars: Vec<char> = token.chars().collect();
+        let last = chars.len() - 1;
+        chars[last] = if chars[last] == '0' { '1' } else { '0' };
+        let edited: String = chars.into_iter().collect();
+        // A token with a valid checksum claiming 33 bytes of state, which pads to the same
+        // length as the maximum of 32.
+        let length = padded_length(Cursor::MAX_STATE_LENGTH);
+        let mut bytes = vec![0u8; length];
+        bytes[0] = TOKEN_VERSION;
+        bytes[1] = 33;
+        bytes[HEADER_LENGTH + BYTE_LENGTH..][..33].copy_from_slice(&[7; 33]);
+        let sum = checksum(&bytes[..length - CHECKSUM_LENGTH]);
+        bytes[length - CHECKSUM_LENGTH..].copy_from_slice(&sum);
+        let oversized = base62::encode_bytes(&bytes, encoded_length(length));
+        for bad in &[&edited[..], &token[1..], "", "not a cursor", &KSUID::new().to_string(), &oversized] {
+            let err = Cursor::parse(bad).unwrap_err();
+            assert_eq!(err.kind(), KSUIDErrorKind::InvalidFormat);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cursor_state_too_long() {
+        Cursor::new(KSUID::new(), &[0; 33]);
+    }
+}
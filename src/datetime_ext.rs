@@ -0,0 +1,89 @@
+use byteorder::{BigEndian, ByteOrder};
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use errors;
+use ksuid::{EPOCH_START, BYTE_LENGTH, KSUID, TIMESTAMP_LENGTH};
+
+/// Builds probe ids straight from a `DateTime<Utc>`, so time range queries read as
+/// `since.min_ksuid()..=until.max_ksuid()`.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::{Duration, Utc};
+/// use ksuid::{KsuidDateTimeExt, KSUID};
+///
+/// let until = Utc::now();
+/// let since = until - Duration::hours(1);
+/// let recent = since.min_ksuid()..=until.max_ksuid();
+/// assert!(recent.contains(&KSUID::new()));
+/// ```
+pub trait KsuidDateTimeExt {
+    /// Return the smallest id created in this second: the timestamp with an all zero payload.
+    /// Times before the ksuid epoch clamp to the nil id and times past the last representable
+    /// second to that second.
+    fn min_ksuid(&self) -> KSUID;
+
+    /// Return the largest id created in this second: the timestamp with an all `0xff` payload.
+    /// Clamped like `min_ksuid`, so times before the epoch give the largest id of its first
+    /// second.
+    fn max_ksuid(&self) -> KSUID;
+
+    /// Return the id with this timestamp and `payload`, or `KSUIDError::TimestampOutOfRange` if
+    /// the time can't be represented.
+    fn ksuid_with_payload(&self, payload: [u8; 16]) -> Result<KSUID, errors::KSUIDError>;
+}
+
+// The ksuid second containing `t`, clamped to the representable range.
+fn clamped_ksuid_time(t: &DateTime<Utc>) -> u32 {
+    (t.timestamp() - EPOCH_START).clamp(0, i64::from(u32::MAX)) as u32
+}
+
+fn probe(t: &DateTime<Utc>, fill: u8) -> KSUID {
+    let mut bytes = [fill; BYTE_LENGTH];
+    BigEndian::write_u32(&mut bytes[..TIMESTAMP_LENGTH], clamped_ksuid_time(t));
+    KSUID(bytes)
+}
+
+impl KsuidDateTimeExt for DateTime<Utc> {
+    fn min_ksuid(&self) -> KSUID {
+        probe(self, 0)
+    }
+
+    fn max_ksuid(&self) -> KSUID {
+        probe(self, 0xff)
+    }
+
+    fn ksuid_with_payload(&self, payload: [u8; 16]) -> Result<KSUID, errors::KSUIDError> {
+        KSUID::from_parts(*self, &payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use ksuid::from_ksuid_time;
+
+    #[test]
+    fn test_datetime_probes() {
+        let t = DateTime::from_timestamp(1_600_000_000, 500_000_000).unwrap();
+        let min = t.min_ksuid();
+        let max = t.max_ksuid();
+        assert_eq!(min.timestamp(), DateTime::from_timestamp(1_600_000_000, 0).unwrap());
+        assert_eq!(min.payload(), &[0; 16]);
+        assert_eq!(max.payload(), &[0xff; 16]);
+        assert!((t - Duration::seconds(1)).max_ksuid() < min);
+        assert!(max < (t + Duration::seconds(1)).min_ksuid());
+        assert_eq!(t.ksuid_with_payload([7; 16]).unwrap(), KSUID::from_parts(t, &[7; 16]).unwrap());
+    }
+
+    #[test]
+    fn test_datetime_probes_clamp() {
+        let before = from_ksuid_time(0) - Duration::days(1);
+        let after = from_ksuid_time(u32::MAX) + Duration::days(1);
+        assert!(before.min_ksuid().is_nil());
+        assert_eq!(after.max_ksuid(), KSUID([0xff; 20]));
+        assert!(before.ksuid_with_payload([0; 16]).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! DynamoDB glue, available with the `dynamodb` feature: conversions between `KSUID` and
+//! `aws_sdk_dynamodb`'s `AttributeValue`, sort key conditions over creation time, and a serde
+//! helper for `serde_dynamo` items.
+//!
+//! Both attribute forms sort by creation time: `S` holds the base62 string, `B` the 20 raw bytes
+//! (DynamoDB compares binary as unsigned bytes), which is smaller on disk and in indexes.
+//!
+//! For `serde_dynamo`, `#[serde(with = "ksuid::serde::flexible")]` stores a field as `S`, and
+//! `#[serde(with = "ksuid::dynamodb::binary")]` as `B`. Both read either form back. The
+//! `serde_dynamo` feature adds nothing to the API; it only runs the tests against it.
+
+use aws_sdk_dynamodb::primitives::Blob;
+use aws_sdk_dynamodb::types::AttributeValue;
+use byteorder::{BigEndian, ByteOrder};
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use errors;
+use ksuid::{ceil_ksuid_time, BYTE_LENGTH, KSUID, TIMESTAMP_LENGTH};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ops::Range;
+
+/// Placeholder for the lower bound in `KeyCondition::expression`.
+pub const LOWER_PLACEHOLDER: &str = ":ksuid_lo";
+/// Placeholder for the upper bound in `KeyCondition::expression`.
+pub const UPPER_PLACEHOLDER: &str = ":ksuid_hi";
+
+/// Which attribute type an id is stored as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttributeEncoding {
+    /// `S`: the base62 string.
+    String,
+    /// `B`: the 20 raw bytes.
+    Binary,
+}
+
+impl KSUID {
+    /// Convert to an `AttributeValue` of the given type.
+    pub fn to_attribute_value(&self, encoding: AttributeEncoding) -> AttributeValue {
+        match encoding {
+            AttributeEncoding::String => AttributeValue::S(self.to_base62()),
+            AttributeEncoding::Binary => AttributeValue::B(Blob::new(self.0.to_vec())),
+        }
+    }
+}
+
+/// Stores the base62 string, `S`.
+impl From<KSUID> for AttributeValue {
+    fn from(uid: KSUID) -> Self {
+        uid.to_attribute_value(AttributeEncoding::String)
+    }
+}
+
+/// Reads either an `S` base62 string or a 20 byte `B`.
+impl<'a> TryFrom<&'a AttributeValue> for KSUID {
+    type Error = errors::KSUIDError;
+
+    fn try_from(value: &'a AttributeValue) -> Result<Self, Self::Error> {
+        match *value {
+            AttributeValue::S(ref s) => KSUID::from_base62(s),
+            AttributeValue::B(ref b) if b.as_ref().len() == BYTE_LENGTH => KSUID::from_bytes(b.as_ref()),
+            _ => Err(errors::KSUIDError::InvalidAttributeValue),
+        }
+    }
+}
+
+/// A sort key condition for a query's `KeyConditionExpression`, with the attribute values its
+/// placeholders refer to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyCondition {
+    /// E.g. `#sk BETWEEN :ksuid_lo AND :ksuid_hi`. Combine it with the partition key condition
+    /// using `AND`.
+    pub expression: String,
+    /// Values for `LOWER_PLACEHOLDER` and `UPPER_PLACEHOLDER`.
+    pub values: HashMap<String, AttributeValue>,
+}
+
+/// Build a condition matching every id created within `range` (end exclusive) on the sort key
+/// `attribute`, which may be a `#name` placeholder. Returns `None` when no whole second of the
+/// range is representable, since DynamoDB rejects a `BETWEEN` whose bounds are reversed.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::Duration;
+/// use ksuid::dynamodb::{sort_key_between, AttributeEncoding};
+/// use ksuid::KSUID;
+///
+/// let now = KSUID::new().timestamp();
+/// let condition = sort_key_between("sk", now - Duration::hours(1)..now, AttributeEncoding::String).unwrap();
+/// assert_eq!(condition.expression, "sk BETWEEN :ksuid_lo AND :ksuid_hi");
+/// // client.query()
+/// //     .key_condition_expression(format!("pk = :pk AND {}", condition.expression))
+/// //     .set_expression_attribute_values(Some(condition.values))
+/// //     .expression_attribute_values(":pk", partition)
+/// ```
+pub fn sort_key_between(attribute: &str, range: Range<DateTime<Utc>>, encoding: AttributeEncoding) -> Option<KeyCondition> {
+    let start = ceil_ksuid_time(range.start);
+    let end = ceil_ksuid_time(range.end);
+    if start >= end {
+        return None;
+    }
+    let bound = |t: i64, fill: u8| {
+        let mut bytes = [fill; BYTE_LENGTH];
+        BigEndian::write_u32(&mut bytes[..TIMESTAMP_LENGTH], t as u32);
+        KSUID(bytes).to_attribute_value(encoding)
+    };
+    let mut values = HashMap::new();
+    values.insert(LOWER_PLACEHOLDER.to_owned(), bound(start, 0x00));
+    values.insert(UPPER_PLACEHOLDER.to_owned(), bound(end - 1, 0xff));
+    Some(KeyCondition {
+        expression: format!("{} BETWEEN {} AND {}", attribute, LOWER_PLACEHOLDER, UPPER_PLACEHOLDER),
+        values,
+    })
+}
+
+/// `#[serde(with = "ksuid::dynamodb::binary")]`: serialize as raw bytes, which `serde_dynamo`
+/// stores as `B`. Deserializing accepts everything `ksuid::serde::flexible` does.
+pub mod binary {
+    use ksuid::KSUID;
+    use serde::flexible;
+    use serde_crate::{Deserializer, Serializer};
+
+    /// Serialize `uid` as its 20 raw bytes.
+    pub fn serialize<S: Serializer>(uid: &KSUID, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&uid.0)
+    }
+
+    /// Deserialize raw bytes, or any other form `ksuid::serde::flexible` accepts.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<KSUID, D::Error> {
+        flexible::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_crate::{Deserialize, Serialize};
+    #[cfg(feature = "serde_dynamo")]
+    use serde_dynamo;
+    use serde_json;
+
+    #[test]
+    fn test_attribute_value_roundtrip() {
+        let uid = KSUID::new();
+        assert_eq!(AttributeValue::from(uid), AttributeValue::S(uid.to_base62()));
+        for &encoding in [AttributeEncoding::String, AttributeEncoding::Binary].iter() {
+            assert_eq!(KSUID::try_from(&uid.to_attribute_value(encoding)).unwrap(), uid);
+        }
+        let short = AttributeValue::B(Blob::new(vec![0; 16]));
+        assert_eq!(KSUID::try_from(&short), Err(errors::KSUIDError::InvalidAttributeValue));
+        let number = AttributeValue::N("1".to_owned());
+        assert_eq!(KSUID::try_from(&number).unwrap_err().kind(), errors::KSUIDErrorKind::InvalidFormat);
+    }
+
+    #[test]
+    fn test_sort_key_between() {
+        let uid = KSUID::new();
+        let t = uid.timestamp();
+        for &encoding in [AttributeEncoding::String, AttributeEncoding::Binary].iter() {
+            let condition = sort_key_between("#sk", t..t + Duration::seconds(1), encoding).unwrap();
+            assert_eq!(condition.expression, "#sk BETWEEN :ksuid_lo AND :ksuid_hi");
+            let lo = KSUID::try_from(&condition.values[LOWER_PLACEHOLDER]).unwrap();
+            let hi = KSUID::try_from(&condition.values[UPPER_PLACEHOLDER]).unwrap();
+            assert!(lo <= uid && uid <= hi);
+            assert_eq!((lo.timestamp(), hi.timestamp()), (t, t));
+        }
+        // A sub-second range past a whole second, and an inverted one, cover no ids.
+        let within = t + Duration::milliseconds(100)..t + Duration::milliseconds(900);
+        assert!(sort_key_between("sk", within, AttributeEncoding::String).is_none());
+        assert!(sort_key_between("sk", t..t - Duration::hours(1), AttributeEncoding::String).is_none());
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Item {
+        #[serde(with = "::serde::flexible")]
+        pk: KSUID,
+        #[serde(with = "binary")]
+        sk: KSUID,
+    }
+
+    #[test]
+    fn test_binary_item() {
+        let item = Item { pk: KSUID::new(), sk: KSUID::new() };
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["pk"], json!(item.pk.to_base62()));
+        assert_eq!(json["sk"], json!(item.sk.0.to_vec()));
+        assert_eq!(serde_json::from_value::<Item>(json).unwrap(), item);
+    }
+
+    #[cfg(feature = "serde_dynamo")]
+    #[test]
+    fn test_serde_dynamo_item() {
+        let item = Item { pk: KSUID::new(), sk: KSUID::new() };
+        let attributes: HashMap<String, AttributeValue> = serde_dynamo::to_item(&item).unwrap();
+        assert_eq!(attributes["pk"], item.pk.to_attribute_value(AttributeEncoding::String));
+        assert_eq!(attributes["sk"], item.sk.to_attribute_value(AttributeEncoding::Binary));
+        assert_eq!(serde_dynamo::from_item::<_, Item>(attributes).unwrap(), item);
+    }
+}
@@ -0,0 +1,82 @@
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::Aes128;
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use byteorder::{BigEndian, ByteOrder};
+use errors;
+use ksuid::{checked_ksuid_time, to_ksuid_time, BYTE_LENGTH, KSUID, TIMESTAMP_LENGTH};
+
+/// Encrypted payload mode. The 16 byte payload is a single AES-128 block holding an internal
+/// value (a sequence number, a shard hint, ...) that only holders of the key can read back.
+/// Encryption is deterministic, so two ids carrying the same value under the same key share a
+/// payload; make sure the value is unique (or mixes in random bytes) if that matters.
+impl KSUID {
+
+    /// Create a `KSUID` whose payload is `value` encrypted under `key`. Fails with
+    /// `KSUIDError::TimestampOutOfRange` if `ts` is outside the range a ksuid can represent.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let key = [7u8; 16];
+    /// let uid = KSUID::new();
+    /// let secret = KSUID::from_encrypted_payload(uid.timestamp(), &[42; 16], &key).unwrap();
+    /// assert_ne!(secret.payload(), &[42; 16]);
+    /// assert_eq!(secret.decrypt_payload(&key), [42; 16]);
+    /// ```
+    pub fn from_encrypted_payload(ts: DateTime<Utc>, value: &[u8; 16], key: &[u8; 16]) -> Result<Self, errors::KSUIDError> {
+        Ok(Self::encrypted(checked_ksuid_time(ts)?, value, key))
+    }
+
+    /// Same as `from_encrypted_payload`, stamped with the current time.
+    pub fn new_encrypted(value: &[u8; 16], key: &[u8; 16]) -> Self {
+        Self::encrypted(to_ksuid_time(Utc::now()), value, key)
+    }
+
+    fn encrypted(time: u32, value: &[u8; 16], key: &[u8; 16]) -> Self {
+        let mut block = (*value).into();
+        Aes128::new(key.into()).encrypt_block(&mut block);
+        let mut bytes = [0u8; BYTE_LENGTH];
+        BigEndian::write_u32(&mut bytes, time);
+        bytes[TIMESTAMP_LENGTH..].copy_from_slice(&block);
+        KSUID(bytes)
+    }
+
+    /// Decrypt the payload with `key`, returning the internal value. A wrong key yields garbage
+    /// rather than an error, since any 16 bytes are a valid ciphertext.
+    pub fn decrypt_payload(&self, key: &[u8; 16]) -> [u8; 16] {
+        let mut block = [0u8; 16];
+        block.copy_from_slice(&self.0[TIMESTAMP_LENGTH..]);
+        let mut block = block.into();
+        Aes128::new(key.into()).decrypt_block(&mut block);
+        block.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_roundtrip() {
+        let key = [1u8; 16];
+        let value = *b"shard-07/seq-042";
+        let uid = KSUID::new_encrypted(&value, &key);
+        assert_ne!(uid.payload(), &value);
+        assert_eq!(uid.decrypt_payload(&key), value);
+        assert_ne!(uid.decrypt_payload(&[2u8; 16]), value);
+    }
+
+    #[test]
+    fn test_encrypted_timestamp_range() {
+        let key = [1u8; 16];
+        let ts = DateTime::from_timestamp(1_500_000_000, 0).unwrap();
+        let uid = KSUID::from_encrypted_payload(ts, &[3; 16], &key).unwrap();
+        assert_eq!((uid.timestamp(), uid.decrypt_payload(&key)), (ts, [3; 16]));
+        let early = DateTime::from_timestamp(1_000_000_000, 0).unwrap();
+        assert_eq!(
+            KSUID::from_encrypted_payload(early, &[3; 16], &key),
+            Err(errors::KSUIDError::TimestampOutOfRange { unix_seconds: 1_000_000_000 })
+        );
+    }
+}
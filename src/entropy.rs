@@ -0,0 +1,139 @@
+use std::cell::RefCell;
+use std::cmp;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use rand;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+// Refilling in large chunks amortizes the cost of each RNG call across ~200 ids.
+const BUFFER_SIZE: usize = 4096;
+
+struct EntropyBuffer {
+    bytes: [u8; BUFFER_SIZE],
+    position: usize,
+    // Acquired on the first refill and kept for the life of the thread.
+    rng: Option<rand::ThreadRng>,
+}
+
+impl EntropyBuffer {
+    fn fill(&mut self, dst: &mut [u8]) {
+        let mut written = 0;
+        while written < dst.len() {
+            if self.position == BUFFER_SIZE {
+                self.rng.get_or_insert_with(rand::thread_rng).fill_bytes(&mut self.bytes);
+                self.position = 0;
+            }
+            let n = cmp::min(dst.len() - written, BUFFER_SIZE - self.position);
+            dst[written..written + n].copy_from_slice(&self.bytes[self.position..self.position + n]);
+            self.position += n;
+            written += n;
+        }
+    }
+}
+
+thread_local! {
+    static BUFFER: RefCell<EntropyBuffer> = const { RefCell::new(EntropyBuffer {
+        bytes: [0; BUFFER_SIZE],
+        position: BUFFER_SIZE,
+        rng: None,
+    }) };
+}
+
+/// Fill `dst` with random bytes drawn from a per-thread buffer that is refilled from the thread's
+/// `rand::thread_rng()` handle 4 KiB at a time.
+///
+/// Like `thread_rng()` itself (a userspace generator), the buffer is duplicated by `fork`, so a
+/// child and parent will share whatever was left in the forking thread's buffer.
+pub(crate) fn fill(dst: &mut [u8]) {
+    BUFFER.with(|buffer| buffer.borrow_mut().fill(dst))
+}
+
+/// A uniformly random `u16` below `limit`, from the same buffer as `fill`.
+pub(crate) fn gen_u16_below(limit: u16) -> u16 {
+    assert!(limit.is_power_of_two());
+    let mut bytes = [0u8; 2];
+    fill(&mut bytes);
+    u16::from_be_bytes(bytes) & (limit - 1)
+}
+
+// Domain separation for `mix`. Bump the version if the construction ever changes.
+const MIX_DOMAIN: &[u8] = b"ksuid-entropy-mix-v1";
+
+// Distinguishes every mixed payload this process produces, whatever the RNG returns.
+static MIX_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The construction behind `GeneratorOptions::mix_entropy`: SHA-256 over the domain tag, then the
+/// timestamp, process id and counter as big endian integers, then 16 bytes of RNG output.
+pub(crate) fn mix(timestamp: u32, pid: u32, counter: u64, random: &[u8; 16]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&Sha256::new()
+        .chain_update(MIX_DOMAIN)
+        .chain_update(timestamp.to_be_bytes())
+        .chain_update(pid.to_be_bytes())
+        .chain_update(counter.to_be_bytes())
+        .chain_update(random)
+        .finalize());
+    digest
+}
+
+/// Fill `dst` (at most 32 bytes) with the leading bytes of `mix` over `random` and the next value
+/// of the process wide counter.
+pub(crate) fn mix_into(timestamp: u32, random: &[u8; 16], dst: &mut [u8]) {
+    let counter = MIX_COUNTER.fetch_add(1, Ordering::Relaxed);
+    dst.copy_from_slice(&mix(timestamp, process::id(), counter, random)[..dst.len()]);
+}
+
+#[cfg(test)]
+mod tests {
+    use test::Bencher;
+    use super::*;
+
+    #[test]
+    fn test_fill_spans_refills() {
+        let mut seen = Vec::new();
+        for len in [20, 4000, 1, 4096, 9000].iter() {
+            let mut bytes = vec![0u8; *len];
+            fill(&mut bytes);
+            seen.push(bytes);
+        }
+        // 9000 bytes of zeros from a working RNG is not going to happen.
+        assert!(seen[4].iter().any(|&b| b != 0));
+        assert_ne!(&seen[0][..], &seen[1][..20]);
+    }
+
+    #[test]
+    fn test_mix_vector() {
+        let digest = mix(0x0102_0304, 42, 7, &[0xAA; 16]);
+        // sha256(b"ksuid-entropy-mix-v1" + pack(">IIQ", 0x01020304, 42, 7) + b"\xaa" * 16)
+        assert_eq!(&digest[..8], &[0xbe, 0x95, 0xbf, 0x5f, 0x4d, 0x03, 0x91, 0x0d]);
+        assert_eq!(&digest[24..], &[0xf1, 0x3d, 0xa3, 0x80, 0xfd, 0xe8, 0xae, 0x4c]);
+    }
+
+    #[test]
+    fn test_mix_survives_repeating_rng() {
+        // A stuck RNG still yields distinct payloads through the counter, and the same inputs in
+        // another process differ through the pid.
+        let random = [0u8; 16];
+        assert_ne!(mix(1, 100, 0, &random), mix(1, 100, 1, &random));
+        assert_ne!(mix(1, 100, 0, &random), mix(1, 101, 0, &random));
+        assert_ne!(mix(1, 100, 0, &random), mix(2, 100, 0, &random));
+        let mut a = [0u8; 16];
+        let mut b = [0u8; 16];
+        mix_into(1, &random, &mut a);
+        mix_into(1, &random, &mut b);
+        assert_ne!(a, b);
+    }
+
+    #[bench]
+    fn bench_fill_direct(b: &mut Bencher) {
+        let mut bytes = [0u8; 20];
+        b.iter(|| rand::thread_rng().fill_bytes(&mut bytes));
+    }
+
+    #[bench]
+    fn bench_fill_buffered(b: &mut Bencher) {
+        let mut bytes = [0u8; 20];
+        b.iter(|| fill(&mut bytes));
+    }
+}
@@ -1,15 +1,396 @@
-#[derive(Debug, Fail)]
+use failure;
+#[cfg(feature = "serde")]
+use serde_crate::Serialize;
+use std::fmt;
+use std::time::Duration;
+
+/// Errors produced by this crate. New variants may be added as validation grows, so matches
+/// need a wildcard arm; `kind()` gives a coarse, stable classification to branch on instead.
+///
+/// Errors about parsing text never include the text in their `Display`, `Debug` or serialized
+/// output, since it may be a token that shouldn't end up in logs. It is still available from
+/// `input()`, and `redacted()` renders a masked preview of it.
+///
+/// With the `serde` feature errors serialize as an object tagged with the variant name, e.g.
+/// `{"error":"InvalidBase62Length","expected":27,"actual":26}`, for passing them across RPC
+/// boundaries as structured problem details.
+#[derive(Clone, Fail, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(tag = "error"))]
+#[non_exhaustive]
 pub enum KSUIDError {
     #[fail(display = "byte slice too small: {}", length)]
     SliceTooSmall {
         length: usize,
     },
-    #[fail(display = "invalid character in base62 string: '{}'", value)]
+    #[fail(display = "payload has length {}, expected {}", actual, expected)]
+    InvalidPayloadLength {
+        expected: usize,
+        actual: usize,
+    },
+    #[fail(display = "invalid character in base62 string at position {}", position)]
     InvalidBase62Character {
+        #[cfg_attr(feature = "serde", serde(skip))]
         value: String,
+        /// Byte offset of the first offending character.
+        position: usize,
     },
-    #[fail(display = "base62 string has invalid length: '{}'", value)]
+    #[fail(display = "base62 string has length {}, expected {}", actual, expected)]
     InvalidBase62Length {
+        #[cfg_attr(feature = "serde", serde(skip))]
+        value: String,
+        expected: usize,
+        actual: usize,
+    },
+    #[fail(display = "base62 value is too large")]
+    Base62Overflow {
+        #[cfg_attr(feature = "serde", serde(skip))]
+        value: String,
+    },
+    #[fail(display = "invalid character in base58 string at position {}", position)]
+    InvalidBase58Character {
+        #[cfg_attr(feature = "serde", serde(skip))]
+        value: String,
+        /// Byte offset of the first offending character.
+        position: usize,
+    },
+    #[fail(display = "base58 string has length {}, expected {}", actual, expected)]
+    InvalidBase58Length {
+        #[cfg_attr(feature = "serde", serde(skip))]
+        value: String,
+        expected: usize,
+        actual: usize,
+    },
+    #[fail(display = "base58 value is too large")]
+    Base58Overflow {
+        #[cfg_attr(feature = "serde", serde(skip))]
+        value: String,
+    },
+    #[fail(display = "timestamp {} is outside the range a ksuid can represent", unix_seconds)]
+    TimestampOutOfRange {
+        unix_seconds: i64,
+    },
+    #[fail(display = "timestamp is {} second(s) in the future", seconds_ahead)]
+    FutureTimestamp {
+        /// How far the timestamp is ahead of the clock that checked it.
+        seconds_ahead: i64,
+    },
+    #[fail(display = "timestamp is {} second(s) older than allowed", seconds_past)]
+    StaleTimestamp {
+        /// How far the timestamp is past the oldest one the caller accepts.
+        seconds_past: i64,
+    },
+    #[fail(display = "not a ksuid urn")]
+    InvalidUrn {
+        #[cfg_attr(feature = "serde", serde(skip))]
+        value: String,
+    },
+    #[fail(display = "not a supported multibase ksuid")]
+    InvalidMultibase {
+        #[cfg_attr(feature = "serde", serde(skip))]
+        value: String,
+    },
+    #[fail(display = "not a 40 digit hex ksuid")]
+    InvalidHex {
+        #[cfg_attr(feature = "serde", serde(skip))]
         value: String,
     },
+    #[fail(display = "byte string has length {}, expected {}", actual, expected)]
+    InvalidByteLength {
+        expected: usize,
+        actual: usize,
+    },
+    #[fail(display = "not a valid pagination cursor")]
+    InvalidCursor {
+        #[cfg_attr(feature = "serde", serde(skip))]
+        value: String,
+    },
+    #[fail(display = "attribute value is not a ksuid string or 20 byte binary")]
+    InvalidAttributeValue,
+    #[fail(display = "not a ksuid file name")]
+    InvalidFilename {
+        #[cfg_attr(feature = "serde", serde(skip))]
+        value: String,
+    },
+    #[fail(display = "invalid ksuid filter: {}", reason)]
+    InvalidFilter {
+        reason: &'static str,
+    },
+    #[fail(display = "ksuid signature verification failed")]
+    InvalidSignature,
+    #[fail(display = "public id was not issued under this key")]
+    InvalidPublicId,
+    #[fail(display = "invalid generator options: {}", reason)]
+    InvalidOptions {
+        reason: &'static str,
+    },
+    #[fail(display = "clock moved backwards by {} second(s)", seconds)]
+    ClockRegression {
+        /// How far the wall clock is behind the latest time already minted with.
+        seconds: u32,
+    },
+    #[fail(display = "failed to persist generator state: {}", reason)]
+    StatePersistence {
+        reason: String,
+    },
+    #[fail(display = "generator rate limit exceeded, retry after {:?}", retry_after)]
+    RateLimited {
+        /// How long until the request would fit under the limit.
+        retry_after: Duration,
+    },
+}
+
+/// Coarse classification of a `KSUIDError`, returned by `KSUIDError::kind`. Kinds are stable
+/// across releases even as variants gain fields or split; more kinds may be added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[non_exhaustive]
+pub enum KSUIDErrorKind {
+    /// Input had the wrong length: a byte slice too short, or encoded text of the wrong width.
+    InvalidLength,
+    /// Encoded text contained a character outside its alphabet.
+    InvalidCharacter,
+    /// Encoded text decodes to a value too large for the id.
+    Overflow,
+    /// A timestamp before the ksuid epoch or too far after it, or further in the future or
+    /// further in the past than a caller allowed.
+    TimestampOutOfRange,
+    /// Text missing the prefix or framing of the expected representation, e.g. a URN that
+    /// doesn't start with `urn:ksuid:`.
+    InvalidFormat,
+    /// A file name that isn't a ksuid file name.
+    InvalidFilename,
+    /// A malformed or incompatible `KsuidFilter`.
+    InvalidFilter,
+    /// A signed or public id that didn't verify under the given key.
+    VerificationFailed,
+    /// A `Generator` was configured with options it can't honor.
+    InvalidOptions,
+    /// A `Generator` refused to mint because the wall clock went backwards.
+    ClockRegression,
+    /// A `Generator`'s `StateStore` failed to load or save.
+    StatePersistence,
+    /// A `Generator` with `max_per_second` set had no capacity left.
+    RateLimited,
+}
+
+// How many leading characters of the input `redacted()` shows, matching `KSUID::redacted()`.
+const VISIBLE_CHARS: usize = 6;
+
+impl KSUIDError {
+    /// Return the text that failed to parse, for variants that carry it. Treat it as sensitive:
+    /// it is the raw, possibly secret, input.
+    pub fn input(&self) -> Option<&str> {
+        match *self {
+            KSUIDError::InvalidBase62Character { ref value, .. }
+            | KSUIDError::InvalidBase62Length { ref value, .. }
+            | KSUIDError::Base62Overflow { ref value }
+            | KSUIDError::InvalidBase58Character { ref value, .. }
+            | KSUIDError::InvalidBase58Length { ref value, .. }
+            | KSUIDError::Base58Overflow { ref value }
+            | KSUIDError::InvalidUrn { ref value }
+            | KSUIDError::InvalidMultibase { ref value }
+            | KSUIDError::InvalidCursor { ref value }
+            | KSUIDError::InvalidHex { ref value }
+            | KSUIDError::InvalidFilename { ref value } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return a display adapter suited to log pipelines: the normal message followed by the
+    /// length and first few characters of the offending input, e.g.
+    /// `base62 string has length 26, expected 27 (input: 26 bytes, "0ujssz…")`.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let err = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDT").unwrap_err();
+    /// assert_eq!(err.to_string(), "base62 string has length 26, expected 27");
+    /// assert_eq!(err.redacted().to_string(), "base62 string has length 26, expected 27 (input: 26 bytes, \"0ujssz…\")");
+    /// assert_eq!(err.input(), Some("0ujsszwN8NRY24YaXiTIE2VWDT"));
+    /// ```
+    pub fn redacted(&self) -> RedactedError<'_> {
+        RedactedError(self)
+    }
+
+    /// Return the kind of this error.
+    /// # Example
+    /// ```
+    /// use ksuid::{KSUIDErrorKind, KSUID};
+    ///
+    /// let err = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDT").unwrap_err();
+    /// assert_eq!(err.kind(), KSUIDErrorKind::InvalidLength);
+    /// ```
+    pub fn kind(&self) -> KSUIDErrorKind {
+        match *self {
+            KSUIDError::SliceTooSmall { .. }
+            | KSUIDError::InvalidPayloadLength { .. }
+            | KSUIDError::InvalidByteLength { .. }
+            | KSUIDError::InvalidBase62Length { .. }
+            | KSUIDError::InvalidBase58Length { .. } => KSUIDErrorKind::InvalidLength,
+            KSUIDError::InvalidBase62Character { .. } | KSUIDError::InvalidBase58Character { .. } => KSUIDErrorKind::InvalidCharacter,
+            KSUIDError::Base62Overflow { .. } | KSUIDError::Base58Overflow { .. } => KSUIDErrorKind::Overflow,
+            KSUIDError::TimestampOutOfRange { .. }
+            | KSUIDError::FutureTimestamp { .. }
+            | KSUIDError::StaleTimestamp { .. } => KSUIDErrorKind::TimestampOutOfRange,
+            KSUIDError::InvalidUrn { .. }
+            | KSUIDError::InvalidMultibase { .. }
+            | KSUIDError::InvalidCursor { .. }
+            | KSUIDError::InvalidHex { .. }
+            | KSUIDError::InvalidAttributeValue => KSUIDErrorKind::InvalidFormat,
+            KSUIDError::InvalidFilename { .. } => KSUIDErrorKind::InvalidFilename,
+            KSUIDError::InvalidFilter { .. } => KSUIDErrorKind::InvalidFilter,
+            KSUIDError::InvalidSignature | KSUIDError::InvalidPublicId => KSUIDErrorKind::VerificationFailed,
+            KSUIDError::InvalidOptions { .. } => KSUIDErrorKind::InvalidOptions,
+            KSUIDError::ClockRegression { .. } => KSUIDErrorKind::ClockRegression,
+            KSUIDError::StatePersistence { .. } => KSUIDErrorKind::StatePersistence,
+            KSUIDError::RateLimited { .. } => KSUIDErrorKind::RateLimited,
+        }
+    }
+}
+
+impl fmt::Debug for KSUIDError {
+    // As derived, except that the input text is replaced by its length.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            KSUIDError::SliceTooSmall { length } => f.debug_struct("SliceTooSmall").field("length", &length).finish(),
+            KSUIDError::InvalidPayloadLength { expected, actual } => {
+                f.debug_struct("InvalidPayloadLength").field("expected", &expected).field("actual", &actual).finish()
+            }
+            KSUIDError::InvalidBase62Character { ref value, position } => f
+                .debug_struct("InvalidBase62Character")
+                .field("value", &RedactedInput(value))
+                .field("position", &position)
+                .finish(),
+            KSUIDError::InvalidBase62Length { ref value, expected, actual } => f
+                .debug_struct("InvalidBase62Length")
+                .field("value", &RedactedInput(value))
+                .field("expected", &expected)
+                .field("actual", &actual)
+                .finish(),
+            KSUIDError::Base62Overflow { ref value } => f.debug_struct("Base62Overflow").field("value", &RedactedInput(value)).finish(),
+            KSUIDError::InvalidBase58Character { ref value, position } => f
+                .debug_struct("InvalidBase58Character")
+                .field("value", &RedactedInput(value))
+                .field("position", &position)
+                .finish(),
+            KSUIDError::InvalidBase58Length { ref value, expected, actual } => f
+                .debug_struct("InvalidBase58Length")
+                .field("value", &RedactedInput(value))
+                .field("expected", &expected)
+                .field("actual", &actual)
+                .finish(),
+            KSUIDError::Base58Overflow { ref value } => f.debug_struct("Base58Overflow").field("value", &RedactedInput(value)).finish(),
+            KSUIDError::TimestampOutOfRange { unix_seconds } => f.debug_struct("TimestampOutOfRange").field("unix_seconds", &unix_seconds).finish(),
+            KSUIDError::FutureTimestamp { seconds_ahead } => f.debug_struct("FutureTimestamp").field("seconds_ahead", &seconds_ahead).finish(),
+            KSUIDError::StaleTimestamp { seconds_past } => f.debug_struct("StaleTimestamp").field("seconds_past", &seconds_past).finish(),
+            KSUIDError::InvalidUrn { ref value } => f.debug_struct("InvalidUrn").field("value", &RedactedInput(value)).finish(),
+            KSUIDError::InvalidMultibase { ref value } => f.debug_struct("InvalidMultibase").field("value", &RedactedInput(value)).finish(),
+            KSUIDError::InvalidHex { ref value } => f.debug_struct("InvalidHex").field("value", &RedactedInput(value)).finish(),
+            KSUIDError::InvalidByteLength { expected, actual } => {
+                f.debug_struct("InvalidByteLength").field("expected", &expected).field("actual", &actual).finish()
+            }
+            KSUIDError::InvalidCursor { ref value } => f.debug_struct("InvalidCursor").field("value", &RedactedInput(value)).finish(),
+            KSUIDError::InvalidAttributeValue => f.write_str("InvalidAttributeValue"),
+            KSUIDError::InvalidFilename { ref value } => f.debug_struct("InvalidFilename").field("value", &RedactedInput(value)).finish(),
+            KSUIDError::InvalidFilter { reason } => f.debug_struct("InvalidFilter").field("reason", &reason).finish(),
+            KSUIDError::InvalidSignature => f.write_str("InvalidSignature"),
+            KSUIDError::InvalidPublicId => f.write_str("InvalidPublicId"),
+            KSUIDError::InvalidOptions { reason } => f.debug_struct("InvalidOptions").field("reason", &reason).finish(),
+            KSUIDError::ClockRegression { seconds } => f.debug_struct("ClockRegression").field("seconds", &seconds).finish(),
+            KSUIDError::StatePersistence { ref reason } => f.debug_struct("StatePersistence").field("reason", reason).finish(),
+            KSUIDError::RateLimited { retry_after } => f.debug_struct("RateLimited").field("retry_after", &retry_after).finish(),
+        }
+    }
+}
+
+// Stands in for input text in `Debug` output.
+struct RedactedInput<'a>(&'a str);
+
+impl<'a> fmt::Debug for RedactedInput<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{} bytes>", self.0.len())
+    }
+}
+
+/// Display adapter returned by `KSUIDError::redacted`.
+#[derive(Debug)]
+pub struct RedactedError<'a>(&'a KSUIDError);
+
+impl<'a> fmt::Display for RedactedError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        if let Some(input) = self.0.input() {
+            let preview: String = input.chars().take(VISIBLE_CHARS).collect();
+            let ellipsis = if preview.len() < input.len() { "…" } else { "" };
+            write!(f, " (input: {} bytes, {:?})", input.len(), format!("{}{}", preview, ellipsis))?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by `KSUID::parse_many`, listing every input that failed along with its
+/// position in the input sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct BulkParseError {
+    /// `(index, cause)` for each failed element, in input order.
+    pub errors: Vec<(usize, KSUIDError)>,
+}
+
+impl fmt::Display for BulkParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ksuid(s) failed to parse", self.errors.len())?;
+        if let Some(&(index, ref cause)) = self.errors.first() {
+            write!(f, ", first at index {}: {}", index, cause)?;
+        }
+        Ok(())
+    }
+}
+
+impl failure::Fail for BulkParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ksuid::KSUID;
+
+    #[test]
+    fn test_errors_compare_and_clone() {
+        let err = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDT").unwrap_err();
+        assert_eq!(err.clone(), err);
+        assert_eq!(err, KSUIDError::InvalidBase62Length {
+            value: "0ujsszwN8NRY24YaXiTIE2VWDT".to_owned(),
+            expected: 27,
+            actual: 26,
+        });
+        assert_ne!(err, KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWD").unwrap_err());
+    }
+
+    #[test]
+    fn test_errors_do_not_echo_input() {
+        let secret = "s3cretTokenThatIsNotAKsuid!";
+        let err = KSUID::from_base62(secret).unwrap_err();
+        assert!(!err.to_string().contains("s3cret"));
+        assert!(!err.redacted().to_string().contains("TokenThat"));
+        assert_eq!(format!("{:?}", err), "InvalidBase62Character { value: <27 bytes>, position: 26 }");
+        let name = KSUIDError::InvalidFilename { value: secret.to_owned() };
+        assert!(!name.to_string().contains("s3cret") && !format!("{:?}", name).contains("s3cret"));
+        assert_eq!(err.input(), Some(secret));
+        let short = KSUID::from_base62("ab").unwrap_err();
+        assert!(short.redacted().to_string().ends_with("(input: 2 bytes, \"ab\")"));
+        assert!(KSUIDError::InvalidSignature.input().is_none());
+        assert_eq!(KSUIDError::InvalidSignature.redacted().to_string(), "ksuid signature verification failed");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_errors_serialize() {
+        let err = KSUID::from_base62("0ujsszwN8NRY2-YaXiTIE2VWDTS").unwrap_err();
+        assert_eq!(::serde_json::to_value(&err).unwrap(), json!({
+            "error": "InvalidBase62Character",
+            "position": 13,
+        }));
+        assert_eq!(::serde_json::to_value(KSUIDError::InvalidSignature).unwrap(), json!({"error": "InvalidSignature"}));
+        assert_eq!(::serde_json::to_value(err.kind()).unwrap(), json!("InvalidCharacter"));
+    }
 }
@@ -0,0 +1,127 @@
+use byteorder::{BigEndian, ByteOrder};
+use chrono::prelude::Utc;
+use chrono::{DateTime, Duration};
+use entropy;
+use errors;
+use ksuid::{to_ksuid_time, BYTE_LENGTH, KSUID, TIMESTAMP_LENGTH};
+use std::fmt;
+use std::ops::Deref;
+use std::str;
+
+const TTL_LENGTH: usize = 4;
+
+/// A `KSUID` that carries its own expiry, for short-lived tokens such as action links. The first
+/// four payload bytes hold the lifetime in seconds (big endian) and the remaining 12 bytes (96
+/// bits) are random. It is an ordinary ksuid on the wire: same 27 character form, same sort
+/// order, and any `KSUID` can be read as one.
+///
+/// The expiry is counted from the truncated creation second, so a token may expire up to a
+/// second before its full lifetime has passed, never after.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::Duration;
+/// use ksuid::{ExpiringKsuid, KSUID};
+///
+/// let link = ExpiringKsuid::new(Duration::minutes(15));
+/// assert!(!link.is_expired());
+/// let parsed: ExpiringKsuid = link.to_base62().parse().unwrap();
+/// assert_eq!(parsed.expires_at(), link.timestamp() + Duration::minutes(15));
+/// let plain: KSUID = parsed.into();
+/// assert_eq!(plain, *link);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExpiringKsuid(pub KSUID);
+
+impl ExpiringKsuid {
+    /// Mint a token valid for `ttl` from now. The lifetime is kept in whole seconds, clamped to
+    /// between zero and `u32::MAX` seconds.
+    pub fn new(ttl: Duration) -> Self {
+        let mut bytes = [0u8; BYTE_LENGTH];
+        BigEndian::write_u32(&mut bytes, to_ksuid_time(Utc::now()));
+        let seconds = ttl.num_seconds().clamp(0, i64::from(u32::MAX)) as u32;
+        BigEndian::write_u32(&mut bytes[TIMESTAMP_LENGTH..], seconds);
+        entropy::fill(&mut bytes[TIMESTAMP_LENGTH + TTL_LENGTH..]);
+        ExpiringKsuid(KSUID(bytes))
+    }
+
+    /// Return the lifetime the token was minted with.
+    pub fn ttl(&self) -> Duration {
+        Duration::seconds(i64::from(BigEndian::read_u32(self.0.payload())))
+    }
+
+    /// Return when the token expires.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.0.timestamp() + self.ttl()
+    }
+
+    /// Whether the token has expired.
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(Utc::now())
+    }
+
+    fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at()
+    }
+}
+
+impl Deref for ExpiringKsuid {
+    type Target = KSUID;
+
+    fn deref(&self) -> &KSUID {
+        &self.0
+    }
+}
+
+impl From<KSUID> for ExpiringKsuid {
+    fn from(id: KSUID) -> Self {
+        ExpiringKsuid(id)
+    }
+}
+
+impl From<ExpiringKsuid> for KSUID {
+    fn from(id: ExpiringKsuid) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for ExpiringKsuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for ExpiringKsuid {
+    type Err = errors::KSUIDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        KSUID::from_base62(s).map(ExpiringKsuid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ksuid::from_ksuid_time;
+
+    #[test]
+    fn test_expiring_ksuid() {
+        let token = ExpiringKsuid::new(Duration::seconds(90));
+        assert_eq!(token.ttl(), Duration::seconds(90));
+        assert_eq!(&token.payload()[..4], &[0, 0, 0, 90]);
+        let created = token.timestamp();
+        assert!(!token.is_expired_at(created + Duration::seconds(89)));
+        assert!(token.is_expired_at(created + Duration::seconds(90)));
+        assert_ne!(&ExpiringKsuid::new(Duration::seconds(90)).payload()[4..], &token.payload()[4..]);
+    }
+
+    #[test]
+    fn test_expiring_ksuid_clamps_ttl() {
+        assert_eq!(ExpiringKsuid::new(Duration::seconds(-5)).ttl(), Duration::zero());
+        assert!(ExpiringKsuid::new(Duration::zero()).is_expired());
+        assert_eq!(ExpiringKsuid::new(Duration::days(100_000)).ttl(), Duration::seconds(i64::from(u32::MAX)));
+        let max = ExpiringKsuid(KSUID([0xff; 20]));
+        assert_eq!(max.expires_at(), from_ksuid_time(u32::MAX) + Duration::seconds(i64::from(u32::MAX)));
+    }
+}
@@ -0,0 +1,194 @@
+//! A flat on-disk format for large id lists: nothing but packed 20 byte ids, back to back, with
+//! no header. The file length is always a multiple of 20 and record `i` starts at byte `20 * i`.
+//! Files written in ascending order can be binary searched in place once mapped.
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+use io::WriteKsuidExt;
+use ksuid::{BYTE_LENGTH, KSUID};
+#[cfg(feature = "mmap")]
+use std::ops::Deref;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+#[cfg(feature = "mmap")]
+use std::slice;
+
+/// Append-only writer of packed id files.
+/// # Example
+/// ```
+/// use ksuid::{KsuidFileWriter, KSUID};
+///
+/// let path = std::env::temp_dir().join(format!("ksuid-doc-{}.bin", KSUID::new()));
+/// let mut writer = KsuidFileWriter::create(&path).unwrap();
+/// writer.append(&KSUID::new()).unwrap();
+/// writer.flush().unwrap();
+/// assert_eq!(std::fs::metadata(&path).unwrap().len(), 20);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct KsuidFileWriter<W: Write> {
+    inner: W,
+}
+
+impl KsuidFileWriter<BufWriter<File>> {
+    /// Open `path` for appending, creating it if needed. Fails with `InvalidData` if an existing
+    /// file's length isn't a whole number of records, e.g. after a torn write, since appending
+    /// would misalign every record after it.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        if file.metadata()?.len() % BYTE_LENGTH as u64 != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "ksuid file length is not a multiple of 20"));
+        }
+        Ok(KsuidFileWriter { inner: BufWriter::new(file) })
+    }
+}
+
+impl<W: Write> KsuidFileWriter<W> {
+    /// Wrap any writer positioned at a record boundary.
+    pub fn new(inner: W) -> Self {
+        KsuidFileWriter { inner }
+    }
+
+    /// Append one id.
+    pub fn append(&mut self, id: &KSUID) -> io::Result<()> {
+        self.inner.write_ksuid(id)
+    }
+
+    /// Append every id in `ids`, in order.
+    pub fn append_all<'a, I: IntoIterator<Item = &'a KSUID>>(&mut self, ids: I) -> io::Result<()> {
+        ids.into_iter().try_for_each(|id| self.append(id))
+    }
+
+    /// Flush buffered records to the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// Unwrap the underlying writer, without flushing it.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// A packed id file mapped into memory and viewed as `&[KSUID]`, so opening it costs the same
+/// whatever its size and records are paged in as they are touched. It derefs to a slice, so the
+/// usual slice methods apply directly; for files written in ascending order that includes
+/// `binary_search`, and `ksuid::sort::range_by_time` for time windows.
+///
+/// As with any mapping, the file must not be truncated or modified while it is open.
+/// # Example
+/// ```
+/// use ksuid::{KsuidFile, KsuidFileWriter, KSUID};
+///
+/// let mut ids: Vec<KSUID> = (0..100).map(|_| KSUID::new()).collect();
+/// ids.sort();
+/// let path = std::env::temp_dir().join(format!("ksuid-doc-{}.bin", KSUID::new()));
+/// let mut writer = KsuidFileWriter::create(&path).unwrap();
+/// writer.append_all(&ids).unwrap();
+/// writer.flush().unwrap();
+///
+/// let file = KsuidFile::open(&path).unwrap();
+/// assert_eq!(file.len(), 100);
+/// assert_eq!(file.binary_search(&ids[42]), Ok(42));
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct KsuidFile {
+    // Mapping an empty file is an error on some platforms, so those are kept unmapped.
+    map: Option<Mmap>,
+}
+
+#[cfg(feature = "mmap")]
+impl KsuidFile {
+    /// Map the file at `path` read-only. Fails with `InvalidData` if its length isn't a whole
+    /// number of records.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len % BYTE_LENGTH as u64 != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "ksuid file length is not a multiple of 20"));
+        }
+        if len == 0 {
+            return Ok(KsuidFile { map: None });
+        }
+        // Safety: the mapping is read-only, and callers are told not to modify the file while
+        // it's open, which is the contract every mmap reader relies on.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(KsuidFile { map: Some(map) })
+    }
+
+    /// View the records as ids.
+    pub fn as_slice(&self) -> &[KSUID] {
+        match self.map {
+            Some(ref map) => {
+                // Safety: `KSUID` is `repr(transparent)` over `[u8; 20]`, so it has size 20,
+                // alignment 1 and no invalid bit patterns, and the length was checked to be a
+                // whole number of records on open.
+                unsafe { slice::from_raw_parts(map.as_ptr() as *const KSUID, map.len() / BYTE_LENGTH) }
+            }
+            None => &[],
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Deref for KsuidFile {
+    type Target = [KSUID];
+
+    fn deref(&self) -> &[KSUID] {
+        self.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_path() -> PathBuf {
+        env::temp_dir().join(format!("ksuid-test-{}.bin", KSUID::new()))
+    }
+
+    #[test]
+    fn test_writer_appends() {
+        let path = temp_path();
+        let ids: Vec<KSUID> = (0..10).map(|_| KSUID::new()).collect();
+        for chunk in ids.chunks(5) {
+            let mut writer = KsuidFileWriter::create(&path).unwrap();
+            writer.append_all(chunk).unwrap();
+            writer.flush().unwrap();
+        }
+        let bytes = fs::read(&path).unwrap();
+        let expected: Vec<u8> = ids.iter().flat_map(|id| id.as_bytes().to_vec()).collect();
+        assert_eq!(bytes, expected);
+
+        // A torn trailing record makes the file unsafe to append to.
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(KsuidFileWriter::create(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_file() {
+        let path = temp_path();
+        fs::write(&path, b"").unwrap();
+        assert!(KsuidFile::open(&path).unwrap().is_empty());
+
+        let mut ids: Vec<KSUID> = (0..1000).map(|_| KSUID::new()).collect();
+        ids.sort();
+        let mut writer = KsuidFileWriter::create(&path).unwrap();
+        writer.append_all(&ids).unwrap();
+        writer.flush().unwrap();
+        let file = KsuidFile::open(&path).unwrap();
+        assert_eq!(&file[..], &ids[..]);
+        assert_eq!(file.binary_search(&ids[500]), Ok(500));
+
+        fs::write(&path, [0u8; 21]).unwrap();
+        assert_eq!(KsuidFile::open(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+use errors;
+use ksuid::{KSUID, BYTE_LENGTH};
+
+/// Sortable file names. Names are the 40 character lowercase hex form of the id, optionally
+/// followed by `.` and an extension. Hex is used instead of base62 because base62 relies on
+/// letter case for both uniqueness and ordering, which case-insensitive file systems (the
+/// defaults on macOS and Windows) do not preserve. Because every name has the same width and
+/// hex digits sort in byte order, plain lexicographic order of the names (`ls`, `sort`, glob
+/// expansion) is creation time order, with ties broken by payload exactly as for `KSUID`s.
+impl KSUID {
+
+    /// Return a file name for this id, with `extension` appended after a `.` if given.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// let name = uid.to_filename(Some("wal"));
+    /// assert!(name.ends_with(".wal"));
+    /// assert_eq!(KSUID::from_filename(&name).unwrap(), uid);
+    /// ```
+    pub fn to_filename(&self, extension: Option<&str>) -> String {
+        let mut name: String = self.0.iter().map(|b| format!("{:02x}", b)).collect();
+        if let Some(ext) = extension {
+            name.push('.');
+            name.push_str(ext);
+        }
+        name
+    }
+
+    /// Parse a name produced by `to_filename`, ignoring any extension.
+    pub fn from_filename(name: &str) -> Result<Self, errors::KSUIDError> {
+        let invalid = || errors::KSUIDError::InvalidFilename { value: name.to_owned() };
+        let stem = name.split('.').next().unwrap_or("");
+        if stem.len() != BYTE_LENGTH * 2 || !stem.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            return Err(invalid());
+        }
+        let mut bytes = [0u8; BYTE_LENGTH];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&stem[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(KSUID(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ksuid::from_ksuid_time;
+
+    #[test]
+    fn test_filename_roundtrip() {
+        let uid = KSUID::new();
+        assert_eq!(KSUID::from_filename(&uid.to_filename(None)).unwrap(), uid);
+        assert_eq!(KSUID::from_filename(&uid.to_filename(Some("tar.gz"))).unwrap(), uid);
+        assert!(KSUID::from_filename("not-an-id.txt").is_err());
+        assert!(KSUID::from_filename(&uid.to_filename(None).to_uppercase()).is_err());
+    }
+
+    #[test]
+    fn test_filename_order() {
+        let mut names = Vec::new();
+        for (t, fill) in [(1, 0xff), (2, 0x00), (2, 0x01), (300, 0x00)].iter() {
+            names.push(KSUID::from_parts(from_ksuid_time(*t), &[*fill; 16]).unwrap().to_filename(Some("snap")));
+        }
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+    }
+}
@@ -0,0 +1,159 @@
+use byteorder::{BigEndian, ByteOrder};
+use errors;
+use ksuid::KSUID;
+
+const MAGIC: &[u8; 4] = b"KSF1";
+const HEADER_LENGTH: usize = 4 + 8 + 4;
+
+/// A bloom filter specialized for `KSUID`s. Instead of hashing each id, the probe positions are
+/// taken straight from the random payload via double hashing. The last 8 bytes seed the first
+/// hash; the second also mixes in the leading payload bytes, so ids minted by a `Generator`
+/// with a node id or counter (which make those bytes predictable) still spread well.
+/// # Example
+/// ```
+/// use ksuid::{KSUID, KsuidFilter};
+///
+/// let mut seen = KsuidFilter::with_rate(1000, 0.01).unwrap();
+/// let uid = KSUID::new();
+/// assert!(!seen.contains(&uid));
+/// seen.insert(&uid);
+/// assert!(seen.contains(&uid));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KsuidFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl KsuidFilter {
+
+    /// Create a filter with `num_bits` bits (rounded up to a multiple of 64) probing
+    /// `num_hashes` positions per id. Both are clamped to at least one.
+    pub fn new(num_bits: u64, num_hashes: u32) -> Self {
+        let words = num_bits.max(1).div_ceil(64);
+        KsuidFilter {
+            bits: vec![0; words as usize],
+            num_bits: words * 64,
+            num_hashes: num_hashes.max(1),
+        }
+    }
+
+    /// Create a filter sized for `expected_items` ids at the given false positive rate, using the
+    /// standard optimal bloom filter parameters. Fails with `KSUIDError::InvalidFilter` unless
+    /// `expected_items` is positive and the rate strictly between 0 and 1.
+    pub fn with_rate(expected_items: usize, false_positive_rate: f64) -> Result<Self, errors::KSUIDError> {
+        if expected_items == 0 {
+            return Err(errors::KSUIDError::InvalidFilter { reason: "expected item count must be positive" });
+        }
+        if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err(errors::KSUIDError::InvalidFilter { reason: "false positive rate must be between 0 and 1" });
+        }
+        let n = expected_items as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-n * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+        let hashes = (bits / n * ln2).round();
+        Ok(Self::new(bits as u64, hashes as u32))
+    }
+
+    fn positions(&self, id: &KSUID) -> impl Iterator<Item = u64> {
+        let h1 = BigEndian::read_u64(&id.0[12..]);
+        let h2 = (BigEndian::read_u64(&id.0[4..]) ^ h1.rotate_left(32)) | 1;
+        let num_bits = self.num_bits;
+        (0..u64::from(self.num_hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+
+    /// Add an id to the filter.
+    pub fn insert(&mut self, id: &KSUID) {
+        for pos in self.positions(id) {
+            self.bits[(pos / 64) as usize] |= 1 << (pos % 64);
+        }
+    }
+
+    /// Check whether an id may have been inserted. False positives are possible, false
+    /// negatives are not.
+    pub fn contains(&self, id: &KSUID) -> bool {
+        self.positions(id).all(|pos| self.bits[(pos / 64) as usize] & (1 << (pos % 64)) != 0)
+    }
+
+    /// Add every id of `other` to this filter. Both filters must have the same shape.
+    pub fn merge(&mut self, other: &KsuidFilter) -> Result<(), errors::KSUIDError> {
+        if self.num_bits != other.num_bits || self.num_hashes != other.num_hashes {
+            return Err(errors::KSUIDError::InvalidFilter { reason: "filters have different shapes" });
+        }
+        for (word, theirs) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word |= theirs;
+        }
+        Ok(())
+    }
+
+    /// Serialize the filter: a `KSF1` magic, the bit count (u64) and hash count (u32), then the
+    /// bit array as u64 words, all big endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![0u8; HEADER_LENGTH + self.bits.len() * 8];
+        out[..4].copy_from_slice(MAGIC);
+        BigEndian::write_u64(&mut out[4..], self.num_bits);
+        BigEndian::write_u32(&mut out[12..], self.num_hashes);
+        BigEndian::write_u64_into(&self.bits, &mut out[HEADER_LENGTH..]);
+        out
+    }
+
+    /// Deserialize a filter produced by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, errors::KSUIDError> {
+        let invalid = |reason| errors::KSUIDError::InvalidFilter { reason };
+        if bytes.len() < HEADER_LENGTH || &bytes[..4] != MAGIC {
+            return Err(invalid("missing filter header"));
+        }
+        let num_bits = BigEndian::read_u64(&bytes[4..]);
+        let num_hashes = BigEndian::read_u32(&bytes[12..]);
+        let body = &bytes[HEADER_LENGTH..];
+        if num_bits == 0 || num_bits % 64 != 0 || num_hashes == 0 || body.len() as u64 * 8 != num_bits {
+            return Err(invalid("filter header does not match its body"));
+        }
+        let mut bits = vec![0u64; body.len() / 8];
+        BigEndian::read_u64_into(body, &mut bits);
+        Ok(KsuidFilter { bits, num_bits, num_hashes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_no_false_negatives() {
+        let mut filter = KsuidFilter::with_rate(10_000, 0.01).unwrap();
+        let ids: Vec<KSUID> = (0..10_000).map(|_| KSUID::new()).collect();
+        for id in &ids {
+            filter.insert(id);
+        }
+        assert!(ids.iter().all(|id| filter.contains(id)));
+        let false_positives = (0..10_000).filter(|_| filter.contains(&KSUID::new())).count();
+        assert!(false_positives < 300, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn test_filter_rejects_bad_rates() {
+        assert!(KsuidFilter::with_rate(0, 0.01).is_err());
+        for rate in [0.0, -0.5, 1.0, 2.0, f64::NAN] {
+            assert!(KsuidFilter::with_rate(1000, rate).is_err(), "rate {}", rate);
+        }
+    }
+
+    #[test]
+    fn test_filter_merge_and_serialize() {
+        let (a_id, b_id) = (KSUID::new(), KSUID::new());
+        let mut a = KsuidFilter::new(4096, 4);
+        let mut b = KsuidFilter::new(4096, 4);
+        a.insert(&a_id);
+        b.insert(&b_id);
+        a.merge(&b).unwrap();
+        assert!(a.contains(&a_id) && a.contains(&b_id));
+        assert!(a.merge(&KsuidFilter::new(64, 4)).is_err());
+
+        let restored = KsuidFilter::from_bytes(&a.to_bytes()).unwrap();
+        assert_eq!(restored, a);
+        assert!(KsuidFilter::from_bytes(&a.to_bytes()[..20]).is_err());
+        assert!(KsuidFilter::from_bytes(b"nope").is_err());
+    }
+}
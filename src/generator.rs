@@ -0,0 +1,977 @@
+use byteorder::{BigEndian, ByteOrder};
+use chrono::prelude::{DateTime, Utc};
+use chrono::Duration;
+use chacha::ChaCha20Options;
+#[cfg(feature = "chacha")]
+use chacha::ChaChaSource;
+use clock::{ClockRegression, ClockRegressionPolicy};
+use entropy;
+use errors::KSUIDError;
+use hook::{GenerationContext, GenerationHook, Hooks};
+use ksuid::{from_ksuid_time, to_ksuid_time, truncate_ksuid_time, BYTE_LENGTH, EPOCH_START, KSUID, TIMESTAMP_LENGTH};
+use rate_limit::TokenBucket;
+use sort;
+use state::{GeneratorState, StateStore};
+#[cfg(feature = "metrics")]
+use metrics;
+use std::iter;
+use std::thread;
+use std::time::Instant;
+
+/// Options controlling how a `Generator` mints ids.
+#[derive(Clone, Debug, Default)]
+pub struct GeneratorOptions {
+    /// Round every issued timestamp down to a multiple of this duration (e.g. one hour) to
+    /// reduce what public ids reveal about their creation time. `None` keeps full precision.
+    pub timestamp_granularity: Option<Duration>,
+    /// Embed this identifier (at most `MAX_NODE_ID_LENGTH` bytes) at the start of every payload so
+    /// the minting instance can be recovered later with `KSUID::node_id(len)`. Each byte costs 8
+    /// bits of entropy.
+    pub node_id: Option<Vec<u8>>,
+    /// Reserve two payload bytes (after the node id, if any) for a big endian counter that starts
+    /// at a random value each second and increments per id. Ids from one generator are then
+    /// totally ordered even within the same second, at the cost of 16 bits of entropy (112
+    /// remain). If the counter is exhausted within a second, the timestamp is advanced by a
+    /// second rather than wrapping.
+    pub counter: bool,
+    /// Hybrid logical clock mode: like `counter`, but the counter occupies the first two payload
+    /// bytes (ahead of any node id), restarts at zero each second, and `Generator::observe` folds
+    /// in ids received from elsewhere. Each timestamp is `max(wall clock, last issued or
+    /// observed)`, so an id minted after observing another always sorts after it, even across
+    /// nodes with a skewed clock. Supersedes `counter`; read node ids with
+    /// `Generator::node_id_of` since they no longer start the payload.
+    pub hlc: bool,
+    /// What to do when the wall clock reads earlier than it did for a previous id. The default,
+    /// `Ignore`, mints with the clock as read.
+    pub clock_regression: ClockRegressionPolicy,
+    /// Refuse to move more than this far ahead of the wall clock, which the `counter`, `hlc` and
+    /// `Hold` machinery can otherwise drift into, e.g. after observing an id from a node with a
+    /// broken clock. The step that would cross the limit is refused and the generator keeps its
+    /// state: `observe` fails, as do `try_next_id` and `try_next_batch`, with
+    /// `KSUIDError::FutureTimestamp`, while `next_id` and `next_batch` panic. `None` disables the
+    /// check.
+    pub max_future_skew: Option<Duration>,
+    /// Cap issuance at this many ids per second, with bursts of up to one second's worth after
+    /// a quiet spell. `next_id` and `next_batch` sleep until there is capacity, while
+    /// `try_next_id` and `try_next_batch` fail with `KSUIDError::RateLimited` instead. A batch
+    /// counts as one request for its whole size. `None` leaves issuance unlimited.
+    pub max_per_second: Option<u32>,
+    /// Derive the random payload bytes by hashing rather than taking them straight from the RNG,
+    /// as a guard against weak or repeated RNG state, e.g. containers snapshotted before boot
+    /// entropy arrived. The bytes are the leading bytes of
+    /// `SHA-256("ksuid-entropy-mix-v1" || timestamp || pid || counter || rng)`, where `timestamp`
+    /// is the id's 32 bit timestamp, `pid` the 32 bit process id, `counter` a 64 bit process wide
+    /// sequence number (all big endian) and `rng` 16 fresh RNG bytes. Ids within a process stay
+    /// distinct even if the RNG repeats; the output is only as unpredictable as the RNG. Costs one
+    /// hash per id.
+    pub mix_entropy: bool,
+    /// Draw all randomness from a ChaCha20 generator owned by the `Generator` and seeded from
+    /// the operating system, instead of the shared per-thread `rand` generator. For deployments
+    /// whose policy dictates the RNG construction. `None` uses the thread generator. Only the
+    /// `chacha` feature makes `ChaCha20Options` constructible.
+    pub chacha20: Option<ChaCha20Options>,
+}
+
+// Counters start below this so at least 32768 ids fit in a second before borrowing the next one.
+pub(crate) const COUNTER_START_LIMIT: u16 = 1 << 15;
+pub(crate) const COUNTER_LENGTH: usize = 2;
+
+// How far ahead of the wall clock `observe` accepts ids when `max_future_skew` is unset.
+const DEFAULT_OBSERVE_SKEW_SECONDS: i64 = 300;
+
+/// The longest node id a `Generator` accepts, leaving at least 64 random payload bits even with
+/// the counter enabled.
+pub const MAX_NODE_ID_LENGTH: usize = 6;
+
+/// A configurable source of `KSUID`s. `KSUID::new()` covers the common case; a generator is for
+/// when the way ids are minted needs tuning.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::{Duration, Timelike};
+/// use ksuid::{Generator, GeneratorOptions};
+///
+/// let mut gen = Generator::with_options(GeneratorOptions {
+///     timestamp_granularity: Some(Duration::hours(1)),
+///     ..Default::default()
+/// }).unwrap();
+/// assert_eq!(gen.next_id().timestamp().minute(), 0);
+/// ```
+#[derive(Debug, Default)]
+pub struct Generator {
+    options: GeneratorOptions,
+    last_time: u32,
+    counter: u16,
+    // The latest wall clock reading minted with, for spotting regressions.
+    last_wall: u32,
+    limiter: Option<TokenBucket>,
+    hooks: Hooks,
+    #[cfg(feature = "chacha")]
+    chacha: Option<ChaChaSource>,
+    store: Option<Box<dyn StateStore>>,
+    // The `last_time` most recently saved to `store`.
+    persisted_time: u32,
+}
+
+impl Generator {
+
+    /// Create a generator with the default options, equivalent to calling `KSUID::new()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a generator with the given options.
+    ///
+    /// Fails with `KSUIDError::InvalidOptions` if `node_id` is longer than `MAX_NODE_ID_LENGTH`,
+    /// `max_per_second` is zero or a `chacha20` reseed interval is zero.
+    pub fn with_options(options: GeneratorOptions) -> Result<Self, KSUIDError> {
+        let invalid = |reason| Err(KSUIDError::InvalidOptions { reason });
+        if options.node_id.as_ref().is_some_and(|node_id| node_id.len() > MAX_NODE_ID_LENGTH) {
+            return invalid("node id longer than MAX_NODE_ID_LENGTH bytes");
+        }
+        if options.max_per_second == Some(0) {
+            return invalid("rate limit must allow at least one id per second");
+        }
+        #[cfg(feature = "chacha")]
+        {
+            if options.chacha20.is_some_and(|chacha| chacha.reseed_interval == 0) {
+                return invalid("chacha20 reseed interval must be nonzero");
+            }
+        }
+        let limiter = options.max_per_second.map(TokenBucket::new);
+        Ok(Generator {
+            #[cfg(feature = "chacha")]
+            chacha: options.chacha20.map(ChaChaSource::new),
+            options,
+            limiter,
+            ..Default::default()
+        })
+    }
+
+    /// Create a generator that keeps its counter state in `store`, so ids stay strictly
+    /// increasing across restarts. Saved state is loaded now, and the first id issued sorts after
+    /// every id issued under it; after that the state is saved once per second of issuance. Only
+    /// the `counter` and `hlc` options order ids strictly, so the store is unused without them.
+    ///
+    /// Fails with `KSUIDError::StatePersistence` if the saved state can't be read, or as
+    /// `with_options` does.
+    pub fn with_store<S: StateStore + 'static>(options: GeneratorOptions, mut store: S) -> Result<Self, KSUIDError> {
+        let saved = store.load().map_err(|err| KSUIDError::StatePersistence { reason: err.to_string() })?;
+        let mut gen = Generator::with_options(options)?;
+        if let Some(state) = saved {
+            // A spent counter makes the next id in this second borrow the next one.
+            gen.last_time = state.last_time;
+            gen.counter = u16::MAX;
+            gen.persisted_time = state.last_time;
+        }
+        gen.store = Some(Box::new(store));
+        Ok(gen)
+    }
+
+    /// Return the options this generator was built with.
+    pub fn options(&self) -> &GeneratorOptions {
+        &self.options
+    }
+
+    /// Mint the next id, waiting for capacity if `max_per_second` is set.
+    pub fn next_id(&mut self) -> KSUID {
+        self.wait_for(1);
+        self.read_clock()
+            .and_then(|clock| self.mint_one(clock))
+            .unwrap_or_else(|err| panic!("{}; use try_next_id to handle it", err))
+    }
+
+    /// Mint the next id, or fail with `KSUIDError::RateLimited` if `max_per_second` is set and
+    /// already used up.
+    /// # Example
+    /// ```
+    /// use ksuid::{Generator, GeneratorOptions, KSUIDErrorKind};
+    ///
+    /// let mut gen = Generator::with_options(GeneratorOptions { max_per_second: Some(2), ..Default::default() }).unwrap();
+    /// assert!(gen.try_next_id().is_ok());
+    /// assert!(gen.try_next_id().is_ok());
+    /// assert_eq!(gen.try_next_id().unwrap_err().kind(), KSUIDErrorKind::RateLimited);
+    /// ```
+    pub fn try_next_id(&mut self) -> Result<KSUID, KSUIDError> {
+        let clock = self.read_clock()?;
+        self.try_reserve(1)?;
+        self.mint_one(clock)
+    }
+
+    /// Mint `n` ids at once, waiting for capacity for all of them if `max_per_second` is set.
+    pub fn next_batch(&mut self, n: usize) -> Vec<KSUID> {
+        #[cfg(feature = "metrics")]
+        metrics::record_batch(n);
+        self.wait_for(n);
+        self.read_clock()
+            .and_then(|clock| self.mint_batch(clock, n))
+            .unwrap_or_else(|err| panic!("{}; use try_next_batch to handle it", err))
+    }
+
+    /// Mint `n` ids at once, or none if `max_per_second` is set and can't cover all of them now.
+    /// A batch larger than `max_per_second` always fails; use `next_batch` for those. Batches are
+    /// all or nothing: on any error no id is reported to hooks or metrics, and a clock error is
+    /// raised before any rate limit capacity is spent.
+    pub fn try_next_batch(&mut self, n: usize) -> Result<Vec<KSUID>, KSUIDError> {
+        #[cfg(feature = "metrics")]
+        metrics::record_batch(n);
+        let clock = self.read_clock()?;
+        self.try_reserve(n)?;
+        self.mint_batch(clock, n)
+    }
+
+    /// Mint `n` distinct ids in ascending order, e.g. for bulk inserts into a B-tree index. With
+    /// the `counter` or `hlc` options ids come out strictly increasing already; otherwise the
+    /// batch is radix sorted and any duplicate replaced. Rate limits and hooks apply as for
+    /// `next_batch`.
+    /// # Example
+    /// ```
+    /// use ksuid::Generator;
+    ///
+    /// let ids = Generator::new().generate_n_sorted(10_000);
+    /// assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    /// ```
+    pub fn generate_n_sorted(&mut self, n: usize) -> Vec<KSUID> {
+        let mut ids = self.next_batch(n);
+        if self.options.counter || self.options.hlc {
+            return ids;
+        }
+        loop {
+            sort::sort_unstable(&mut ids);
+            ids.dedup();
+            if ids.len() == n {
+                return ids;
+            }
+            let missing = n - ids.len();
+            ids.extend(self.next_batch(missing));
+        }
+    }
+
+    fn wait_for(&mut self, n: usize) {
+        if let Some(ref mut limiter) = self.limiter {
+            let wait = limiter.take(n, Instant::now());
+            if !wait.is_zero() {
+                thread::sleep(wait);
+            }
+        }
+    }
+
+    fn try_reserve(&mut self, n: usize) -> Result<(), KSUIDError> {
+        match self.limiter {
+            Some(ref mut limiter) => limiter.try_take(n, Instant::now()).map_err(|retry_after| {
+                #[cfg(feature = "metrics")]
+                metrics::record_rate_limited();
+                KSUIDError::RateLimited { retry_after }
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Register a hook to be called with every id this generator issues from now on, e.g. to
+    /// keep an audit trail. Hooks run in registration order.
+    pub fn add_hook<H: GenerationHook + 'static>(&mut self, hook: H) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    // Read the wall clock for a batch of ids, returning it along with the time to mint with.
+    fn read_clock(&mut self) -> Result<(DateTime<Utc>, u32), KSUIDError> {
+        let now = Utc::now();
+        let mut time = to_ksuid_time(now);
+        if let Some(granularity) = self.options.timestamp_granularity {
+            time = truncate_ksuid_time(time, granularity);
+        }
+        self.check_clock(time).map(|time| (now, time))
+    }
+
+    // Mint a single id at the time read by `read_clock`, without the batch's allocation.
+    fn mint_one(&mut self, clock: (DateTime<Utc>, u32)) -> Result<KSUID, KSUIDError> {
+        let mut id = [KSUID::default()];
+        self.mint_into(clock, &mut id)?;
+        Ok(id[0])
+    }
+
+    // Mint `n` ids at the time read by `read_clock`.
+    fn mint_batch(&mut self, clock: (DateTime<Utc>, u32), n: usize) -> Result<Vec<KSUID>, KSUIDError> {
+        let mut ids = vec![KSUID::default(); n];
+        self.mint_into(clock, &mut ids)?;
+        Ok(ids)
+    }
+
+    // Fill `ids` with fresh ids. If any fails the counter is rolled back and the rest are
+    // discarded before hooks or metrics see them.
+    fn mint_into(&mut self, (now, time): (DateTime<Utc>, u32), ids: &mut [KSUID]) -> Result<(), KSUIDError> {
+        let saved = (self.last_time, self.counter);
+        for slot in ids.iter_mut() {
+            match self.mint(time) {
+                Ok(uid) => *slot = uid,
+                Err(err) => {
+                    self.last_time = saved.0;
+                    self.counter = saved.1;
+                    return Err(err);
+                }
+            }
+        }
+        #[cfg(feature = "metrics")]
+        metrics::record_issued(ids.len());
+        if !self.hooks.is_empty() {
+            for (batch_index, uid) in ids.iter().enumerate() {
+                self.hooks.notify(uid, &GenerationContext {
+                    options: &self.options,
+                    issued_at: now,
+                    batch_index,
+                    batch_size: ids.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn mint(&mut self, mut time: u32) -> Result<KSUID, KSUIDError> {
+        let mut bytes = [0u8; BYTE_LENGTH];
+        let mut random_start = TIMESTAMP_LENGTH;
+        if self.options.hlc {
+            time = self.write_counter(time, &mut bytes[random_start..])?;
+            random_start += COUNTER_LENGTH;
+        }
+        if let Some(ref node_id) = self.options.node_id {
+            bytes[random_start..random_start + node_id.len()].copy_from_slice(node_id);
+            random_start += node_id.len();
+        }
+        if self.options.counter && !self.options.hlc {
+            time = self.write_counter(time, &mut bytes[random_start..])?;
+            random_start += COUNTER_LENGTH;
+        }
+        // Only the bytes nothing else claimed need randomness.
+        #[cfg(feature = "metrics")]
+        let rng_start = Instant::now();
+        if self.options.mix_entropy {
+            let mut random = [0u8; 16];
+            self.fill_random(&mut random);
+            entropy::mix_into(time, &random, &mut bytes[random_start..]);
+        } else {
+            self.fill_random(&mut bytes[random_start..]);
+        }
+        #[cfg(feature = "metrics")]
+        metrics::record_rng(rng_start.elapsed());
+        BigEndian::write_u32(&mut bytes, time);
+        let uid = KSUID(bytes);
+        if let Some(tolerance) = self.options.max_future_skew {
+            uid.validate_not_future(tolerance)?;
+        }
+        Ok(uid)
+    }
+
+    // Apply the clock regression policy to a wall clock reading, returning the time to mint with.
+    fn check_clock(&mut self, time: u32) -> Result<u32, KSUIDError> {
+        if time >= self.last_wall {
+            self.last_wall = time;
+            return Ok(time);
+        }
+        match self.options.clock_regression {
+            ClockRegressionPolicy::Ignore => Ok(time),
+            ClockRegressionPolicy::Hold => self.check_future(self.last_wall).map(|_| self.last_wall),
+            ClockRegressionPolicy::Error => Err(KSUIDError::ClockRegression { seconds: self.last_wall - time }),
+            ClockRegressionPolicy::Callback(ref callback) => {
+                callback(&ClockRegression {
+                    previous: from_ksuid_time(self.last_wall),
+                    now: from_ksuid_time(time),
+                });
+                self.check_future(self.last_wall).map(|_| self.last_wall)
+            }
+        }
+    }
+
+    // Fail if minting at `time` would exceed `max_future_skew`.
+    fn check_future(&self, time: u32) -> Result<(), KSUIDError> {
+        if let Some(tolerance) = self.options.max_future_skew {
+            let ahead = from_ksuid_time(time) - Utc::now();
+            if ahead > tolerance {
+                return Err(KSUIDError::FutureTimestamp { seconds_ahead: ahead.num_seconds() });
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_random(&mut self, dst: &mut [u8]) {
+        #[cfg(feature = "chacha")]
+        {
+            if let Some(ref mut chacha) = self.chacha {
+                return chacha.fill(dst);
+            }
+        }
+        entropy::fill(dst)
+    }
+
+    // A counter starting point below `COUNTER_START_LIMIT`, from the generator's RNG. Hybrid
+    // logical clocks count from zero instead.
+    fn random_counter_start(&mut self) -> u16 {
+        if self.options.hlc {
+            return 0;
+        }
+        let mut bytes = [0u8; 2];
+        self.fill_random(&mut bytes);
+        BigEndian::read_u16(&bytes) & (COUNTER_START_LIMIT - 1)
+    }
+
+    /// Return an endless iterator minting ids from this generator. With the counter option each
+    /// id is strictly greater than the one before.
+    /// # Example
+    /// ```
+    /// use ksuid::{Generator, GeneratorOptions, KSUID};
+    ///
+    /// let mut gen = Generator::with_options(GeneratorOptions { counter: true, ..Default::default() }).unwrap();
+    /// let ids: Vec<KSUID> = gen.iter().take(1000).collect();
+    /// assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    /// ```
+    pub fn iter(&mut self) -> impl Iterator<Item = KSUID> + '_ {
+        iter::repeat_with(move || self.next_id())
+    }
+
+    // Advance the counter for an id minted at wall clock `time`, write it to the front of `dst`,
+    // and return the timestamp the id must carry. A new second is saved to the store, if any,
+    // before any id carries it.
+    fn write_counter(&mut self, time: u32, dst: &mut [u8]) -> Result<u32, KSUIDError> {
+        self.advance_counter(time)?;
+        if self.last_time > self.persisted_time {
+            if let Some(ref mut store) = self.store {
+                store.save(&GeneratorState { last_time: self.last_time })
+                    .map_err(|err| KSUIDError::StatePersistence { reason: err.to_string() })?;
+            }
+            self.persisted_time = self.last_time;
+        }
+        #[cfg(feature = "metrics")]
+        {
+            if self.last_time != time {
+                metrics::record_adjustment();
+            }
+        }
+        BigEndian::write_u16(dst, self.counter);
+        Ok(self.last_time)
+    }
+
+    /// Record an id received from another node, so that every id this generator mints afterwards
+    /// sorts after it. This is the receive step of a hybrid logical clock and only has an effect
+    /// with the `hlc` option. Observing an id timestamped in the future pulls this generator's
+    /// timestamps forward to match, but only so far: an id more than `max_future_skew` (five
+    /// minutes if unset) ahead of the wall clock is refused with `KSUIDError::FutureTimestamp`
+    /// and leaves the generator unchanged, so one peer with a broken clock can't drag every later
+    /// id into the future.
+    /// # Example
+    /// ```
+    /// extern crate chrono;
+    /// extern crate ksuid;
+    /// use chrono::{Duration, Utc};
+    /// use ksuid::{Generator, GeneratorOptions, KSUID};
+    ///
+    /// let mut local = Generator::with_options(GeneratorOptions { hlc: true, ..Default::default() }).unwrap();
+    /// // An id from a node whose clock runs a minute fast.
+    /// let remote = KSUID::from_parts(Utc::now() + Duration::minutes(1), &[0; 16]).unwrap();
+    /// local.observe(&remote).unwrap();
+    /// assert!(local.next_id() > remote);
+    /// let broken = KSUID::from_parts(Utc::now() + Duration::days(1), &[0; 16]).unwrap();
+    /// assert!(local.observe(&broken).is_err());
+    /// ```
+    pub fn observe(&mut self, uid: &KSUID) -> Result<(), KSUIDError> {
+        if !self.options.hlc {
+            return Ok(());
+        }
+        let tolerance = self.options.max_future_skew.unwrap_or_else(|| Duration::seconds(DEFAULT_OBSERVE_SKEW_SECONDS));
+        uid.validate_not_future(tolerance)?;
+        let observed = (BigEndian::read_u32(&uid.0), BigEndian::read_u16(uid.payload()));
+        if observed > (self.last_time, self.counter) {
+            self.last_time = observed.0;
+            self.counter = observed.1;
+        }
+        Ok(())
+    }
+
+    // Step the (last_time, counter) pair forward so it is strictly greater than the previous one.
+    // A clock that went backwards keeps using the last issued second. Fails, changing nothing,
+    // once the counter is spent in the last second a ksuid can represent, or when the step would
+    // exceed `max_future_skew`.
+    fn advance_counter(&mut self, time: u32) -> Result<(), KSUIDError> {
+        let next_time = if time > self.last_time {
+            time
+        } else if self.counter == u16::MAX {
+            self.last_time.checked_add(1).ok_or(KSUIDError::TimestampOutOfRange {
+                unix_seconds: EPOCH_START + i64::from(u32::MAX) + 1,
+            })?
+        } else {
+            self.last_time
+        };
+        self.check_future(next_time)?;
+        if next_time == self.last_time {
+            self.counter += 1;
+        } else {
+            self.last_time = next_time;
+            self.counter = self.random_counter_start();
+        }
+        Ok(())
+    }
+
+    fn node_id_len(&self) -> usize {
+        self.options.node_id.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Return the counter embedded in an id minted by a generator with this one's options.
+    pub fn counter_of(&self, uid: &KSUID) -> u16 {
+        let start = if self.options.hlc { 0 } else { self.node_id_len() };
+        BigEndian::read_u16(&uid.payload()[start..start + COUNTER_LENGTH])
+    }
+
+    /// Return the node id embedded in an id minted by a generator with this one's options. Unlike
+    /// `KSUID::node_id` this accounts for the `hlc` counter preceding it.
+    pub fn node_id_of<'a>(&self, uid: &'a KSUID) -> &'a [u8] {
+        let start = if self.options.hlc { COUNTER_LENGTH } else { 0 };
+        &uid.payload()[start..start + self.node_id_len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test::Bencher;
+    use super::*;
+
+    #[test]
+    fn test_generator_granularity() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            timestamp_granularity: Some(Duration::days(1)),
+            ..Default::default()
+        }).unwrap();
+        let uid = gen.next_id();
+        assert_eq!(uid.timestamp().timestamp() % 86400, 0);
+        assert_ne!(uid, gen.next_id());
+    }
+
+    #[test]
+    fn test_generator_counter_ordering() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            counter: true,
+            ..Default::default()
+        }).unwrap();
+        let mut last = gen.next_id();
+        assert!(gen.counter_of(&last) < COUNTER_START_LIMIT);
+        for _ in 0..100_000 {
+            let uid = gen.next_id();
+            assert!(uid.as_bytes() > last.as_bytes());
+            last = uid;
+        }
+    }
+
+    #[test]
+    fn test_generator_iter() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            counter: true,
+            ..Default::default()
+        }).unwrap();
+        let ids: Vec<KSUID> = gen.iter().zip(0..10_000).map(|(id, _)| id).collect();
+        assert_eq!(ids.len(), 10_000);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        // The generator's state carries on after the iterator is dropped.
+        assert!(gen.next_id() > ids[ids.len() - 1]);
+    }
+
+    #[test]
+    fn test_generator_node_id() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            node_id: Some(vec![0xAB, 0xCD]),
+            counter: true,
+            ..Default::default()
+        }).unwrap();
+        let first = gen.next_id();
+        let second = gen.next_id();
+        assert_eq!(first.node_id(2), &[0xAB, 0xCD]);
+        if first.timestamp() == second.timestamp() {
+            assert_eq!(gen.counter_of(&second), gen.counter_of(&first) + 1);
+        }
+    }
+
+    #[test]
+    fn test_generator_node_id_too_long() {
+        let err = Generator::with_options(GeneratorOptions {
+            node_id: Some(vec![0; MAX_NODE_ID_LENGTH + 1]),
+            ..Default::default()
+        }).unwrap_err();
+        assert_eq!(err.kind(), ::errors::KSUIDErrorKind::InvalidOptions);
+    }
+
+    #[test]
+    fn test_generator_counter_overflow_borrows_second() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            counter: true,
+            ..Default::default()
+        }).unwrap();
+        let now = gen.next_id();
+        gen.counter = u16::MAX;
+        let next = gen.next_id();
+        assert!(next.timestamp() > now.timestamp());
+        assert!(next.as_bytes() > now.as_bytes());
+    }
+
+    #[test]
+    fn test_generator_rate_limit() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            max_per_second: Some(20),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(gen.try_next_batch(15).unwrap().len(), 15);
+        match gen.try_next_batch(10) {
+            Err(KSUIDError::RateLimited { retry_after }) => assert!(retry_after <= ::std::time::Duration::from_millis(250)),
+            other => panic!("expected rate limit, got {:?}", other),
+        }
+        // The failed batch took nothing, so smaller requests still fit.
+        assert_eq!(gen.try_next_batch(5).unwrap().len(), 5);
+        assert!(gen.try_next_id().is_err());
+        // Blocking calls wait out the deficit instead.
+        let start = Instant::now();
+        assert_eq!(gen.next_batch(4).len(), 4);
+        assert!(start.elapsed() >= ::std::time::Duration::from_millis(150));
+    }
+
+    #[test]
+    fn test_generator_hooks() {
+        use std::sync::{Arc, Mutex};
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut gen = Generator::with_options(GeneratorOptions {
+            node_id: Some(vec![7]),
+            ..Default::default()
+        }).unwrap();
+        for tag in 0..2 {
+            let seen = seen.clone();
+            gen.add_hook(move |id: &KSUID, ctx: &GenerationContext| {
+                assert_eq!(ctx.options.node_id, Some(vec![7]));
+                seen.lock().unwrap().push((tag, *id, ctx.batch_index, ctx.batch_size));
+            });
+        }
+        let single = gen.next_id();
+        let batch = gen.try_next_batch(2).unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![
+            (0, single, 0, 1), (1, single, 0, 1),
+            (0, batch[0], 0, 2), (1, batch[0], 0, 2),
+            (0, batch[1], 1, 2), (1, batch[1], 1, 2),
+        ]);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_generator_metrics() {
+        use metrics;
+        use metrics_crate::with_local_recorder;
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+        use metrics_util::MetricKind;
+        use std::collections::HashMap;
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        with_local_recorder(&recorder, || {
+            let mut gen = Generator::with_options(GeneratorOptions {
+                counter: true,
+                max_per_second: Some(8),
+                ..Default::default()
+            }).unwrap();
+            gen.next_id();
+            gen.next_batch(5);
+            assert!(gen.try_next_batch(5).is_err());
+            gen.counter = u16::MAX;
+            gen.next_id();
+        });
+        let mut recorded: HashMap<_, _> = snapshotter.snapshot().into_vec().into_iter()
+            .map(|(key, _, _, value)| ((key.kind(), key.key().name().to_owned()), value))
+            .collect();
+        let mut take = |kind, name: &str| recorded.remove(&(kind, name.to_owned())).unwrap();
+        assert_eq!(take(MetricKind::Counter, metrics::IDS_ISSUED), DebugValue::Counter(7));
+        assert_eq!(take(MetricKind::Counter, metrics::RATE_LIMITED), DebugValue::Counter(1));
+        assert_eq!(take(MetricKind::Counter, metrics::MONOTONIC_ADJUSTMENTS), DebugValue::Counter(1));
+        match take(MetricKind::Histogram, metrics::BATCH_SIZE) {
+            DebugValue::Histogram(sizes) => assert_eq!(sizes.len(), 2),
+            other => panic!("unexpected {:?}", other),
+        }
+        match take(MetricKind::Histogram, metrics::RNG_SECONDS) {
+            DebugValue::Histogram(samples) => assert_eq!(samples.len(), 7),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_generator_mix_entropy() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            node_id: Some(vec![1, 2]),
+            mix_entropy: true,
+            ..Default::default()
+        }).unwrap();
+        let ids = gen.next_batch(1000);
+        assert!(ids.iter().all(|id| id.node_id(2) == [1, 2]));
+        let mut payloads: Vec<_> = ids.iter().map(|id| id.payload().to_vec()).collect();
+        payloads.sort();
+        payloads.dedup();
+        assert_eq!(payloads.len(), 1000);
+    }
+
+    #[cfg(feature = "chacha")]
+    #[test]
+    fn test_generator_chacha20() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            chacha20: Some(ChaCha20Options { reseed_interval: 100 }),
+            counter: true,
+            mix_entropy: true,
+            ..Default::default()
+        }).unwrap();
+        let ids = gen.next_batch(1000);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(gen.counter_of(&ids[0]) < COUNTER_START_LIMIT);
+        let plain = Generator::with_options(GeneratorOptions {
+            chacha20: Some(Default::default()),
+            ..Default::default()
+        }).unwrap().next_batch(2);
+        assert_ne!(plain[0].payload(), plain[1].payload());
+    }
+
+    #[test]
+    fn test_generator_hlc() {
+        let options = GeneratorOptions {
+            hlc: true,
+            node_id: Some(vec![9]),
+            ..Default::default()
+        };
+        let mut a = Generator::with_options(options.clone()).unwrap();
+        let mut b = Generator::with_options(options).unwrap();
+        let first = a.next_id();
+        assert_eq!(a.counter_of(&first), 0);
+        assert_eq!(a.node_id_of(&first), &[9]);
+        assert_eq!(first.payload()[0..3], [0, 0, 9]);
+        // Pretend a's clock is ahead: b must still order after whatever it has seen.
+        a.last_time += 30;
+        a.counter = 500;
+        let ahead = a.next_id();
+        b.observe(&ahead).unwrap();
+        let reply = b.next_id();
+        assert!(reply > ahead);
+        assert_eq!(reply.timestamp(), ahead.timestamp());
+        assert_eq!(b.counter_of(&reply), 502);
+        // Observing something older changes nothing.
+        b.observe(&first).unwrap();
+        assert!(b.next_id() > reply);
+    }
+
+    #[test]
+    fn test_generator_observe_bounded() {
+        let mut gen = Generator::with_options(GeneratorOptions { hlc: true, ..Default::default() }).unwrap();
+        let before = gen.next_id();
+        let err = gen.observe(&KSUID([0xff; 20])).unwrap_err();
+        assert_eq!(err.kind(), ::errors::KSUIDErrorKind::TimestampOutOfRange);
+        let after = gen.next_id();
+        assert!(after > before && after.timestamp() < before.timestamp() + Duration::minutes(1));
+    }
+
+    #[test]
+    fn test_generator_counter_exhausts_last_second() {
+        let mut gen = Generator::with_options(GeneratorOptions { counter: true, ..Default::default() }).unwrap();
+        gen.last_time = u32::MAX;
+        gen.counter = u16::MAX;
+        assert_eq!(gen.try_next_id().unwrap_err().kind(), ::errors::KSUIDErrorKind::TimestampOutOfRange);
+        assert_eq!((gen.last_time, gen.counter), (u32::MAX, u16::MAX));
+    }
+
+    #[test]
+    fn test_generator_observe_ignored_without_hlc() {
+        let mut gen = Generator::with_options(GeneratorOptions { counter: true, ..Default::default() }).unwrap();
+        gen.observe(&KSUID::from_bytes(&[0xff; 20]).unwrap()).unwrap();
+        assert!(gen.next_id() < KSUID::from_bytes(&[0xff; 20]).unwrap());
+    }
+
+    #[test]
+    fn test_generator_clock_regression() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        let regressed = |policy| {
+            let mut gen = Generator::with_options(GeneratorOptions { clock_regression: policy, ..Default::default() }).unwrap();
+            let before = gen.next_id();
+            // Pretend an earlier id was minted five seconds from now.
+            gen.last_wall += 5;
+            (gen, before)
+        };
+
+        let (mut gen, before) = regressed(ClockRegressionPolicy::Ignore);
+        assert!(gen.next_id().timestamp() < before.timestamp() + Duration::seconds(5));
+
+        let (mut gen, before) = regressed(ClockRegressionPolicy::Hold);
+        let held = gen.next_id();
+        assert_eq!(held.timestamp().timestamp(), before.timestamp().timestamp() + 5);
+
+        let (mut gen, _) = regressed(ClockRegressionPolicy::Error);
+        assert_eq!(gen.try_next_id(), Err(KSUIDError::ClockRegression { seconds: 5 }));
+        assert!(gen.try_next_batch(3).is_err());
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+        let (mut gen, before) = regressed(ClockRegressionPolicy::Callback(Arc::new(move |regression: &ClockRegression| {
+            assert_eq!((regression.previous - regression.now).num_seconds(), 5);
+            counted.fetch_add(1, Ordering::SeqCst);
+        })));
+        assert!(gen.next_id() > before);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_generator_batches_are_atomic() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        let issued = Arc::new(AtomicUsize::new(0));
+        let counter = |gen: &mut Generator| {
+            let counted = issued.clone();
+            gen.add_hook(move |_: &KSUID, _: &GenerationContext| {
+                counted.fetch_add(1, Ordering::SeqCst);
+            });
+        };
+        let mut gen = Generator::with_options(GeneratorOptions {
+            clock_regression: ClockRegressionPolicy::Error,
+            max_per_second: Some(3),
+            ..Default::default()
+        }).unwrap();
+        counter(&mut gen);
+        gen.next_id();
+        let wall = gen.last_wall;
+        gen.last_wall = u32::MAX;
+        assert_eq!(gen.try_next_batch(2).unwrap_err().kind(), ::errors::KSUIDErrorKind::ClockRegression);
+        assert_eq!(issued.load(Ordering::SeqCst), 1);
+        // The refused batch spent no capacity.
+        gen.last_wall = wall;
+        assert_eq!(gen.try_next_batch(2).unwrap().len(), 2);
+        assert_eq!(issued.load(Ordering::SeqCst), 3);
+
+        // Running out of timestamps halfway through a batch issues none of it.
+        let mut gen = Generator::with_options(GeneratorOptions { counter: true, ..Default::default() }).unwrap();
+        counter(&mut gen);
+        gen.last_time = u32::MAX;
+        gen.counter = u16::MAX - 1;
+        assert_eq!(gen.try_next_batch(3).unwrap_err().kind(), ::errors::KSUIDErrorKind::TimestampOutOfRange);
+        assert_eq!((gen.last_time, gen.counter), (u32::MAX, u16::MAX - 1));
+        assert_eq!(issued.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "clock moved backwards")]
+    fn test_generator_clock_regression_error_panics_next_id() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            clock_regression: ClockRegressionPolicy::Error,
+            ..Default::default()
+        }).unwrap();
+        gen.last_wall = u32::MAX;
+        gen.next_id();
+    }
+
+    #[derive(Debug, Default)]
+    struct MemoryStore {
+        saved: Option<GeneratorState>,
+        saves: usize,
+        fail: bool,
+    }
+
+    impl StateStore for MemoryStore {
+        fn load(&mut self) -> ::std::io::Result<Option<GeneratorState>> {
+            Ok(self.saved)
+        }
+
+        fn save(&mut self, state: &GeneratorState) -> ::std::io::Result<()> {
+            if self.fail {
+                return Err(::std::io::Error::other("disk full"));
+            }
+            self.saved = Some(*state);
+            self.saves += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_generator_store() {
+        let options = GeneratorOptions { counter: true, ..Default::default() };
+        let mut gen = Generator::with_store(options.clone(), MemoryStore::default()).unwrap();
+        let first = gen.next_batch(1000);
+        assert_eq!(gen.persisted_time, gen.last_time);
+        // Resume from a state saved a minute ahead of the clock, as after a clock step back.
+        let ahead = GeneratorState { last_time: gen.last_time + 60 };
+        let mut resumed = Generator::with_store(options, MemoryStore { saved: Some(ahead), ..Default::default() }).unwrap();
+        let next = resumed.next_id();
+        assert!(next > first[999]);
+        assert_eq!(resumed.last_time, ahead.last_time + 1);
+        assert_eq!(resumed.persisted_time, ahead.last_time + 1);
+    }
+
+    #[test]
+    fn test_generator_store_failure() {
+        let options = GeneratorOptions { hlc: true, ..Default::default() };
+        let mut gen = Generator::with_store(options, MemoryStore { fail: true, ..Default::default() }).unwrap();
+        assert_eq!(gen.try_next_id().unwrap_err().kind(), ::errors::KSUIDErrorKind::StatePersistence);
+    }
+
+    #[test]
+    fn test_generator_n_sorted() {
+        for options in [GeneratorOptions::default(), GeneratorOptions { counter: true, ..Default::default() }].iter() {
+            let ids = Generator::with_options(options.clone()).unwrap().generate_n_sorted(50_000);
+            assert_eq!(ids.len(), 50_000);
+            assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        }
+        assert!(Generator::new().generate_n_sorted(0).is_empty());
+    }
+
+    #[test]
+    fn test_generator_max_future_skew() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            hlc: true,
+            max_future_skew: Some(Duration::seconds(60)),
+            ..Default::default()
+        }).unwrap();
+        assert!(gen.try_next_id().is_ok());
+        gen.observe(&KSUID::from_parts(Utc::now() + Duration::seconds(30), &[0; 16]).unwrap()).unwrap();
+        assert!(gen.try_next_id().is_ok());
+        // A peer too far ahead is refused without dragging this generator along.
+        let err = gen.observe(&KSUID::from_parts(Utc::now() + Duration::days(3), &[0; 16]).unwrap()).unwrap_err();
+        assert_eq!(err.kind(), ::errors::KSUIDErrorKind::TimestampOutOfRange);
+        assert!(gen.next_id().timestamp() < Utc::now() + Duration::seconds(61));
+        // So is borrowing a second past the limit once the counter runs out.
+        let limit = to_ksuid_time(Utc::now() + Duration::seconds(61));
+        gen.last_time = limit;
+        gen.counter = u16::MAX;
+        assert!(matches!(gen.try_next_id(), Err(KSUIDError::FutureTimestamp { .. })));
+        assert_eq!((gen.last_time, gen.counter), (limit, u16::MAX));
+    }
+
+    #[test]
+    fn test_generator_max_future_skew_holds() {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            clock_regression: ClockRegressionPolicy::Hold,
+            max_future_skew: Some(Duration::seconds(60)),
+            ..Default::default()
+        }).unwrap();
+        gen.next_id();
+        gen.last_wall += 10;
+        assert!(gen.try_next_id().is_ok());
+        gen.last_wall += 3 * 86_400;
+        assert!(matches!(gen.try_next_id(), Err(KSUIDError::FutureTimestamp { .. })));
+    }
+
+    #[test]
+    fn test_generator_unlimited_by_default() {
+        let mut gen = Generator::new();
+        assert_eq!(gen.try_next_batch(100_000).unwrap().len(), 100_000);
+    }
+
+    #[test]
+    fn test_generator_zero_rate_limit() {
+        let err = Generator::with_options(GeneratorOptions {
+            max_per_second: Some(0),
+            ..Default::default()
+        }).unwrap_err();
+        assert_eq!(err.kind(), ::errors::KSUIDErrorKind::InvalidOptions);
+    }
+
+    #[bench]
+    fn bench_generator_counter(b: &mut Bencher) {
+        let mut gen = Generator::with_options(GeneratorOptions {
+            counter: true,
+            ..Default::default()
+        }).unwrap();
+        b.iter(|| gen.next_id());
+    }
+}
@@ -0,0 +1,159 @@
+use byteorder::{BigEndian, ByteOrder};
+use ksuid::{BYTE_LENGTH, HASH_MARKER, KSUID};
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
+
+// Odd multiplier (from FxHash) for folding inputs that aren't a ksuid.
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+/// `HashMap` keyed by `KSUID` using `KsuidHashBuilder`.
+pub type KsuidHashMap<V> = HashMap<KSUID, V, KsuidHashBuilder>;
+
+/// `HashSet` of `KSUID`s using `KsuidHashBuilder`.
+pub type KsuidHashSet = HashSet<KSUID, KsuidHashBuilder>;
+
+/// A `BuildHasher` for `KSUID` keys that skips hashing altogether: the last 8 bytes of a ksuid
+/// payload are already uniformly random, so they are used as the hash directly. Those bytes are
+/// random for every id this crate mints, including from a `Generator` with a node id and
+/// counter, which only claim the leading payload bytes. Keys other than a `KSUID` still work,
+/// through a simple multiplicative fold.
+///
+/// Unlike the default SipHash this offers no protection against chosen keys: only use it where
+/// the ids were minted randomly, not where an attacker can pick them (e.g. `from_parts` on
+/// client supplied payloads), or they can force every key into one bucket.
+/// # Example
+/// ```
+/// use ksuid::{KsuidHashMap, KSUID};
+///
+/// let mut cache: KsuidHashMap<&str> = KsuidHashMap::default();
+/// let uid = KSUID::new();
+/// cache.insert(uid, "value");
+/// assert_eq!(cache.get(&uid), Some(&"value"));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KsuidHashBuilder;
+
+impl BuildHasher for KsuidHashBuilder {
+    type Hasher = KsuidHasher;
+
+    fn build_hasher(&self) -> KsuidHasher {
+        KsuidHasher::default()
+    }
+}
+
+/// The `Hasher` built by `KsuidHashBuilder`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KsuidHasher {
+    hash: u64,
+    // `KSUID`'s `Hash` wrote its marker, so a 20 byte write next is the id itself.
+    ksuid_next: bool,
+}
+
+impl KsuidHasher {
+    fn fold(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+
+    // Fold in a marker that turned out not to precede a ksuid.
+    fn flush_marker(&mut self) {
+        if self.ksuid_next {
+            self.ksuid_next = false;
+            self.fold(HASH_MARKER);
+        }
+    }
+}
+
+impl Hasher for KsuidHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        if self.ksuid_next && bytes.len() == BYTE_LENGTH {
+            self.ksuid_next = false;
+            // Scramble what came before, so ids in a composite key don't cancel out; a lone id
+            // still hashes to its tail unchanged.
+            self.hash = self.hash.rotate_left(5).wrapping_mul(SEED) ^ BigEndian::read_u64(&bytes[BYTE_LENGTH - 8..]);
+            return;
+        }
+        self.flush_marker();
+        for chunk in bytes.chunks(8) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.fold(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.flush_marker();
+        if i == HASH_MARKER {
+            self.ksuid_next = true;
+        } else {
+            self.fold(i);
+        }
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.flush_marker();
+        self.fold(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        let mut hasher = *self;
+        hasher.flush_marker();
+        hasher.hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test::{black_box, Bencher};
+    use super::*;
+    use std::collections::hash_map::RandomState;
+
+    #[test]
+    fn test_hash_uses_payload_tail() {
+        let a = KSUID::from_bytes(&[1; 20]).unwrap();
+        let mut bytes = [1; 20];
+        bytes[..12].copy_from_slice(&[9; 12]);
+        let b = KSUID(bytes);
+        assert_eq!(KsuidHashBuilder.hash_one(a), KsuidHashBuilder.hash_one(b));
+        bytes[19] = 2;
+        assert_ne!(KsuidHashBuilder.hash_one(a), KsuidHashBuilder.hash_one(KSUID(bytes)));
+        // Other key types are hashed too, not collapsed, even when they are 20 bytes long.
+        assert_ne!(KsuidHashBuilder.hash_one("a"), KsuidHashBuilder.hash_one("b"));
+        let mut digest = [1u8; 20];
+        let first = KsuidHashBuilder.hash_one(digest);
+        digest[0] = 2;
+        assert_ne!(KsuidHashBuilder.hash_one(digest), first);
+        assert_ne!(KsuidHashBuilder.hash_one(HASH_MARKER), KsuidHashBuilder.hash_one(0u64));
+    }
+
+    #[test]
+    fn test_hash_tuple_keys() {
+        let (a, b) = (KSUID::new(), KSUID::new());
+        assert_ne!(KsuidHashBuilder.hash_one((a, b)), KsuidHashBuilder.hash_one((b, a)));
+        assert_ne!(KsuidHashBuilder.hash_one((a, a)), KsuidHashBuilder.hash_one((b, b)));
+        assert_ne!(KsuidHashBuilder.hash_one((a, a)), 0);
+        let tail = BigEndian::read_u64(&a.0[BYTE_LENGTH - 8..]);
+        assert_eq!(KsuidHashBuilder.hash_one(a), tail);
+    }
+
+    #[test]
+    fn test_hash_map() {
+        let ids: Vec<KSUID> = (0..10_000).map(|_| KSUID::new()).collect();
+        let map: KsuidHashMap<usize> = ids.iter().cloned().zip(0..).collect();
+        for (i, id) in ids.iter().enumerate() {
+            assert_eq!(map[id], i);
+        }
+    }
+
+    #[bench]
+    fn bench_hash_siphash(b: &mut Bencher) {
+        let builder = RandomState::new();
+        let uid = KSUID::new();
+        b.iter(|| builder.hash_one(black_box(&uid)));
+    }
+
+    #[bench]
+    fn bench_hash_ksuid(b: &mut Bencher) {
+        let uid = KSUID::new();
+        b.iter(|| KsuidHashBuilder.hash_one(black_box(&uid)));
+    }
+}
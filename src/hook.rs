@@ -0,0 +1,77 @@
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use generator::GeneratorOptions;
+use ksuid::KSUID;
+use std::fmt;
+
+/// Details about how an id was minted, passed to every `GenerationHook`. More fields may be
+/// added.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct GenerationContext<'a> {
+    /// The options of the generator that minted the id.
+    pub options: &'a GeneratorOptions,
+    /// The wall clock time the id was minted at. This can differ from the id's own timestamp
+    /// when `timestamp_granularity` rounds it or the counter borrowed the next second.
+    pub issued_at: DateTime<Utc>,
+    /// Position of the id within the call that minted it: always 0 for `next_id`, and counting
+    /// up through `batch_size` for `next_batch`.
+    pub batch_index: usize,
+    /// How many ids the call minted in total, 1 for `next_id`.
+    pub batch_size: usize,
+}
+
+/// Observer invoked by a `Generator` for every id it issues, registered with
+/// `Generator::add_hook`. Hooks run synchronously on the minting thread, so anything slow should
+/// hand off to a channel. Closures taking `(&KSUID, &GenerationContext)` implement it.
+/// # Example
+/// ```
+/// use ksuid::{GenerationContext, Generator, KSUID};
+/// use std::sync::{Arc, Mutex};
+///
+/// let audit = Arc::new(Mutex::new(Vec::new()));
+/// let log = audit.clone();
+/// let mut gen = Generator::new();
+/// gen.add_hook(move |id: &KSUID, _: &GenerationContext| log.lock().unwrap().push(*id));
+/// let issued = gen.next_batch(3);
+/// assert_eq!(*audit.lock().unwrap(), issued);
+/// ```
+pub trait GenerationHook: Send + Sync {
+    /// Called once for each issued id, after it is minted and before it is returned.
+    fn on_issue(&self, id: &KSUID, context: &GenerationContext);
+}
+
+impl<F> GenerationHook for F
+where
+    F: Fn(&KSUID, &GenerationContext) + Send + Sync,
+{
+    fn on_issue(&self, id: &KSUID, context: &GenerationContext) {
+        self(id, context)
+    }
+}
+
+// The hooks registered on a generator, wrapped so the generator can keep deriving `Debug`.
+#[derive(Default)]
+pub(crate) struct Hooks(Vec<Box<dyn GenerationHook>>);
+
+impl Hooks {
+    pub(crate) fn push(&mut self, hook: Box<dyn GenerationHook>) {
+        self.0.push(hook);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn notify(&self, id: &KSUID, context: &GenerationContext) {
+        for hook in &self.0 {
+            hook.on_issue(id, context);
+        }
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{} hook(s)]", self.0.len())
+    }
+}
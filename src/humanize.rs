@@ -0,0 +1,65 @@
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use chrono_humanize::HumanTime;
+use ksuid::KSUID;
+use std::fmt;
+
+/// Display adapter returned by `KSUID::age`, rendering how long ago the id was created, e.g.
+/// `3 hours ago`, relative to the time it is formatted. The alternate flag (`{:#}`) gives the
+/// precise form, e.g. `3 hours, 12 minutes and 5 seconds ago`. Ids with timestamps ahead of the
+/// local clock render as `in 2 minutes`.
+#[derive(Clone, Copy, Debug)]
+pub struct HumanAge(DateTime<Utc>);
+
+impl HumanAge {
+    fn at(&self, now: DateTime<Utc>) -> HumanTime {
+        HumanTime::from(self.0 - now)
+    }
+}
+
+impl fmt::Display for HumanAge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.at(Utc::now()), f)
+    }
+}
+
+/// Human readable ages, for admin tools and dashboards.
+impl KSUID {
+
+    /// Return a display adapter for this id's age.
+    /// # Example
+    /// ```
+    /// extern crate chrono;
+    /// extern crate ksuid;
+    /// use chrono::Duration;
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::from_parts(KSUID::new().timestamp() - Duration::hours(3), &[0; 16]).unwrap();
+    /// assert_eq!(format!("created {}", uid.age()), "created 3 hours ago");
+    /// ```
+    pub fn age(&self) -> HumanAge {
+        HumanAge(self.timestamp())
+    }
+
+    /// Return this id's age as text, e.g. `3 hours ago`.
+    pub fn age_human(&self) -> String {
+        self.age().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_human_age() {
+        let now = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let age = |d: Duration| HumanAge(now - d).at(now);
+        assert_eq!(age(Duration::hours(3)).to_string(), "3 hours ago");
+        assert_eq!(age(Duration::days(2)).to_string(), "2 days ago");
+        assert_eq!(age(Duration::seconds(-120)).to_string(), "in 2 minutes");
+        assert_eq!(format!("{:#}", age(Duration::hours(3) + Duration::minutes(12))), "3 hours and 12 minutes ago");
+        assert_eq!(KSUID::new().age_human(), "now");
+    }
+}
@@ -0,0 +1,219 @@
+use chrono::prelude::Utc;
+use chrono::{DateTime, Duration};
+use errors;
+use ksuid::KSUID;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::future::{self, Future};
+use std::io;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::str;
+use std::sync::Mutex;
+
+/// A client chosen `KSUID` identifying one attempt at a non-repeatable operation, such as a
+/// payment. Retries of the attempt reuse the key, so the server can recognize and refuse them.
+///
+/// The key's own timestamp bounds how long it has to be remembered: a server accepting keys for
+/// a `window` rejects older ones outright, so a store only needs each key until `expires_at`.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate futures;
+/// extern crate ksuid;
+/// use chrono::Duration;
+/// use futures::executor::block_on;
+/// use ksuid::{IdempotencyKey, IdempotencyStore, MemoryIdempotencyStore};
+///
+/// let window = Duration::hours(24);
+/// let store = MemoryIdempotencyStore::new();
+/// let key: IdempotencyKey = IdempotencyKey::new().to_string().parse().unwrap();
+/// key.validate(window, Duration::minutes(1)).unwrap();
+/// assert!(block_on(store.insert_if_new(&key, key.expires_at(window))).unwrap());
+/// // A retry with the same key is a replay.
+/// assert!(!block_on(store.insert_if_new(&key, key.expires_at(window))).unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IdempotencyKey(pub KSUID);
+
+impl IdempotencyKey {
+    /// Mint a fresh key, for clients.
+    pub fn new() -> Self {
+        IdempotencyKey(KSUID::new())
+    }
+
+    /// Return when a key accepted for `window` can be forgotten. Timestamps are truncated to the
+    /// second, so this is a second past the nominal `timestamp() + window`, saturating at the
+    /// latest representable time for huge windows.
+    pub fn expires_at(&self, window: Duration) -> DateTime<Utc> {
+        self.0.timestamp()
+            .checked_add_signed(window)
+            .and_then(|t| t.checked_add_signed(Duration::seconds(1)))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// Check the key is usable now: at most `max_future_skew` ahead of the local clock, failing
+    /// with `KSUIDError::FutureTimestamp`, and not yet past `expires_at(window)`, failing with
+    /// `KSUIDError::StaleTimestamp`.
+    pub fn validate(&self, window: Duration, max_future_skew: Duration) -> Result<(), errors::KSUIDError> {
+        self.validate_at(window, max_future_skew, Utc::now())
+    }
+
+    pub(crate) fn validate_at(&self, window: Duration, max_future_skew: Duration, now: DateTime<Utc>) -> Result<(), errors::KSUIDError> {
+        self.0.validate_not_future_at(max_future_skew, now)?;
+        if self.0.is_older_than_at(window, now) {
+            let past = now - self.expires_at(window);
+            return Err(errors::KSUIDError::StaleTimestamp { seconds_past: past.num_seconds() });
+        }
+        Ok(())
+    }
+}
+
+impl Default for IdempotencyKey {
+    /// A fresh key, unlike `KSUID::default`: every client would share a nil key.
+    fn default() -> Self {
+        IdempotencyKey::new()
+    }
+}
+
+impl Deref for IdempotencyKey {
+    type Target = KSUID;
+
+    fn deref(&self) -> &KSUID {
+        &self.0
+    }
+}
+
+impl From<KSUID> for IdempotencyKey {
+    fn from(id: KSUID) -> Self {
+        IdempotencyKey(id)
+    }
+}
+
+impl From<IdempotencyKey> for KSUID {
+    fn from(key: IdempotencyKey) -> Self {
+        key.0
+    }
+}
+
+impl fmt::Display for IdempotencyKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for IdempotencyKey {
+    type Err = errors::KSUIDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        KSUID::from_base62(s).map(IdempotencyKey)
+    }
+}
+
+/// Future returned by `IdempotencyStore::insert_if_new`.
+pub type InsertFuture = Pin<Box<dyn Future<Output = io::Result<bool>> + Send>>;
+
+/// Remembers which `IdempotencyKey`s have been used. Shared between requests, so it takes
+/// `&self`; implementations backed by a database should make `insert_if_new` a single atomic
+/// operation, e.g. an insert guarded by a unique constraint, so concurrent retries can't both
+/// win.
+pub trait IdempotencyStore: fmt::Debug + Send + Sync {
+    /// Record `key`, resolving to `true` if it hadn't been recorded before and `false` for a
+    /// replay. The key must be remembered at least until `expires_at`; after that it is rejected
+    /// as stale before reaching the store. The future can't borrow from the store, so clone
+    /// whatever connection handle it needs into it.
+    fn insert_if_new(&self, key: &IdempotencyKey, expires_at: DateTime<Utc>) -> InsertFuture;
+}
+
+/// An in-process `IdempotencyStore`, for single instance services and tests. Expired keys are
+/// dropped as new ones arrive.
+#[derive(Debug, Default)]
+pub struct MemoryIdempotencyStore {
+    keys: Mutex<MemoryKeys>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryKeys {
+    seen: HashSet<IdempotencyKey>,
+    by_expiry: BTreeSet<(DateTime<Utc>, IdempotencyKey)>,
+}
+
+impl MemoryIdempotencyStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        MemoryIdempotencyStore::default()
+    }
+
+    /// Return the number of keys remembered.
+    pub fn len(&self) -> usize {
+        self.keys.lock().unwrap().seen.len()
+    }
+
+    /// Whether no keys are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert_at(&self, key: &IdempotencyKey, expires_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        let mut keys = self.keys.lock().unwrap();
+        while let Some(&(expiry, expired)) = keys.by_expiry.iter().next() {
+            if expiry > now {
+                break;
+            }
+            keys.by_expiry.remove(&(expiry, expired));
+            keys.seen.remove(&expired);
+        }
+        if !keys.seen.insert(*key) {
+            return false;
+        }
+        keys.by_expiry.insert((expires_at, *key));
+        true
+    }
+}
+
+impl IdempotencyStore for MemoryIdempotencyStore {
+    fn insert_if_new(&self, key: &IdempotencyKey, expires_at: DateTime<Utc>) -> InsertFuture {
+        Box::pin(future::ready(Ok(self.insert_at(key, expires_at, Utc::now()))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use errors::KSUIDErrorKind;
+
+    fn key_at(t: DateTime<Utc>, fill: u8) -> IdempotencyKey {
+        IdempotencyKey(KSUID::from_parts(t, &[fill; 16]).unwrap())
+    }
+
+    #[test]
+    fn test_idempotency_key_validate() {
+        let now = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let window = Duration::minutes(10);
+        let skew = Duration::seconds(30);
+        let key = key_at(now - window, 1);
+        assert_eq!(key.expires_at(window), now + Duration::seconds(1));
+        assert!(key.validate_at(window, skew, now).is_ok());
+        let err = key.validate_at(window, skew, now + Duration::seconds(5)).unwrap_err();
+        assert_eq!(err, errors::KSUIDError::StaleTimestamp { seconds_past: 4 });
+        assert_eq!(err.kind(), KSUIDErrorKind::TimestampOutOfRange);
+        assert!(key_at(now + skew, 1).validate_at(window, skew, now).is_ok());
+        assert!(key_at(now + skew + Duration::seconds(1), 1).validate_at(window, skew, now).is_err());
+        assert!(key.validate_at(Duration::MAX, Duration::MAX, now).is_ok());
+        assert_eq!(key.expires_at(Duration::MAX), DateTime::<Utc>::MAX_UTC);
+    }
+
+    #[test]
+    fn test_memory_store_expires_keys() {
+        let store = MemoryIdempotencyStore::new();
+        let now = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let (a, b) = (key_at(now, 1), key_at(now, 2));
+        assert!(store.insert_at(&a, now + Duration::seconds(10), now));
+        assert!(!store.insert_at(&a, now + Duration::seconds(10), now + Duration::seconds(9)));
+        assert!(store.insert_at(&b, now + Duration::seconds(20), now + Duration::seconds(9)));
+        // Inserting past a's expiry forgets it.
+        assert!(store.insert_at(&key_at(now, 3), now + Duration::seconds(30), now + Duration::seconds(10)));
+        assert_eq!(store.len(), 2);
+        assert!(store.insert_at(&a, now + Duration::seconds(40), now + Duration::seconds(11)));
+    }
+}
@@ -0,0 +1,283 @@
+use chrono::Duration;
+use http::header::HeaderName;
+use http::{Request, Response, StatusCode};
+use idempotency::{IdempotencyKey, IdempotencyStore, InsertFuture};
+use ksuid::KSUID;
+use std::fmt;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+static IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// Layer enforcing idempotency keys. Each request must carry a KSUID `IdempotencyKey` in the
+/// `Idempotency-Key` header that is no older than the window and not from the future; the first
+/// request with a key goes through, with the key in its extensions, and later ones are refused.
+///
+/// Requests are answered without reaching the inner service with:
+/// - `400 Bad Request` for a missing, malformed, stale or future dated key,
+/// - `409 Conflict` for a key the store has already seen,
+/// - `503 Service Unavailable` if the store fails.
+///
+/// A key is spent as soon as the store accepts it, whatever the inner service then does with the
+/// request. The inner service is only called once the store has answered, so it must be `Clone`,
+/// as with most tower middleware that waits before forwarding.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::Duration;
+/// use ksuid::{IdempotencyLayer, MemoryIdempotencyStore};
+///
+/// // With tower's ServiceBuilder:
+/// // ServiceBuilder::new().layer(layer).service(payments)
+/// // and in a handler: `request.extensions().get::<IdempotencyKey>()`.
+/// let layer = IdempotencyLayer::new(MemoryIdempotencyStore::new(), Duration::hours(24))
+///     .max_future_skew(Duration::seconds(10));
+/// ```
+#[derive(Clone)]
+pub struct IdempotencyLayer {
+    store: Arc<dyn IdempotencyStore>,
+    window: Duration,
+    max_future_skew: Duration,
+    header: HeaderName,
+}
+
+impl IdempotencyLayer {
+    /// Accept keys up to `window` old, remembering them in `store`. Keys may be up to a minute
+    /// ahead of the local clock, to allow for client clock skew.
+    pub fn new<S: IdempotencyStore + 'static>(store: S, window: Duration) -> Self {
+        IdempotencyLayer {
+            store: Arc::new(store),
+            window,
+            max_future_skew: Duration::minutes(1),
+            header: IDEMPOTENCY_KEY.clone(),
+        }
+    }
+
+    /// Read the key from `header` instead of `Idempotency-Key`.
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Allow keys up to `skew` ahead of the local clock.
+    pub fn max_future_skew(mut self, skew: Duration) -> Self {
+        self.max_future_skew = skew;
+        self
+    }
+
+    // The key `request` carries, or the status to refuse it with if it has no usable one.
+    fn key_of<B>(&self, request: &Request<B>) -> Result<IdempotencyKey, StatusCode> {
+        let key = match request.headers().get(&self.header).map(|v| KSUID::from_base62_bytes(v.as_bytes())) {
+            Some(Ok(id)) => IdempotencyKey(id),
+            _ => return Err(StatusCode::BAD_REQUEST),
+        };
+        key.validate(self.window, self.max_future_skew).map_err(|_| StatusCode::BAD_REQUEST)?;
+        Ok(key)
+    }
+}
+
+impl fmt::Debug for IdempotencyLayer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IdempotencyLayer")
+            .field("store", &self.store)
+            .field("window", &self.window)
+            .field("max_future_skew", &self.max_future_skew)
+            .field("header", &self.header)
+            .finish()
+    }
+}
+
+impl<S> Layer<S> for IdempotencyLayer {
+    type Service = Idempotency<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Idempotency { layer: self.clone(), inner }
+    }
+}
+
+/// Service returned by `IdempotencyLayer`.
+#[derive(Clone, Debug)]
+pub struct Idempotency<S> {
+    layer: IdempotencyLayer,
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for Idempotency<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone,
+    ResBody: Default,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = IdempotencyFuture<S, ReqBody>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let key = match self.layer.key_of(&request) {
+            Ok(key) => key,
+            Err(status) => return IdempotencyFuture(State::Refused(Some(refusal(status)))),
+        };
+        let insert = self.layer.store.insert_if_new(&key, key.expires_at(self.layer.window));
+        request.extensions_mut().insert(key);
+        // Take the service that was polled ready, leaving a clone for the next request.
+        let clone = self.inner.clone();
+        let inner = mem::replace(&mut self.inner, clone);
+        IdempotencyFuture(State::Inserting(insert, Some(Box::new((inner, request)))))
+    }
+}
+
+fn refusal<B: Default>(status: StatusCode) -> Response<B> {
+    let mut response = Response::new(B::default());
+    *response.status_mut() = status;
+    response
+}
+
+/// Future returned by `Idempotency`: waits for the store, then runs the inner service or
+/// answers with a refusal.
+pub struct IdempotencyFuture<S: Service<Request<R>>, R>(State<S, R>);
+
+enum State<S: Service<Request<R>>, R> {
+    Inserting(InsertFuture, Option<Box<(S, Request<R>)>>),
+    Inner(Pin<Box<S::Future>>),
+    Refused(Option<S::Response>),
+}
+
+// Nothing is pinned in place: the futures are boxed, and the service and request are only moved.
+impl<S: Service<Request<R>>, R> Unpin for IdempotencyFuture<S, R> {}
+
+impl<S: Service<Request<R>>, R> fmt::Debug for IdempotencyFuture<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = match self.0 {
+            State::Inserting(..) => "Inserting",
+            State::Inner(_) => "Inner",
+            State::Refused(_) => "Refused",
+        };
+        f.debug_tuple("IdempotencyFuture").field(&state).finish()
+    }
+}
+
+impl<S, R, B> Future for IdempotencyFuture<S, R>
+where
+    S: Service<Request<R>, Response = Response<B>>,
+    B: Default,
+{
+    type Output = Result<Response<B>, S::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let state = &mut self.get_mut().0;
+        loop {
+            let next = match *state {
+                State::Inserting(ref mut insert, ref mut pending) => match ready!(insert.as_mut().poll(cx)) {
+                    Ok(true) => {
+                        let (mut inner, request) = *pending.take().expect("polled after completion");
+                        State::Inner(Box::pin(inner.call(request)))
+                    }
+                    Ok(false) => State::Refused(Some(refusal(StatusCode::CONFLICT))),
+                    Err(_) => State::Refused(Some(refusal(StatusCode::SERVICE_UNAVAILABLE))),
+                },
+                State::Inner(ref mut future) => return future.as_mut().poll(cx),
+                State::Refused(ref mut response) => return Poll::Ready(Ok(response.take().expect("polled after completion"))),
+            };
+            *state = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::prelude::Utc;
+    use idempotency::MemoryIdempotencyStore;
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+    use std::io;
+    use std::task::Waker;
+
+    // Answers 200, echoing the idempotency key it was handed in the response extensions.
+    #[derive(Clone)]
+    struct Ok200;
+
+    impl Service<Request<()>> for Ok200 {
+        type Response = Response<()>;
+        type Error = Infallible;
+        type Future = Ready<Result<Response<()>, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<()>) -> Self::Future {
+            let mut response = Response::new(());
+            if let Some(key) = request.extensions().get::<IdempotencyKey>() {
+                response.extensions_mut().insert(*key);
+            }
+            ready(Ok(response))
+        }
+    }
+
+    fn send<S>(service: &mut S, header: &str, key: Option<String>) -> Response<()>
+    where
+        S: Service<Request<()>, Response = Response<()>, Error = Infallible>,
+    {
+        let mut request = Request::builder();
+        if let Some(key) = key {
+            request = request.header(header, key);
+        }
+        let mut future = Box::pin(service.call(request.body(()).unwrap()));
+        match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(response) => response.unwrap(),
+            Poll::Pending => panic!("future should be ready"),
+        }
+    }
+
+    #[test]
+    fn test_rejects_replays() {
+        let mut service = IdempotencyLayer::new(MemoryIdempotencyStore::new(), Duration::hours(1)).layer(Ok200);
+        let key = IdempotencyKey::new();
+        let response = send(&mut service, "idempotency-key", Some(key.to_string()));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.extensions().get::<IdempotencyKey>(), Some(&key));
+        assert_eq!(send(&mut service, "idempotency-key", Some(key.to_string())).status(), StatusCode::CONFLICT);
+        assert_eq!(send(&mut service, "idempotency-key", Some(IdempotencyKey::new().to_string())).status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_rejects_invalid_keys() {
+        let layer = IdempotencyLayer::new(MemoryIdempotencyStore::new(), Duration::hours(1)).header(HeaderName::from_static("x-key"));
+        let mut service = layer.layer(Ok200);
+        let stale = KSUID::from_parts(Utc::now() - Duration::hours(2), &[1; 16]).unwrap();
+        let future = KSUID::from_parts(Utc::now() + Duration::hours(2), &[1; 16]).unwrap();
+        for key in [None, Some("not-a-ksuid".to_owned()), Some(stale.to_string()), Some(future.to_string())] {
+            assert_eq!(send(&mut service, "x-key", key).status(), StatusCode::BAD_REQUEST);
+        }
+        assert_eq!(send(&mut service, "x-key", Some(IdempotencyKey::new().to_string())).status(), StatusCode::OK);
+        // The default header is ignored once another is configured.
+        assert_eq!(send(&mut service, "idempotency-key", Some(IdempotencyKey::new().to_string())).status(), StatusCode::BAD_REQUEST);
+    }
+
+    // Fails every insert, as a store whose database is down would.
+    #[derive(Debug)]
+    struct Unavailable;
+
+    impl IdempotencyStore for Unavailable {
+        fn insert_if_new(&self, _key: &IdempotencyKey, _expires_at: ::chrono::DateTime<Utc>) -> InsertFuture {
+            Box::pin(ready(Err(io::Error::other("connection refused"))))
+        }
+    }
+
+    #[test]
+    fn test_store_errors_refuse_requests() {
+        let mut service = IdempotencyLayer::new(Unavailable, Duration::hours(1)).layer(Ok200);
+        let response = send(&mut service, "idempotency-key", Some(IdempotencyKey::new().to_string()));
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,179 @@
+use ksuid::KSUID;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// A set of disjoint, half-open `KSUID` ranges, e.g. for tracking which time spans have been
+/// processed. Overlapping and adjacent ranges are merged on insert, so the set is always stored
+/// in its minimal form.
+/// # Example
+/// ```
+/// use ksuid::{KSUID, KsuidIntervalSet};
+///
+/// let ids: Vec<KSUID> = (1..=4).map(|i| KSUID::from_bytes(&[i; 20]).unwrap()).collect();
+/// let mut done = KsuidIntervalSet::new();
+/// done.insert(ids[0]..ids[1]);
+/// done.insert(ids[2]..ids[3]);
+/// assert!(done.contains(&ids[0]));
+/// assert!(!done.contains(&ids[1]));
+/// assert_eq!(done.gaps(ids[0]..ids[3]), vec![ids[1]..ids[2]]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KsuidIntervalSet {
+    // start -> end of every range; ranges never overlap or touch.
+    ranges: BTreeMap<KSUID, KSUID>,
+}
+
+impl KsuidIntervalSet {
+
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a range to the set, merging it with any ranges it overlaps or touches. Empty ranges
+    /// are ignored.
+    pub fn insert(&mut self, range: Range<KSUID>) {
+        if range.start >= range.end {
+            return;
+        }
+        let mut start = range.start;
+        let mut end = range.end;
+        // A range starting at or before ours may reach into it.
+        if let Some((&s, &e)) = self.ranges.range(..=start).next_back() {
+            if e >= start {
+                start = s;
+                end = end.max(e);
+            }
+        }
+        // Everything starting inside [start, end] gets absorbed.
+        let absorbed: Vec<(KSUID, KSUID)> = self.ranges.range(start..=end).map(|(&s, &e)| (s, e)).collect();
+        for (s, e) in absorbed {
+            end = end.max(e);
+            self.ranges.remove(&s);
+        }
+        self.ranges.insert(start, end);
+    }
+
+    /// Return the union of this set and `other`.
+    pub fn union(&self, other: &KsuidIntervalSet) -> KsuidIntervalSet {
+        let mut out = self.clone();
+        for range in other.iter() {
+            out.insert(range);
+        }
+        out
+    }
+
+    /// Check whether `id` falls inside any range of the set.
+    pub fn contains(&self, id: &KSUID) -> bool {
+        self.ranges.range(..=*id).next_back().is_some_and(|(_, end)| id < end)
+    }
+
+    /// Return the sub-ranges of `within` not covered by the set, in order.
+    pub fn gaps(&self, within: Range<KSUID>) -> Vec<Range<KSUID>> {
+        let mut gaps = Vec::new();
+        let mut cursor = within.start;
+        for range in self.iter() {
+            if cursor >= within.end {
+                break;
+            }
+            if range.end <= cursor {
+                continue;
+            }
+            if range.start > cursor {
+                gaps.push(cursor..range.start.min(within.end));
+            }
+            cursor = cursor.max(range.end);
+        }
+        if cursor < within.end {
+            gaps.push(cursor..within.end);
+        }
+        gaps
+    }
+
+    /// Iterate over the ranges of the set in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<KSUID>> + '_ {
+        self.ranges.iter().map(|(&start, &end)| start..end)
+    }
+
+    /// Return the number of disjoint ranges in the set.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Check whether the set covers nothing.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+impl Extend<Range<KSUID>> for KsuidIntervalSet {
+    fn extend<I: IntoIterator<Item = Range<KSUID>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand;
+    use rand::Rng;
+
+    fn id(n: u8) -> KSUID {
+        let mut bytes = [0u8; 20];
+        bytes[19] = n;
+        KSUID(bytes)
+    }
+
+    #[test]
+    fn test_insert_merges() {
+        let mut set = KsuidIntervalSet::new();
+        set.insert(id(1)..id(3));
+        set.insert(id(5)..id(7));
+        assert_eq!(set.len(), 2);
+        set.insert(id(3)..id(5));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![id(1)..id(7)]);
+        set.insert(id(0)..id(10));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![id(0)..id(10)]);
+        set.insert(id(4)..id(4));
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_union_and_gaps() {
+        let mut a = KsuidIntervalSet::new();
+        a.insert(id(2)..id(4));
+        let mut b = KsuidIntervalSet::new();
+        b.insert(id(6)..id(8));
+        let u = a.union(&b);
+        assert_eq!(u.gaps(id(0)..id(10)), vec![id(0)..id(2), id(4)..id(6), id(8)..id(10)]);
+        assert_eq!(u.gaps(id(3)..id(7)), vec![id(4)..id(6)]);
+        assert!(u.gaps(id(2)..id(4)).is_empty());
+    }
+
+    // Compare against a plain bitmap over a small domain.
+    #[test]
+    fn test_against_model() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let mut set = KsuidIntervalSet::new();
+            let mut model = [false; 64];
+            for _ in 0..rng.gen_range(0, 10) {
+                let a = rng.gen_range(0, 64u8);
+                let b = rng.gen_range(0, 64u8);
+                set.insert(id(a)..id(b));
+                for slot in model.iter_mut().take(b as usize).skip(a as usize) {
+                    *slot = true;
+                }
+            }
+            for n in 0..64u8 {
+                assert_eq!(set.contains(&id(n)), model[n as usize]);
+                let in_gap = set.gaps(id(0)..id(64)).iter().any(|g| g.start <= id(n) && id(n) < g.end);
+                assert_eq!(in_gap, !model[n as usize]);
+            }
+            let ranges: Vec<_> = set.iter().collect();
+            assert!(ranges.windows(2).all(|w| w[0].end < w[1].start));
+        }
+    }
+}
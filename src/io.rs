@@ -0,0 +1,98 @@
+use failure::Fail;
+use ksuid::KSUID;
+use std::io;
+
+// Width of the base62 text form, which the text readers consume exactly.
+pub(crate) const ENCODED_LENGTH: usize = 27;
+
+pub(crate) fn invalid_data<E: Fail>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.compat())
+}
+
+/// Parse the fixed width base62 text form read off a stream.
+pub(crate) fn parse_text(text: &[u8; ENCODED_LENGTH]) -> io::Result<KSUID> {
+    KSUID::from_base62_bytes(text).map_err(invalid_data)
+}
+
+/// Extends `Read` with methods for reading `KSUID`s, in the spirit of byteorder's
+/// `ReadBytesExt`.
+/// # Example
+/// ```
+/// use ksuid::{KSUID, ReadKsuidExt, WriteKsuidExt};
+///
+/// let uid = KSUID::new();
+/// let mut buf = Vec::new();
+/// buf.write_ksuid(&uid).unwrap();
+/// buf.write_ksuid_base62(&uid).unwrap();
+///
+/// let mut reader = buf.as_slice();
+/// assert_eq!(reader.read_ksuid().unwrap(), uid);
+/// assert_eq!(reader.read_ksuid_base62().unwrap(), uid);
+/// ```
+pub trait ReadKsuidExt: io::Read {
+    /// Read exactly 20 raw bytes as a `KSUID`.
+    fn read_ksuid(&mut self) -> io::Result<KSUID> {
+        let mut bytes = [0u8; 20];
+        self.read_exact(&mut bytes)?;
+        Ok(KSUID(bytes))
+    }
+
+    /// Read exactly 27 bytes of base62 text and parse them. Malformed text is reported as
+    /// `io::ErrorKind::InvalidData`.
+    fn read_ksuid_base62(&mut self) -> io::Result<KSUID> {
+        let mut text = [0u8; ENCODED_LENGTH];
+        self.read_exact(&mut text)?;
+        parse_text(&text)
+    }
+}
+
+impl<R: io::Read + ?Sized> ReadKsuidExt for R {}
+
+/// Extends `Write` with methods for writing `KSUID`s. See `ReadKsuidExt`.
+pub trait WriteKsuidExt: io::Write {
+    /// Write the 20 raw bytes of a `KSUID`.
+    fn write_ksuid(&mut self, id: &KSUID) -> io::Result<()> {
+        self.write_all(id.as_bytes())
+    }
+
+    /// Write the 27 byte base62 text form of a `KSUID`.
+    fn write_ksuid_base62(&mut self, id: &KSUID) -> io::Result<()> {
+        self.write_all(id.to_base62().as_bytes())
+    }
+}
+
+impl<W: io::Write + ?Sized> WriteKsuidExt for W {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_short() {
+        let mut reader: &[u8] = &[0u8; 19];
+        assert_eq!(reader.read_ksuid().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        let mut reader: &[u8] = b"0ujsszwN8NRY24Ya";
+        assert_eq!(reader.read_ksuid_base62().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_read_invalid_text() {
+        let mut reader: &[u8] = &[0xff; 27];
+        assert_eq!(reader.read_ksuid_base62().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let mut reader: &[u8] = b"zzzzzzzzzzzzzzzzzzzzzzzzzzz";
+        assert_eq!(reader.read_ksuid_base62().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_stream_of_ids() {
+        let ids: Vec<KSUID> = (0..10).map(|_| KSUID::new()).collect();
+        let mut buf = Vec::new();
+        for id in &ids {
+            buf.write_ksuid(id).unwrap();
+        }
+        assert_eq!(buf.len(), 200);
+        let mut reader = buf.as_slice();
+        let read: Vec<KSUID> = (0..10).map(|_| reader.read_ksuid().unwrap()).collect();
+        assert_eq!(read, ids);
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::prelude::Utc;
+use chrono::{DateTime, Duration};
+use ksuid::KSUID;
+use serde_crate::de::{self, Deserialize, Deserializer, Visitor};
+use serde_crate::ser::{Serialize, Serializer};
+use std::fmt;
+use std::ops::Deref;
+
+/// A KSUID used as a JWT `jti` (token id) claim. It serializes as the base62 string and is
+/// validated while the claims are deserialized, so with `jsonwebtoken` (or any serde based JWT
+/// library) a token whose `jti` isn't a valid KSUID fails to decode.
+///
+/// The id's timestamp doubles as the issue time, which bounds how long a revoked id must be
+/// remembered: once `is_older_than` the token lifetime, the token has expired anyway.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// extern crate serde;
+/// extern crate serde_json;
+/// use chrono::Duration;
+/// use ksuid::Jti;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Claims {
+///     sub: String,
+///     exp: i64,
+///     jti: Jti,
+/// }
+///
+/// # fn main() {
+/// let jti = Jti::new();
+/// let claims = Claims { sub: "user-1".to_owned(), exp: 0, jti };
+/// let json = serde_json::to_string(&claims).unwrap();
+/// let decoded: Claims = serde_json::from_str(&json).unwrap();
+/// assert_eq!(decoded.jti, jti);
+/// assert!(!decoded.jti.is_older_than(Duration::minutes(15)));
+/// assert!(serde_json::from_str::<Claims>(r#"{"sub":"u","exp":0,"jti":"1234"}"#).is_err());
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Jti(pub KSUID);
+
+impl Jti {
+    /// Mint a fresh token id.
+    pub fn new() -> Self {
+        Jti(KSUID::new())
+    }
+
+    /// When the token id was minted, to the second.
+    pub fn issued_at(&self) -> DateTime<Utc> {
+        self.0.timestamp()
+    }
+
+    /// Whether more than `window` has passed since the id was minted, i.e. whether a token with
+    /// that lifetime has certainly expired and its id can leave a revocation list. See
+    /// `KSUID::is_older_than`.
+    pub fn is_older_than(&self, window: Duration) -> bool {
+        self.0.is_older_than(window)
+    }
+}
+
+impl Default for Jti {
+    /// A fresh id, unlike `KSUID::default`: a nil `jti` is never what a token issuer wants.
+    fn default() -> Self {
+        Jti::new()
+    }
+}
+
+impl Deref for Jti {
+    type Target = KSUID;
+
+    fn deref(&self) -> &KSUID {
+        &self.0
+    }
+}
+
+impl From<KSUID> for Jti {
+    fn from(id: KSUID) -> Self {
+        Jti(id)
+    }
+}
+
+impl fmt::Display for Jti {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Serialize for Jti {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_base62())
+    }
+}
+
+impl<'de> Deserialize<'de> for Jti {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(JtiVisitor)
+    }
+}
+
+struct JtiVisitor;
+
+impl<'de> Visitor<'de> for JtiVisitor {
+    type Value = Jti;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a base62 ksuid jti")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Jti, E> {
+        KSUID::from_base62(v).map(Jti).map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn test_jti_serde() {
+        let jti = Jti::new();
+        assert_eq!(serde_json::to_value(jti).unwrap(), json!(jti.to_base62()));
+        assert_eq!(serde_json::from_value::<Jti>(json!(jti.to_string())).unwrap(), jti);
+        let err = serde_json::from_value::<Jti>(json!("s3cretTokenThatIsNotAKsuid!")).unwrap_err();
+        assert!(!err.to_string().contains("s3cret"));
+        assert!(serde_json::from_value::<Jti>(json!(42)).is_err());
+    }
+
+    #[test]
+    fn test_jti_is_older_than() {
+        let issued = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        assert!(Jti(KSUID::from_parts(issued, &[1; 16]).unwrap()).is_older_than(Duration::minutes(15)));
+        assert!(!Jti::new().is_older_than(Duration::seconds(5)));
+    }
+}
@@ -0,0 +1,65 @@
+use ksuid::KSUID;
+use rdkafka::message::ToBytes;
+
+/// Lets a `KSUID` key Kafka records directly, as its 20 raw bytes, with no per message
+/// allocation: `FutureRecord::to(topic).key(&uid)`. Consumers read the key back with
+/// `KSUID::from_bytes`.
+impl ToBytes for KSUID {
+    fn to_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl KSUID {
+    /// Choose a partition in `0..partition_count` from the id's random payload, using the same
+    /// formula as `shard_of`, so records spread evenly regardless of when they were created.
+    /// Pass it to `FutureRecord::partition` to bypass the client's key hash. Panics if
+    /// `partition_count` isn't positive.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// assert_eq!(uid.kafka_partition(12), uid.shard_of(12) as i32);
+    /// ```
+    pub fn kafka_partition(&self, partition_count: i32) -> i32 {
+        assert!(partition_count > 0, "partition count must be positive");
+        // Below `partition_count`, so it fits back in an i32.
+        self.shard_of(partition_count as u32) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdkafka::producer::BaseRecord;
+
+    #[test]
+    fn test_kafka_key() {
+        let uid = KSUID::new();
+        let record: BaseRecord<KSUID, str> = BaseRecord::to("events").key(&uid).payload("hello");
+        assert_eq!(record.key.map(ToBytes::to_bytes), Some(&uid.0[..]));
+    }
+
+    #[test]
+    fn test_kafka_partition() {
+        for _ in 0..1000 {
+            let partition = KSUID::new().kafka_partition(7);
+            assert!((0..7).contains(&partition));
+        }
+        let max = KSUID([0xff; 20]);
+        assert_eq!(i64::from(max.kafka_partition(i32::MAX)), (u64::MAX % i32::MAX as u64) as i64);
+    }
+
+    #[test]
+    #[should_panic(expected = "partition count must be positive")]
+    fn test_kafka_partition_rejects_negative() {
+        KSUID::new().kafka_partition(-1);
+    }
+
+    #[test]
+    #[should_panic(expected = "partition count must be positive")]
+    fn test_kafka_partition_rejects_zero() {
+        KSUID::new().kafka_partition(0);
+    }
+}
@@ -1,51 +1,241 @@
+#[cfg(feature = "arrayvec")]
+use arrayvec::ArrayString;
 use base62;
 use byteorder::{BigEndian, ByteOrder};
 use chrono::prelude::Utc;
-use chrono::{DateTime, NaiveDateTime};
+use chrono::{DateTime, Duration, Local, TimeZone};
+use entropy;
 use errors;
-use rand;
-use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter;
+use std::str;
+#[cfg(feature = "subtle")]
+use subtle::{Choice, ConstantTimeEq};
 
 
 // Define ksuid constants
-const EPOCH_START: i64 = 1400000000;
-const TIMESTAMP_LENGTH: usize = 4;
+pub(crate) const EPOCH_START: i64 = 1400000000;
+pub(crate) const TIMESTAMP_LENGTH: usize = 4;
 const PAYLOAD_LENGTH: usize = 16;
-const BYTE_LENGTH: usize = TIMESTAMP_LENGTH + PAYLOAD_LENGTH;
+pub(crate) const BYTE_LENGTH: usize = TIMESTAMP_LENGTH + PAYLOAD_LENGTH;
 
 // Length of the base62 encoded string version
 const ENCODED_LENGTH: u64 = 27;
 
 // A string-encoded maximum value for a KSUID
-const MAX_STRING_ENCODED: &str  = "aWgEPTl1tmebfsQzFP4bxwgy80V";
+pub(crate) const MAX_STRING_ENCODED: &str  = "aWgEPTl1tmebfsQzFP4bxwgy80V";
 
-#[derive(Debug, Default, PartialEq)]
+/// A K-Sortable Unique IDentifier: a 4 byte big endian timestamp (seconds since `EPOCH_START`)
+/// followed by a 16 byte random payload.
+///
+/// # Ordering
+/// `Ord` compares the raw bytes, so ids sort by creation second and then by payload. The base62
+/// encoding preserves this order: it is fixed width (27 characters, zero padded) and its alphabet
+/// `0-9A-Za-z` is in ascending ASCII order, so for any two ids `a.cmp(&b)` equals
+/// `a.to_base62().cmp(&b.to_base62())`. This is part of the crate's contract, and makes the string
+/// form safe to use for range scans in stores that order keys by bytes.
+///
+/// # Layout
+/// `KSUID` is `repr(transparent)` over its 20 bytes: a `&[KSUID]` has exactly the layout of the
+/// packed bytes, which is relied on to view mapped files in place.
+#[cfg_attr(not(feature = "redacted-debug"), derive(Debug))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(transparent)]
 pub struct KSUID(pub [u8; BYTE_LENGTH]);
 
 
-fn to_ksuid_time(t: DateTime<Utc>) -> u32 {
+pub(crate) fn to_ksuid_time(t: DateTime<Utc>) -> u32 {
     (t.timestamp() - EPOCH_START) as u32
 }
 
-fn from_ksuid_time(t: u32) -> DateTime<Utc> {
-    DateTime::<Utc>::from_utc(
-        NaiveDateTime::from_timestamp(
-            i64::from(t) + EPOCH_START,
-            0,
-        ),
-        Utc,
-    )
+/// Like `to_ksuid_time`, but rejects times the 32 bit timestamp can't hold instead of wrapping.
+pub(crate) fn checked_ksuid_time(t: DateTime<Utc>) -> Result<u32, errors::KSUIDError> {
+    let unix_seconds = t.timestamp();
+    u32::try_from(unix_seconds - EPOCH_START).map_err(|_| errors::KSUIDError::TimestampOutOfRange { unix_seconds })
+}
+
+// The first whole ksuid second at or after `t`, widened so out of range times still compare
+// correctly against every u32 timestamp.
+pub(crate) fn ceil_ksuid_time(t: DateTime<Utc>) -> i64 {
+    let mut seconds = t.timestamp() - EPOCH_START;
+    if t.timestamp_subsec_nanos() > 0 {
+        seconds += 1;
+    }
+    seconds.clamp(0, i64::from(u32::MAX) + 1)
+}
+
+pub(crate) fn from_ksuid_time(t: u32) -> DateTime<Utc> {
+    // Every u32 offset from the epoch is well within chrono's representable range.
+    DateTime::<Utc>::from_timestamp(i64::from(t) + EPOCH_START, 0).unwrap()
+}
+
+/// Check whether `s` is a valid base62 encoded ksuid: exactly 27 characters from the base62
+/// alphabet, no larger than the maximum ksuid. Nothing is allocated or decoded, so this is cheap
+/// enough for hot validation paths; `KSUID::from_base62(s).is_ok()` gives the same answer.
+/// # Example
+/// ```
+/// assert!(ksuid::is_valid("0ujsszwN8NRY24YaXiTIE2VWDTS"));
+/// assert!(!ksuid::is_valid("0ujsszwN8NRY24YaXiTIE2VWDT"));
+/// assert!(!ksuid::is_valid("0ujsszwN8NRY24YaXiTIE2VWDT!"));
+/// assert!(!ksuid::is_valid("zzzzzzzzzzzzzzzzzzzzzzzzzzz"));
+/// ```
+pub fn is_valid(s: &str) -> bool {
+    // The encoding is fixed width and order preserving, so comparing against the encoded maximum
+    // catches values that would overflow 20 bytes.
+    s.len() == ENCODED_LENGTH as usize
+        && s.bytes().all(|b| b.is_ascii_alphanumeric())
+        && s <= MAX_STRING_ENCODED
+}
+
+/// Round a ksuid timestamp down to a multiple of `granularity`, aligned to the unix epoch so that
+/// e.g. hour granularity lands on UTC hour boundaries. Granularities under a second are a no-op.
+pub(crate) fn truncate_ksuid_time(t: u32, granularity: Duration) -> u32 {
+    let step = granularity.num_seconds();
+    if step <= 1 {
+        return t;
+    }
+    let unix = i64::from(t) + EPOCH_START;
+    let truncated = unix - unix.rem_euclid(step);
+    // Buckets straddling the ksuid epoch clamp to its start.
+    (truncated - EPOCH_START).max(0) as u32
+}
+
+// Written just before a ksuid's bytes, so `KsuidHasher` can recognize them; other hashers just
+// mix in one more word.
+pub(crate) const HASH_MARKER: u64 = 0x6b73_7569_645f_6861;
+
+impl Hash for KSUID {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(HASH_MARKER);
+        state.write(&self.0);
+    }
 }
 
 impl fmt::Display for KSUID {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_base62())
+        // Use `pad` rather than `write!` so width, fill, alignment and precision flags are honored.
+        let encoded = self.to_base62_array();
+        f.pad(str::from_utf8(&encoded).unwrap())
+    }
+}
+
+/// Parses the base62 form, exactly as `KSUID::from_base62`, so ids work with `str::parse` and
+/// anything generic over `FromStr`.
+/// # Example
+/// ```
+/// use ksuid::KSUID;
+///
+/// let uid: KSUID = "0ujsszwN8NRY24YaXiTIE2VWDTS".parse().unwrap();
+/// assert_eq!(uid.to_string(), "0ujsszwN8NRY24YaXiTIE2VWDTS");
+/// ```
+impl str::FromStr for KSUID {
+    type Err = errors::KSUIDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        KSUID::from_base62(s)
+    }
+}
+
+/// The nil id, as returned by `KSUID::nil()`. This is the zero value, not a fresh id: use
+/// `KSUID::new()` for that.
+/// # Example
+/// ```
+/// use ksuid::KSUID;
+///
+/// assert!(KSUID::default().is_nil());
+/// ```
+impl Default for KSUID {
+    fn default() -> Self {
+        KSUID::nil()
     }
 }
 
+// `Option::unwrap` isn't const yet.
+const fn const_unwrap(t: Option<DateTime<Utc>>) -> DateTime<Utc> {
+    match t {
+        Some(t) => t,
+        None => panic!("timestamp out of range"),
+    }
+}
+
+/// Structural constants, for sizing buffers, declaring schemas (`BINARY(20)`, `CHAR(27)`) and
+/// validating configuration without hardcoding numbers.
+impl KSUID {
+    /// Length of the binary form.
+    pub const BYTE_LENGTH: usize = BYTE_LENGTH;
+    /// Length of the big endian timestamp at the start of the binary form.
+    pub const TIMESTAMP_LENGTH: usize = TIMESTAMP_LENGTH;
+    /// Length of the payload following the timestamp.
+    pub const PAYLOAD_LENGTH: usize = PAYLOAD_LENGTH;
+    /// Length of the base62 text form.
+    pub const ENCODED_LENGTH: usize = ENCODED_LENGTH as usize;
+    /// The instant a zero timestamp stands for: 2014-05-13T16:53:20Z.
+    pub const EPOCH: DateTime<Utc> = const_unwrap(DateTime::from_timestamp(EPOCH_START, 0));
+    /// The latest representable timestamp, `u32::MAX` seconds after `EPOCH` (2150-06-19T23:21:35Z).
+    pub const MAX_TIMESTAMP: DateTime<Utc> = const_unwrap(DateTime::from_timestamp(EPOCH_START + u32::MAX as i64, 0));
+}
+
 impl KSUID {
 
+    /// The nil id: all 20 bytes zero, encoded as 27 `'0'`s. It sorts before every other id and
+    /// is never returned by the generators, so it is safe to use as a sentinel for "no id".
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// assert_eq!(KSUID::nil().to_base62(), "000000000000000000000000000");
+    /// assert!(KSUID::nil() < KSUID::new());
+    /// ```
+    pub const fn nil() -> Self {
+        KSUID([0; BYTE_LENGTH])
+    }
+
+    /// Whether this is the nil id.
+    pub fn is_nil(&self) -> bool {
+        self.0 == [0; BYTE_LENGTH]
+    }
+
+    /// Return the id immediately after this one in sort order, treating the 20 bytes as one big
+    /// endian number, or `None` for the largest id. Useful for turning an exclusive bound such
+    /// as "after this id" into an inclusive one.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// assert!(uid.next().unwrap() > uid);
+    /// assert_eq!(uid.next().unwrap().prev(), Some(uid));
+    /// assert_eq!(KSUID::nil().next().unwrap().to_base62(), "000000000000000000000000001");
+    /// ```
+    pub fn next(&self) -> Option<KSUID> {
+        let mut bytes = self.0;
+        for byte in bytes.iter_mut().rev() {
+            let (sum, carry) = byte.overflowing_add(1);
+            *byte = sum;
+            if !carry {
+                return Some(KSUID(bytes));
+            }
+        }
+        None
+    }
+
+    /// Return the id immediately before this one in sort order, or `None` for the nil id.
+    pub fn prev(&self) -> Option<KSUID> {
+        let mut bytes = self.0;
+        for byte in bytes.iter_mut().rev() {
+            let (difference, borrow) = byte.overflowing_sub(1);
+            *byte = difference;
+            if !borrow {
+                return Some(KSUID(bytes));
+            }
+        }
+        None
+    }
+
     /// Create a new random `KSUID` based on the current time and some random data
     /// # Example
     /// ```
@@ -58,12 +248,28 @@ impl KSUID {
     pub fn new() -> Self {
         let time = to_ksuid_time(Utc::now());
         let mut bytes = [0u8; BYTE_LENGTH];
-        rand::thread_rng().fill_bytes(&mut bytes);
+        entropy::fill(&mut bytes[TIMESTAMP_LENGTH..]);
         BigEndian::write_u32(&mut bytes, time);
         KSUID(bytes)
     }
 
-    /// Create a new `KSUID` from it's raw components.
+    /// Return an endless iterator of fresh ids, each from `KSUID::new()`. Ids minted in the same
+    /// second are in random order; use `Generator::iter` with the counter option for strictly
+    /// increasing ids.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let ids: Vec<KSUID> = KSUID::iter().take(3).collect();
+    /// assert_eq!(ids.len(), 3);
+    /// ```
+    pub fn iter() -> impl Iterator<Item = KSUID> {
+        iter::repeat_with(KSUID::new)
+    }
+
+    /// Create a new `KSUID` from it's raw components. Fails if the payload is shorter than 16 bytes,
+    /// or if `ts` is before `EPOCH_START` or past the largest 32 bit offset from it. Bytes past
+    /// the 16th are ignored; use `from_parts_exact` to reject them instead.
     /// # Example
     /// ```
     /// use ksuid::KSUID;
@@ -77,11 +283,90 @@ impl KSUID {
             return Err(errors::KSUIDError::SliceTooSmall{length: payload.len()})
         }
         let mut bytes = [0u8; BYTE_LENGTH];
-        BigEndian::write_u32(&mut bytes, to_ksuid_time(ts));
+        BigEndian::write_u32(&mut bytes, checked_ksuid_time(ts)?);
         bytes[TIMESTAMP_LENGTH..].clone_from_slice(&payload[..PAYLOAD_LENGTH]);
         Ok(KSUID(bytes))
     }
 
+    /// Like `from_parts`, but fails with `KSUIDError::InvalidPayloadLength` unless the payload is
+    /// exactly 16 bytes, so a longer hash or key can't be silently truncated into a collision.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// assert_eq!(KSUID::from_parts_exact(uid.timestamp(), uid.payload()).unwrap(), uid);
+    /// assert!(KSUID::from_parts_exact(uid.timestamp(), &[0; 32]).is_err());
+    /// ```
+    pub fn from_parts_exact(ts: DateTime<Utc>, payload: &[u8]) -> Result<Self, errors::KSUIDError> {
+        if payload.len() != PAYLOAD_LENGTH {
+            return Err(errors::KSUIDError::InvalidPayloadLength { expected: PAYLOAD_LENGTH, actual: payload.len() });
+        }
+        Self::from_parts(ts, payload)
+    }
+
+    /// Like `from_parts`, but accepts payloads of up to 16 bytes and left pads them with zeros,
+    /// so the bytes given end the payload. A big endian integer thus keeps its numeric order, e.g.
+    /// a sequence number of ids sharing a timestamp. Fails with
+    /// `KSUIDError::InvalidPayloadLength` if the payload is longer than 16 bytes.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::from_parts_padded(KSUID::EPOCH, &42u32.to_be_bytes()).unwrap();
+    /// assert_eq!(uid.payload(), &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 42]);
+    /// ```
+    pub fn from_parts_padded(ts: DateTime<Utc>, payload: &[u8]) -> Result<Self, errors::KSUIDError> {
+        if payload.len() > PAYLOAD_LENGTH {
+            return Err(errors::KSUIDError::InvalidPayloadLength { expected: PAYLOAD_LENGTH, actual: payload.len() });
+        }
+        let mut padded = [0u8; PAYLOAD_LENGTH];
+        padded[PAYLOAD_LENGTH - payload.len()..].copy_from_slice(payload);
+        Self::from_parts(ts, &padded)
+    }
+
+    /// Create a `KSUID` stamped with the current time around a caller supplied payload, e.g. one
+    /// derived from a hash or an existing key. The array type makes a short or long payload a
+    /// compile error rather than a runtime one.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::from_payload_now([7; 16]);
+    /// assert_eq!(uid.payload(), &[7; 16]);
+    /// ```
+    pub fn from_payload_now(payload: [u8; PAYLOAD_LENGTH]) -> Self {
+        let mut bytes = [0u8; BYTE_LENGTH];
+        BigEndian::write_u32(&mut bytes, to_ksuid_time(Utc::now()));
+        bytes[TIMESTAMP_LENGTH..].copy_from_slice(&payload);
+        KSUID(bytes)
+    }
+
+    /// Create a deterministic `KSUID` from a namespace and a name, in the spirit of UUIDv5. The
+    /// payload is the first 16 bytes of SHA-256 over the namespace length (as a big endian u64),
+    /// the namespace and then the name, so the same inputs always produce the same payload.
+    /// The timestamp is explicit, typically the creation time of the source record.
+    ///
+    /// Fails with `KSUIDError::TimestampOutOfRange` if `ts` is outside the range a ksuid can
+    /// represent (see `from_parts`).
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// let a = KSUID::from_name(b"orders", b"order-1234", uid.timestamp()).unwrap();
+    /// let b = KSUID::from_name(b"orders", b"order-1234", uid.timestamp()).unwrap();
+    /// assert_eq!(a, b);
+    /// assert_ne!(a, KSUID::from_name(b"invoices", b"order-1234", uid.timestamp()).unwrap());
+    /// ```
+    pub fn from_name(namespace: &[u8], name: &[u8], ts: DateTime<Utc>) -> Result<Self, errors::KSUIDError> {
+        let mut hasher = Sha256::new();
+        hasher.update((namespace.len() as u64).to_be_bytes());
+        hasher.update(namespace);
+        hasher.update(name);
+        Self::from_parts(ts, &hasher.finalize())
+    }
+
     /// Return a ksuid built from a byte slice. The slice could be of arbitary size. The first 20
     /// bytes will be the only ones used. If the slice is too small an error is returned.
     /// # Example
@@ -97,49 +382,403 @@ impl KSUID {
             return Err(errors::KSUIDError::SliceTooSmall{length: bytes.len()})
         }
         let mut arr = [0u8; BYTE_LENGTH];
-        (&mut arr).copy_from_slice(&bytes[..BYTE_LENGTH]);
+        arr.copy_from_slice(&bytes[..BYTE_LENGTH]);
         Ok(KSUID(arr))
     }
 
+    /// Parse a ksuid from its 27 character base62 form. Input of any other length, characters
+    /// outside the base62 alphabet, or values too large for 20 bytes produce an error; no input
+    /// causes a panic.
     pub fn from_base62(string: &str) -> Result<Self, errors::KSUIDError> {
         base62::decode(string).and_then(|bytes| {
             Self::from_bytes(bytes.as_slice())
         })
     }
 
+    /// Parse a ksuid from its base62 form given as raw ASCII bytes, e.g. sliced straight out of an
+    /// HTTP header or a binary protocol buffer. Each byte is checked against the base62 alphabet
+    /// directly, so there is no need to validate UTF-8 or build a `&str` first. Accepts and
+    /// rejects exactly what `from_base62` does.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let header: &[u8] = b"X-Request-Id: 0ujsszwN8NRY24YaXiTIE2VWDTS";
+    /// let uid = KSUID::from_base62_bytes(&header[14..]).unwrap();
+    /// assert_eq!(uid.to_base62(), "0ujsszwN8NRY24YaXiTIE2VWDTS");
+    /// assert!(KSUID::from_base62_bytes(b"\xff\xfe").is_err());
+    /// ```
+    pub fn from_base62_bytes(bytes: &[u8]) -> Result<Self, errors::KSUIDError> {
+        base62::decode_bytes(bytes, ENCODED_LENGTH as usize, BYTE_LENGTH).and_then(|bytes| {
+            Self::from_bytes(bytes.as_slice())
+        })
+    }
+
+    /// Forgiving parser for ids copied out of logs and tickets: trims surrounding whitespace,
+    /// strips the first of `prefixes` that matches (plus any whitespace after it), then parses
+    /// the remainder strictly with `from_base62`.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::parse_lenient("  req-0ujsszwN8NRY24YaXiTIE2VWDTS\n", &["ksuid:", "req-"]).unwrap();
+    /// assert_eq!(uid, KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap());
+    /// ```
+    pub fn parse_lenient(string: &str, prefixes: &[&str]) -> Result<Self, errors::KSUIDError> {
+        let mut trimmed = string.trim();
+        if let Some(rest) = prefixes.iter().find_map(|prefix| trimmed.strip_prefix(prefix)) {
+            trimmed = rest.trim_start();
+        }
+        Self::from_base62(trimmed)
+    }
+
+    /// Parse every string in `inputs`, returning all ids or, if any input is invalid, an error
+    /// listing the index and cause of every failure (not just the first).
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let ids = KSUID::parse_many(vec!["0ujsszwN8NRY24YaXiTIE2VWDTS", "0ujsszgFvbiEr7CDgE3z8MAUPFt"]);
+    /// assert_eq!(ids.unwrap().len(), 2);
+    ///
+    /// let err = KSUID::parse_many(vec!["0ujsszwN8NRY24YaXiTIE2VWDTS", "nope"]).unwrap_err();
+    /// assert_eq!(err.errors[0].0, 1);
+    /// ```
+    pub fn parse_many<'a, I>(inputs: I) -> Result<Vec<Self>, errors::BulkParseError>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut ids = Vec::new();
+        let mut failures = Vec::new();
+        for (index, result) in Self::parse_each(inputs) {
+            match result {
+                Ok(id) => ids.push(id),
+                Err(err) => failures.push((index, err)),
+            }
+        }
+        if failures.is_empty() {
+            Ok(ids)
+        } else {
+            Err(errors::BulkParseError { errors: failures })
+        }
+    }
+
+    /// Lazily parse every string in `inputs`, yielding each result with its index. Use this
+    /// instead of `parse_many` to stream, or to keep the valid ids from a partially bad batch.
+    pub fn parse_each<'a, I>(inputs: I) -> impl Iterator<Item = (usize, Result<Self, errors::KSUIDError>)>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        inputs.into_iter().map(Self::from_base62).enumerate()
+    }
 
     /// Return the timestamp portion of a ksuid as a `time::Timespec` struct
     pub fn timestamp(&self) -> DateTime<Utc> {
         from_ksuid_time(BigEndian::read_u32(&self.0))
     }
 
+    /// Return the timestamp in the time zone `tz`, e.g. a business's reporting zone.
+    /// # Example
+    /// ```
+    /// extern crate chrono;
+    /// extern crate ksuid;
+    /// use chrono::{FixedOffset, Timelike};
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+    /// let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+    /// assert_eq!(uid.timestamp_in(&tokyo).hour(), (uid.timestamp().hour() + 9) % 24);
+    /// assert_eq!(uid.timestamp_in(&tokyo), uid.timestamp());
+    /// ```
+    pub fn timestamp_in<Tz: TimeZone>(&self, tz: &Tz) -> DateTime<Tz> {
+        self.timestamp().with_timezone(tz)
+    }
+
+    /// Return the timestamp in the system's local time zone.
+    pub fn timestamp_local(&self) -> DateTime<Local> {
+        self.timestamp_in(&Local)
+    }
+
+    /// Compare only the timestamps of two ids, ignoring their payloads. Cheaper than comparing
+    /// `timestamp()`s, since nothing is converted.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    /// use std::cmp::Ordering;
+    ///
+    /// let a = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+    /// let b = KSUID::from_parts(a.timestamp(), &[0xff; 16]).unwrap();
+    /// assert_eq!(a.cmp_timestamp(&b), Ordering::Equal);
+    /// assert_eq!(a.cmp_timestamp(&KSUID::new()), Ordering::Less);
+    /// ```
+    pub fn cmp_timestamp(&self, other: &KSUID) -> Ordering {
+        self.0[..TIMESTAMP_LENGTH].cmp(&other.0[..TIMESTAMP_LENGTH])
+    }
+
+    /// Whether more than `ttl` has passed since this id was created, the predicate for cache
+    /// eviction and soft expiry. The timestamp is truncated to the second, so this may answer
+    /// `false` for up to a second longer than `ttl`; it never answers `true` early.
+    /// # Example
+    /// ```
+    /// extern crate chrono;
+    /// extern crate ksuid;
+    /// use chrono::Duration;
+    /// use ksuid::KSUID;
+    ///
+    /// assert!(!KSUID::new().is_older_than(Duration::minutes(5)));
+    /// assert!(KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap().is_older_than(Duration::days(365)));
+    /// ```
+    pub fn is_older_than(&self, ttl: Duration) -> bool {
+        self.is_older_than_at(ttl, Utc::now())
+    }
+
+    // `is_older_than` with the clock pinned down for testing. An id created in second `s` was
+    // created before `s + 1`, so it's certainly older than `ttl` once `now - ttl >= s + 1`. A
+    // `ttl` reaching past the representable dates is never exceeded.
+    pub(crate) fn is_older_than_at(&self, ttl: Duration, now: DateTime<Utc>) -> bool {
+        match now.checked_sub_signed(ttl) {
+            Some(cutoff) => cutoff >= self.timestamp() + Duration::seconds(1),
+            None => false,
+        }
+    }
+
+    /// Check that this id's timestamp is no more than `tolerance` ahead of the local clock,
+    /// failing with `KSUIDError::FutureTimestamp` otherwise. Ids minted on machines with broken
+    /// clocks are otherwise accepted silently and fall outside later time range queries.
+    /// # Example
+    /// ```
+    /// extern crate chrono;
+    /// extern crate ksuid;
+    /// use chrono::{Duration, Utc};
+    /// use ksuid::KSUID;
+    ///
+    /// assert!(KSUID::new().validate_not_future(Duration::seconds(5)).is_ok());
+    /// let skewed = KSUID::from_parts(Utc::now() + Duration::days(2), &[0; 16]).unwrap();
+    /// assert!(skewed.validate_not_future(Duration::minutes(5)).is_err());
+    /// ```
+    pub fn validate_not_future(&self, tolerance: Duration) -> Result<(), errors::KSUIDError> {
+        self.validate_not_future_at(tolerance, Utc::now())
+    }
+
+    pub(crate) fn validate_not_future_at(&self, tolerance: Duration, now: DateTime<Utc>) -> Result<(), errors::KSUIDError> {
+        let ahead = self.timestamp() - now;
+        if ahead > tolerance {
+            return Err(errors::KSUIDError::FutureTimestamp { seconds_ahead: ahead.num_seconds() });
+        }
+        Ok(())
+    }
+
+    /// Whether both ids were created in the same second.
+    pub fn same_second(&self, other: &KSUID) -> bool {
+        self.cmp_timestamp(other) == Ordering::Equal
+    }
+
+    /// Return the time from this id's creation to `other`'s, negative if `other` is older. Only
+    /// whole seconds are recorded, so the result is always a whole number of seconds.
+    /// # Example
+    /// ```
+    /// extern crate chrono;
+    /// extern crate ksuid;
+    /// use chrono::Duration;
+    /// use ksuid::KSUID;
+    ///
+    /// let start = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+    /// let end = KSUID::from_parts(start.timestamp() + Duration::minutes(5), &[0; 16]).unwrap();
+    /// assert_eq!(start.duration_between(&end), Duration::minutes(5));
+    /// assert_eq!(end.duration_between(&start), Duration::minutes(-5));
+    /// assert!(!start.same_second(&end));
+    /// ```
+    pub fn duration_between(&self, other: &KSUID) -> Duration {
+        let from = i64::from(BigEndian::read_u32(&self.0));
+        let to = i64::from(BigEndian::read_u32(&other.0));
+        Duration::seconds(to - from)
+    }
+
+    /// Return a copy of this ksuid with its timestamp rounded down to a multiple of `granularity`
+    /// (aligned to the unix epoch), keeping the payload. Coarser timestamps leak less about when
+    /// an id was created while still sorting roughly by time.
+    /// # Example
+    /// ```
+    /// extern crate chrono;
+    /// extern crate ksuid;
+    /// use chrono::{Duration, Timelike};
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new().truncate_timestamp(Duration::hours(1));
+    /// assert_eq!(uid.timestamp().minute(), 0);
+    /// assert_eq!(uid.timestamp().second(), 0);
+    /// ```
+    pub fn truncate_timestamp(&self, granularity: Duration) -> KSUID {
+        let mut bytes = self.0;
+        let t = truncate_ksuid_time(BigEndian::read_u32(&bytes), granularity);
+        BigEndian::write_u32(&mut bytes, t);
+        KSUID(bytes)
+    }
+
+    /// Return the smallest ksuid in this id's time bucket: the timestamp truncated as with
+    /// `truncate_timestamp` and the payload zeroed. Every id in the same bucket maps to the same
+    /// value, which makes it usable directly as a partition key or range-scan start.
+    /// # Example
+    /// ```
+    /// extern crate chrono;
+    /// extern crate ksuid;
+    /// use chrono::Duration;
+    /// use ksuid::KSUID;
+    ///
+    /// let bucket = KSUID::new().bucket(Duration::hours(1));
+    /// assert_eq!(bucket.payload(), &[0; 16]);
+    /// ```
+    pub fn bucket(&self, granularity: Duration) -> KSUID {
+        let mut bytes = [0u8; BYTE_LENGTH];
+        BigEndian::write_u32(&mut bytes, truncate_ksuid_time(BigEndian::read_u32(&self.0), granularity));
+        KSUID(bytes)
+    }
+
+    /// Return a human readable, lexicographically sortable key for this id's time bucket: the
+    /// bucket start in compact ISO 8601 UTC form, e.g. `20170714T020000Z` for hourly buckets.
+    pub fn bucket_key(&self, granularity: Duration) -> String {
+        self.bucket(granularity).timestamp().format("%Y%m%dT%H%M%SZ").to_string()
+    }
+
     /// Return the random payload portion of the ksuid as a reference to the underlying array
     pub fn payload(&self) -> &[u8] {
         &(&self.0)[TIMESTAMP_LENGTH..]
     }
 
+    /// Return the node identifier embedded by a `Generator` configured with a `node_id` of `len`
+    /// bytes. The length isn't recorded in the id itself, so it must be known out of band.
+    /// Panics if `len` exceeds the payload length.
+    /// # Example
+    /// ```
+    /// use ksuid::{Generator, GeneratorOptions};
+    ///
+    /// let mut gen = Generator::with_options(GeneratorOptions {
+    ///     node_id: Some(b"web7".to_vec()),
+    ///     ..Default::default()
+    /// }).unwrap();
+    /// assert_eq!(gen.next_id().node_id(4), b"web7");
+    /// ```
+    pub fn node_id(&self, len: usize) -> &[u8] {
+        &self.payload()[..len]
+    }
+
+    /// Return a stable shard index in `0..n`, computed as the last 8 payload bytes read as a big
+    /// endian u64, modulo `n`. Only random payload bytes are used (never the timestamp, which would
+    /// make recent shards hot, nor the leading bytes a `Generator` may reserve for node ids or
+    /// counters). The formula is part of the crate's contract, so every service routes alike.
+    /// Panics if `n` is zero.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// assert!(uid.shard_of(16) < 16);
+    /// assert_eq!(uid.shard_of(16), uid.shard_of(16));
+    /// ```
+    pub fn shard_of(&self, n: u32) -> u32 {
+        assert!(n > 0, "shard count must be non-zero");
+        (BigEndian::read_u64(&self.0[BYTE_LENGTH - 8..]) % u64::from(n)) as u32
+    }
+
     /// Encode the underlying bytes as a base62 `String`
     pub fn to_base62(&self) -> String {
         base62::encode(&self.0)
     }
 
+    /// Compare this id against a base62 string without allocating or decoding it. The result is
+    /// the byte-wise string order of this id's encoding versus `s`, which for a valid encoded id
+    /// is the same as comparing the ids themselves (see "Ordering" above). Strings that aren't
+    /// valid ids still get a consistent answer, matching how a store would order them.
+    /// # Example
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+    /// assert_eq!(uid.cmp_base62("0ujsszwN8NRY24YaXiTIE2VWDTS"), Ordering::Equal);
+    /// assert_eq!(uid.cmp_base62("0ujtsYcgvSTl8PAuAdqWYSMnLOv"), Ordering::Less);
+    /// ```
+    pub fn cmp_base62(&self, s: &str) -> Ordering {
+        self.to_base62_array()[..].cmp(s.as_bytes())
+    }
+
+    /// Encode the underlying bytes as base62 into a fixed size array. Every byte is guaranteed to
+    /// be ASCII from the base62 alphabet, so the array can be written straight into a buffer or
+    /// viewed with `str::from_utf8` (which cannot fail) without allocating.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// let encoded = uid.to_base62_array();
+    /// assert_eq!(std::str::from_utf8(&encoded).unwrap(), uid.to_base62());
+    /// ```
+    pub fn to_base62_array(&self) -> [u8; 27] {
+        base62::encode_array(&self.0)
+    }
+
+    /// Encode the underlying bytes as a base62 `ArrayString`, without touching the heap.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// assert_eq!(uid.to_base62_arraystring().as_str(), uid.to_base62());
+    /// ```
+    #[cfg(feature = "arrayvec")]
+    pub fn to_base62_arraystring(&self) -> ArrayString<27> {
+        // The encoder only ever emits base62 ASCII, so this can't fail.
+        ArrayString::from_byte_string(&self.to_base62_array()).unwrap()
+    }
+
     /// Return a reference to the bytes that make up a ksuid.
     pub fn as_bytes(&self) -> &[u8] {
         &(self.0)
     }
 }
 
+/// Constant-time comparison. Prefer `ct_eq` (or `ct_eq_bool`) over `==` whenever a `KSUID` acts as a secret, such
+/// as a password-reset or unsubscribe token looked up from user input; `PartialEq` may return
+/// early on the first differing byte and leak how much of a guess was correct. For ordinary
+/// identifiers `==` is fine and faster.
+#[cfg(feature = "subtle")]
+impl ConstantTimeEq for KSUID {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+#[cfg(feature = "subtle")]
+impl KSUID {
+    /// Compare two ksuids in constant time, as `ConstantTimeEq::ct_eq` converted to a `bool`, for
+    /// callers that don't combine results with `subtle`. See the `ConstantTimeEq` impl for when
+    /// to use this instead of `==`.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let token = KSUID::new();
+    /// let presented = KSUID::from_base62(&token.to_base62()).unwrap();
+    /// assert!(token.ct_eq_bool(&presented));
+    /// ```
+    pub fn ct_eq_bool(&self, other: &KSUID) -> bool {
+        ConstantTimeEq::ct_eq(self, other).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use test::Bencher;
     use super::*;
+    use rand;
+    use rand::Rng;
     use std::iter;
 
     #[test]
     fn test_ksuid_base62() {
         let zero = KSUID::from_bytes(&[0; 20]).unwrap();
         let expected = String::from_utf8(
-            iter::repeat('0' as u8).take(ENCODED_LENGTH as usize).collect()
+            iter::repeat_n(b'0', ENCODED_LENGTH as usize).collect()
         ).unwrap(); 
         assert_eq!(zero.to_base62(), expected);
 
@@ -150,24 +789,348 @@ mod tests {
     }
     #[test]
     fn invalid_from_bytes() {
-        let failed = match KSUID::from_bytes(&[0;2]) {
-            Err(_) => true,
-            Ok(_) => false,
-        };
-        assert!(failed);
+        assert!(KSUID::from_bytes(&[0;2]).is_err());
     }
 
     #[test]
     fn test_parse_golang() {
-        let res = KSUID::from_base62(&"0yEaNH85uGuB4bz7EoWhX228k65");
+        let res = KSUID::from_base62("0yEaNH85uGuB4bz7EoWhX228k65");
         assert!(res.is_ok());
         let uid = res.unwrap();
         println!("timestamp: {}, payload: {:?}", uid.timestamp(), uid.payload());
     }
 
+    #[test]
+    fn test_display_format_flags() {
+        let uid = KSUID::from_base62("0yEaNH85uGuB4bz7EoWhX228k65").unwrap();
+        assert_eq!(format!("{}", uid), "0yEaNH85uGuB4bz7EoWhX228k65");
+        assert_eq!(format!("{:>30}", uid), "   0yEaNH85uGuB4bz7EoWhX228k65");
+        assert_eq!(format!("{:<30}|", uid), "0yEaNH85uGuB4bz7EoWhX228k65   |");
+        assert_eq!(format!("{:*^31}", uid), "**0yEaNH85uGuB4bz7EoWhX228k65**");
+        assert_eq!(format!("{:.6}", uid), "0yEaNH");
+    }
+
+    #[test]
+    fn test_truncate_timestamp() {
+        let uid = KSUID::from_parts(DateTime::from_timestamp(1_500_003_725, 0).unwrap(), &[9; 16]).unwrap();
+        let hourly = uid.truncate_timestamp(Duration::hours(1));
+        assert_eq!(hourly.timestamp().timestamp(), 1_500_001_200);
+        assert_eq!(hourly.payload(), uid.payload());
+        assert_eq!(uid.truncate_timestamp(Duration::milliseconds(10)), uid);
+        // Days before the ksuid epoch clamp to the epoch itself.
+        let first = KSUID::from_parts(from_ksuid_time(5), &[0; 16]).unwrap();
+        assert_eq!(BigEndian::read_u32(&first.truncate_timestamp(Duration::days(1)).0), 0);
+    }
+
+    #[test]
+    fn test_shard_of() {
+        let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+        assert_eq!(uid.shard_of(1), 0);
+        assert_eq!(uid.shard_of(1024), (BigEndian::read_u64(&uid.0[12..]) % 1024) as u32);
+        let mut counts = [0u32; 8];
+        for _ in 0..8000 {
+            counts[KSUID::new().shard_of(8) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| c > 800));
+    }
+
+    #[test]
+    fn test_bucket() {
+        let uid = KSUID::from_parts(DateTime::from_timestamp(1_500_003_725, 0).unwrap(), &[9; 16]).unwrap();
+        let bucket = uid.bucket(Duration::hours(1));
+        assert_eq!(bucket.timestamp().timestamp(), 1_500_001_200);
+        assert_eq!(bucket.payload(), &[0; 16]);
+        assert_eq!(uid.bucket_key(Duration::hours(1)), "20170714T030000Z");
+        assert_eq!(uid.bucket_key(Duration::days(1)), "20170714T000000Z");
+        assert_eq!(uid.bucket_key(Duration::minutes(1)), "20170714T034200Z");
+    }
+
+    #[test]
+    fn test_parse_many() {
+        let uid = KSUID::new().to_base62();
+        let inputs = vec![uid.as_str(), "short", uid.as_str(), ""];
+        let err = KSUID::parse_many(inputs.iter().cloned()).unwrap_err();
+        let indexes: Vec<usize> = err.errors.iter().map(|e| e.0).collect();
+        assert_eq!(indexes, vec![1, 3]);
+        assert!(err.to_string().starts_with("2 ksuid(s) failed to parse, first at index 1"));
+
+        let valid: Vec<KSUID> = KSUID::parse_each(inputs).filter_map(|(_, r)| r.ok()).collect();
+        assert_eq!(valid.len(), 2);
+        assert_eq!(KSUID::parse_many(Vec::new()).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_base62_array() {
+        for _ in 0..100 {
+            let uid = KSUID::new();
+            let encoded = uid.to_base62_array();
+            assert!(encoded.iter().all(u8::is_ascii_alphanumeric));
+            assert_eq!(&encoded[..], uid.to_base62().as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_base62_order_matches_byte_order() {
+        let mut ids: Vec<KSUID> = (0..500).map(|_| KSUID::new()).collect();
+        // Mix in ids that differ only in the low bytes or only in the timestamp.
+        let mut low = [0u8; 20];
+        for i in 0..=255u8 {
+            low[19] = i;
+            ids.push(KSUID(low));
+            ids.push(KSUID([i; 20]));
+        }
+        for pair in ids.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert_eq!(a.cmp(&b), a.to_base62().cmp(&b.to_base62()));
+            assert_eq!(a.cmp(&b), a.cmp_base62(&b.to_base62()));
+        }
+        let mut by_bytes = ids.clone();
+        by_bytes.sort();
+        let mut by_string = ids;
+        by_string.sort_by_key(|id| id.to_base62());
+        assert_eq!(by_bytes, by_string);
+    }
+
+    #[test]
+    fn test_cmp_base62_invalid() {
+        let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+        assert_eq!(uid.cmp_base62(""), Ordering::Greater);
+        assert_eq!(uid.cmp_base62("0ujsszwN8NRY24YaXiTIE2VWDTS0"), Ordering::Less);
+        assert_eq!(uid.cmp_base62("~"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_is_valid_agrees_with_parse() {
+        let mut inputs = vec![String::new(), MAX_STRING_ENCODED.to_owned(), "aWgEPTl1tmebfsQzFP4bxwgy80W".to_owned()];
+        for _ in 0..100 {
+            let mut encoded = KSUID::new().to_base62_array();
+            inputs.push(String::from_utf8(encoded.to_vec()).unwrap());
+            encoded[rand::thread_rng().gen_range(0, 27)] = b'-';
+            inputs.push(String::from_utf8(encoded.to_vec()).unwrap());
+        }
+        inputs.push("é0000000000000000000000000".to_owned());
+        for input in &inputs {
+            assert_eq!(is_valid(input), KSUID::from_base62(input).is_ok(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_from_base62_bytes_matches_from_base62() {
+        let valid = KSUID::new().to_base62();
+        for input in [valid.as_str(), "", "0ujsszwN8NRY24YaXiTIE2VWDT!", "zzzzzzzzzzzzzzzzzzzzzzzzzzz", "é0000000000000000000000000"].iter() {
+            assert_eq!(KSUID::from_base62_bytes(input.as_bytes()).ok(), KSUID::from_base62(input).ok());
+        }
+        let mut not_utf8 = valid.into_bytes();
+        not_utf8[3] = 0xff;
+        assert!(KSUID::from_base62_bytes(&not_utf8).is_err());
+    }
+
+    #[test]
+    fn test_parse_error_details() {
+        match KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDT").unwrap_err() {
+            errors::KSUIDError::InvalidBase62Length { expected: 27, actual: 26, .. } => {}
+            err => panic!("unexpected error {:?}", err),
+        }
+        match KSUID::from_base62("0ujsszwN8NRY2-YaXiTIE2VWDTS").unwrap_err() {
+            errors::KSUIDError::InvalidBase62Character { position: 13, .. } => {}
+            err => panic!("unexpected error {:?}", err),
+        }
+        let overflow = KSUID::from_base62("zzzzzzzzzzzzzzzzzzzzzzzzzzz").unwrap_err();
+        assert_eq!(overflow.kind(), errors::KSUIDErrorKind::Overflow);
+        assert_eq!(KSUID::from_bytes(&[0; 3]).unwrap_err().kind(), errors::KSUIDErrorKind::InvalidLength);
+    }
+
+    #[test]
+    fn test_from_parts_rejects_out_of_range_timestamps() {
+        let before_epoch = from_ksuid_time(0) - Duration::seconds(1);
+        let after_max = from_ksuid_time(u32::MAX) + Duration::seconds(1);
+        for ts in [before_epoch, after_max].iter() {
+            let err = KSUID::from_parts(*ts, &[0; 16]).unwrap_err();
+            assert_eq!(err.kind(), errors::KSUIDErrorKind::TimestampOutOfRange);
+        }
+        assert!(KSUID::from_parts(from_ksuid_time(u32::MAX), &[0; 16]).is_ok());
+    }
+
+    #[test]
+    fn test_from_parts_exact() {
+        let ts = from_ksuid_time(1000);
+        assert_eq!(KSUID::from_parts_exact(ts, &[1; 16]).unwrap(), KSUID::from_parts(ts, &[1; 16]).unwrap());
+        for len in [0, 15, 17, 32].iter() {
+            let err = KSUID::from_parts_exact(ts, &vec![1; *len]).unwrap_err();
+            assert_eq!(err, errors::KSUIDError::InvalidPayloadLength { expected: 16, actual: *len });
+            assert_eq!(err.kind(), errors::KSUIDErrorKind::InvalidLength);
+        }
+        let before_epoch = from_ksuid_time(0) - Duration::seconds(1);
+        assert_eq!(KSUID::from_parts_exact(before_epoch, &[1; 16]).unwrap_err().kind(), errors::KSUIDErrorKind::TimestampOutOfRange);
+    }
+
+    #[test]
+    fn test_from_parts_padded() {
+        let ts = from_ksuid_time(1000);
+        let small = KSUID::from_parts_padded(ts, &[1, 0]).unwrap();
+        let large = KSUID::from_parts_padded(ts, &[2, 0]).unwrap();
+        assert_eq!(&small.payload()[..14], &[0; 14]);
+        assert!(small < large);
+        assert!(KSUID::from_parts_padded(ts, &[]).unwrap().payload().iter().all(|&b| b == 0));
+        assert_eq!(KSUID::from_parts_padded(ts, &[5; 16]).unwrap(), KSUID::from_parts(ts, &[5; 16]).unwrap());
+        assert_eq!(KSUID::from_parts_padded(ts, &[5; 17]).unwrap_err(), errors::KSUIDError::InvalidPayloadLength { expected: 16, actual: 17 });
+    }
+
+    #[test]
+    fn test_timestamp_in() {
+        let uid = KSUID::from_parts(DateTime::from_timestamp(1_500_003_725, 0).unwrap(), &[0; 16]).unwrap();
+        let west = chrono::FixedOffset::west_opt(5 * 3600).unwrap();
+        assert_eq!(uid.timestamp_in(&west).to_rfc3339(), "2017-07-13T22:42:05-05:00");
+        assert_eq!(uid.timestamp_in(&Utc), uid.timestamp());
+        assert_eq!(uid.timestamp_local(), uid.timestamp());
+    }
+
+    #[test]
+    fn test_cmp_timestamp() {
+        let early = KSUID::from_parts(from_ksuid_time(0x0100), &[0xff; 16]).unwrap();
+        let late = KSUID::from_parts(from_ksuid_time(0x00ff_ffff), &[0; 16]).unwrap();
+        assert_eq!(early.cmp_timestamp(&late), Ordering::Less);
+        assert_eq!(late.cmp_timestamp(&early), Ordering::Greater);
+        assert_eq!(early.cmp_timestamp(&KSUID::from_parts(from_ksuid_time(0x0100), &[0; 16]).unwrap()), Ordering::Equal);
+        for _ in 0..100 {
+            let (a, b) = (KSUID::new(), KSUID::new());
+            assert_eq!(a.cmp_timestamp(&b), a.timestamp().cmp(&b.timestamp()));
+        }
+    }
+
+    #[test]
+    fn test_next_and_prev() {
+        let mut bytes = [0u8; 20];
+        bytes[18] = 1;
+        bytes[19] = 0xff;
+        let uid = KSUID(bytes);
+        assert_eq!(&uid.next().unwrap().0[18..], &[2, 0]);
+        assert_eq!(uid.next().unwrap().prev(), Some(uid));
+        assert_eq!(uid.prev().unwrap().next(), Some(uid));
+        assert_eq!(KSUID([0xff; 20]).next(), None);
+        assert_eq!(KSUID::nil().prev(), None);
+        assert_eq!(KSUID::nil().next().unwrap().prev(), Some(KSUID::nil()));
+    }
+
+    #[test]
+    fn test_same_second_and_duration_between() {
+        let first = KSUID::from_parts(from_ksuid_time(0), &[9; 16]).unwrap();
+        let last = KSUID::from_parts(from_ksuid_time(u32::MAX), &[0; 16]).unwrap();
+        assert!(first.same_second(&KSUID::from_parts(from_ksuid_time(0), &[0; 16]).unwrap()));
+        assert!(!first.same_second(&last));
+        assert_eq!(first.duration_between(&last), Duration::seconds(i64::from(u32::MAX)));
+        assert_eq!(last.duration_between(&first), Duration::seconds(-i64::from(u32::MAX)));
+        assert_eq!(first.duration_between(&first), Duration::zero());
+        assert_eq!(first.duration_between(&last), last.timestamp() - first.timestamp());
+    }
+
+    #[test]
+    fn test_is_older_than() {
+        let created = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let uid = KSUID::from_parts(created, &[1; 16]).unwrap();
+        let ttl = Duration::minutes(15);
+        // Created anywhere within second 1_600_000_000, so not certainly older until a whole
+        // second past the ttl.
+        assert!(!uid.is_older_than_at(ttl, created + ttl));
+        assert!(!uid.is_older_than_at(ttl, created + ttl + Duration::milliseconds(999)));
+        assert!(uid.is_older_than_at(ttl, created + ttl + Duration::seconds(1)));
+        assert!(!uid.is_older_than_at(Duration::zero(), created));
+        assert!(!KSUID::new().is_older_than(Duration::seconds(5)));
+        assert!(!uid.is_older_than(Duration::MAX));
+        assert!(!uid.is_older_than(Duration::MIN));
+    }
+
+    #[test]
+    fn test_validate_not_future() {
+        let now = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let at = |offset: i64| KSUID::from_parts(now + Duration::seconds(offset), &[0; 16]).unwrap();
+        let tolerance = Duration::seconds(30);
+        assert!(at(-1000).validate_not_future_at(tolerance, now).is_ok());
+        assert!(at(30).validate_not_future_at(tolerance, now).is_ok());
+        let err = at(31).validate_not_future_at(tolerance, now).unwrap_err();
+        assert_eq!(err, errors::KSUIDError::FutureTimestamp { seconds_ahead: 31 });
+        assert_eq!(err.kind(), errors::KSUIDErrorKind::TimestampOutOfRange);
+        assert!(at(1).validate_not_future_at(Duration::zero(), now).is_err());
+    }
+
+    #[test]
+    fn test_from_payload_now() {
+        let before = Utc::now().timestamp();
+        let uid = KSUID::from_payload_now([0xab; 16]);
+        assert_eq!(uid.payload(), &[0xab; 16]);
+        assert!(uid.timestamp().timestamp() >= before && uid.timestamp().timestamp() <= Utc::now().timestamp());
+    }
+
+    #[test]
+    fn test_nil() {
+        assert_eq!(KSUID::default(), KSUID::nil());
+        assert!(KSUID::from_base62("000000000000000000000000000").unwrap().is_nil());
+        assert!(!KSUID::new().is_nil());
+        assert!(!KSUID::from_bytes(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]).unwrap().is_nil());
+    }
+
+    #[test]
+    fn test_iter() {
+        let ids: Vec<KSUID> = KSUID::iter().take(100).collect();
+        let mut distinct = ids.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 100);
+        assert_eq!(KSUID::iter().size_hint(), (usize::MAX, None));
+    }
+
+    #[test]
+    fn test_public_constants() {
+        assert_eq!(KSUID::EPOCH, from_ksuid_time(0));
+        assert_eq!(KSUID::EPOCH.to_rfc3339(), "2014-05-13T16:53:20+00:00");
+        assert_eq!(KSUID::MAX_TIMESTAMP, from_ksuid_time(u32::MAX));
+        assert_eq!(KSUID::MAX_TIMESTAMP.to_rfc3339(), "2150-06-19T23:21:35+00:00");
+        assert_eq!(KSUID::new().as_bytes().len(), KSUID::BYTE_LENGTH);
+        assert_eq!(KSUID::new().payload().len(), KSUID::PAYLOAD_LENGTH);
+        assert_eq!(KSUID::new().to_base62().len(), KSUID::ENCODED_LENGTH);
+        assert_eq!(KSUID::TIMESTAMP_LENGTH + KSUID::PAYLOAD_LENGTH, KSUID::BYTE_LENGTH);
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let expected = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+        for input in ["0ujsszwN8NRY24YaXiTIE2VWDTS", "\t0ujsszwN8NRY24YaXiTIE2VWDTS  ", "ksuid: 0ujsszwN8NRY24YaXiTIE2VWDTS"].iter() {
+            assert_eq!(KSUID::parse_lenient(input, &["ksuid:", "req-"]).unwrap(), expected);
+        }
+        // Unknown decorations and inner garbage are still rejected.
+        assert!(KSUID::parse_lenient("id=0ujsszwN8NRY24YaXiTIE2VWDTS", &["ksuid:"]).is_err());
+        assert!(KSUID::parse_lenient("req-0ujsszwN8NRY24YaXiTIE2VWDTS.", &["req-"]).is_err());
+    }
+
+    #[test]
+    fn test_from_name() {
+        let ts = from_ksuid_time(1000);
+        let uid = KSUID::from_name(b"ns", b"name", ts).unwrap();
+        assert_eq!(uid.timestamp(), ts);
+        assert_eq!(uid, KSUID::from_name(b"ns", b"name", ts).unwrap());
+        // The namespace length is hashed so boundaries can't be shifted between the two inputs.
+        assert_ne!(uid, KSUID::from_name(b"nsn", b"ame", ts).unwrap());
+        let early = DateTime::from_timestamp(EPOCH_START - 1, 0).unwrap();
+        assert_eq!(KSUID::from_name(b"ns", b"name", early).unwrap_err().kind(), errors::KSUIDErrorKind::TimestampOutOfRange);
+    }
+
+    #[cfg(feature = "subtle")]
+    #[test]
+    fn test_ct_eq() {
+        let uid = KSUID::new();
+        let same = KSUID::from_bytes(uid.as_bytes()).unwrap();
+        let mut flipped = uid.0;
+        flipped[19] ^= 1;
+        assert!(uid.ct_eq_bool(&same));
+        assert!(!uid.ct_eq_bool(&KSUID(flipped)));
+        // Method syntax reaches the trait, so results combine with `subtle`.
+        let both = uid.ct_eq(&same) & uid.ct_eq(&KSUID(flipped));
+        assert!(!bool::from(both));
+    }
+
     #[bench]
     fn bench_ksuid_new(b: &mut Bencher) {
-        b.iter(|| KSUID::new());
+        b.iter(KSUID::new);
     }
 
 }
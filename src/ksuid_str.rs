@@ -0,0 +1,157 @@
+use base62;
+use errors;
+use ksuid::{is_valid, KSUID};
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::str::{self, FromStr};
+
+/// The 27 character base62 form of a `KSUID`, stored inline. For services that mostly pass the
+/// textual form around: it derefs to `&str` like a `String` would, but copying one never touches
+/// the heap, and holding one guarantees the text is a valid ksuid.
+///
+/// Ordering matches `KSUID`'s (see its `# Ordering` section), and `Hash` agrees with `str` so a
+/// `HashMap<KsuidStr, _>` can be queried with a plain `&str`.
+/// # Example
+/// ```
+/// use ksuid::{KsuidStr, KSUID};
+///
+/// let text: KsuidStr = "0ujsszwN8NRY24YaXiTIE2VWDTS".parse().unwrap();
+/// assert!(text.starts_with("0ujs"));
+/// assert_eq!(KSUID::from(text).to_base62(), &*text);
+/// assert!("0ujsszwN8NRY24YaXiTIE2VWDT!".parse::<KsuidStr>().is_err());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct KsuidStr([u8; 27]);
+
+impl KsuidStr {
+    /// View the encoded id as a string slice.
+    pub fn as_str(&self) -> &str {
+        // Only ever built from the base62 encoder or validated input, so always ASCII.
+        str::from_utf8(&self.0).unwrap()
+    }
+
+    /// Decode back into the binary `KSUID`.
+    pub fn to_ksuid(&self) -> KSUID {
+        KSUID::from(*self)
+    }
+}
+
+impl Deref for KsuidStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for KsuidStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for KsuidStr {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Hash for KsuidStr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialEq<str> for KsuidStr {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for KsuidStr {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl fmt::Display for KsuidStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl fmt::Debug for KsuidStr {
+    #[cfg(not(feature = "redacted-debug"))]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("KsuidStr").field(&self.as_str()).finish()
+    }
+
+    #[cfg(feature = "redacted-debug")]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("KsuidStr").field(&format_args!("{}", self.to_ksuid().redacted())).finish()
+    }
+}
+
+impl FromStr for KsuidStr {
+    type Err = errors::KSUIDError;
+
+    /// Validate `s` without decoding it, rejecting exactly what `KSUID::from_base62` rejects.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !is_valid(s) {
+            // Let the decoder pick the precise error.
+            return KSUID::from_base62(s).map(KsuidStr::from);
+        }
+        let mut encoded = [0u8; 27];
+        encoded.copy_from_slice(s.as_bytes());
+        Ok(KsuidStr(encoded))
+    }
+}
+
+impl From<KSUID> for KsuidStr {
+    fn from(uid: KSUID) -> Self {
+        KsuidStr(uid.to_base62_array())
+    }
+}
+
+impl From<KsuidStr> for KSUID {
+    fn from(text: KsuidStr) -> Self {
+        // Validated on construction, so decoding can't fail.
+        let bytes = base62::decode_bytes(&text.0, 27, 20).unwrap();
+        KSUID::from_bytes(&bytes).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_ksuid_str_roundtrip() {
+        let uid = KSUID::new();
+        let text = KsuidStr::from(uid);
+        assert_eq!(text, uid.to_base62().as_str());
+        assert_eq!(text.to_ksuid(), uid);
+        assert_eq!(text.parse::<KsuidStr>().unwrap(), text);
+        assert_eq!(format!("{:>30}", text), format!("{:>30}", uid));
+    }
+
+    #[test]
+    fn test_ksuid_str_rejects_invalid() {
+        for input in ["", "0ujsszwN8NRY24YaXiTIE2VWDT", "0ujsszwN8NRY24YaXiTIE2VWDT!", "zzzzzzzzzzzzzzzzzzzzzzzzzzz"].iter() {
+            assert!(input.parse::<KsuidStr>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_ksuid_str_order_and_lookup() {
+        let a = KSUID::from_bytes(&[1; 20]).unwrap();
+        let b = KSUID::from_bytes(&[2; 20]).unwrap();
+        assert!(KsuidStr::from(a) < KsuidStr::from(b));
+        let mut map = HashMap::new();
+        map.insert(KsuidStr::from(a), 1);
+        assert_eq!(map.get(a.to_base62().as_str()), Some(&1));
+    }
+}
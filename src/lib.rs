@@ -1,15 +1,232 @@
 #![feature(test)]
+// failure_derive expands into impls nested inside anonymous consts.
+#![allow(non_local_definitions)]
 
+#[cfg(feature = "crypto")]
+extern crate aes;
+#[cfg(feature = "arbitrary")]
+extern crate arbitrary;
+#[cfg(feature = "arrayvec")]
+extern crate arrayvec;
+#[cfg(feature = "dynamodb")]
+extern crate aws_sdk_dynamodb;
 extern crate chrono;
+#[cfg(feature = "chrono-humanize")]
+extern crate chrono_humanize;
+#[cfg(feature = "cbor")]
+extern crate ciborium;
+#[cfg(feature = "convert")]
+extern crate csv;
 extern crate byteorder;
 extern crate failure;
+#[cfg(feature = "headers")]
+extern crate headers;
+#[cfg(all(test, any(feature = "futures", feature = "warp")))]
+extern crate futures;
+#[cfg(feature = "futures")]
+extern crate futures_core;
+#[cfg(feature = "futures")]
+extern crate futures_timer;
+#[cfg(feature = "signed")]
+extern crate hmac;
+#[cfg(any(feature = "tower", feature = "poem-openapi"))]
+extern crate http;
+#[cfg(all(test, feature = "warp"))]
+extern crate http_body;
+#[cfg(feature = "log")]
+extern crate log;
 #[macro_use] extern crate failure_derive;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+#[cfg(feature = "metrics")]
+#[macro_use] extern crate metrics as metrics_crate;
+#[cfg(all(test, feature = "metrics"))]
+extern crate metrics_util;
+#[cfg(feature = "otel")]
+extern crate opentelemetry;
+#[cfg(feature = "poem-openapi")]
+extern crate poem_openapi;
+#[cfg(feature = "proptest")]
+extern crate proptest as proptest_crate;
 extern crate rand;
+#[cfg(feature = "chacha")]
+extern crate rand_chacha;
+#[cfg(feature = "chacha")]
+extern crate rand_core;
+#[cfg(feature = "kafka")]
+extern crate rdkafka;
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "serde")]
+extern crate serde as serde_crate;
+#[cfg(all(test, feature = "serde_dynamo"))]
+extern crate serde_dynamo;
+#[cfg(all(any(feature = "poem-openapi", feature = "convert"), not(test)))]
+extern crate serde_json;
+#[cfg(all(test, any(feature = "poem-openapi", feature = "serde", feature = "convert")))]
+#[macro_use] extern crate serde_json;
+extern crate sha2;
+#[cfg(feature = "slog")]
+extern crate slog;
+#[cfg(feature = "subtle")]
+extern crate subtle;
 extern crate test;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "tower")]
+extern crate tower_http;
+#[cfg(feature = "tower")]
+extern crate tower_layer;
+#[cfg(any(feature = "tower", all(test, feature = "warp")))]
+extern crate tower_service;
+#[cfg(feature = "tracing")]
+#[macro_use] extern crate tracing;
+#[cfg(feature = "warp")]
+extern crate warp;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
 mod errors;
+mod base58;
 mod base62;
+mod block;
+mod btree_range;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+mod chacha;
+mod entropy;
+mod expiring;
+pub mod compat;
+mod clock;
+mod column;
+#[cfg(feature = "convert")]
+pub mod convert;
+mod cursor;
+mod datetime_ext;
+#[cfg(feature = "dynamodb")]
+pub mod dynamodb;
 mod ksuid;
+mod ksuid_str;
+mod merge;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+mod multibase;
+mod file;
+mod filename;
+mod filter;
+mod generator;
+mod hash;
+mod hook;
+mod idempotency;
+#[cfg(feature = "tower")]
+mod idempotency_layer;
+#[cfg(feature = "chrono-humanize")]
+mod humanize;
+mod interval;
+mod io;
+#[cfg(feature = "jwt")]
+mod jwt;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "log")]
+mod log_value;
+#[cfg(feature = "tokio")]
+mod async_io;
+mod object_id;
+mod object_key;
+#[cfg(feature = "poem-openapi")]
+mod openapi;
+#[cfg(feature = "otel")]
+mod otel;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+mod public;
+mod rate_limit;
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+mod raw_bytes;
+mod redact;
+#[cfg(feature = "tower")]
+mod request_id;
+pub mod schema;
+mod secure;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod shared;
+mod state;
+pub mod sort;
+#[cfg(feature = "futures")]
+mod stream;
+mod window;
+pub mod wire;
+#[cfg(feature = "slog")]
+mod slog_value;
+#[cfg(feature = "tracing")]
+mod trace;
+#[cfg(feature = "headers")]
+mod typed_header;
+mod urn;
+#[cfg(feature = "warp")]
+mod warp_filter;
+#[cfg(feature = "crypto")]
+mod encrypted;
+#[cfg(feature = "signed")]
+mod signed;
 
-pub use errors::KSUIDError;
-pub use ksuid::KSUID;
+pub use chacha::ChaCha20Options;
+pub use clock::{ClockRegression, ClockRegressionPolicy};
+pub use block::{BlockAllocator, BlockGenerator, IdBlock};
+pub use btree_range::KsuidRangeExt;
+pub use column::KsuidColumn;
+pub use cursor::{Cursor, PageCursor};
+pub use datetime_ext::KsuidDateTimeExt;
+pub use errors::{BulkParseError, KSUIDError, KSUIDErrorKind, RedactedError};
+pub use ksuid::{is_valid, KSUID};
+pub use ksuid_str::KsuidStr;
+pub use merge::{merge_sorted, MergeSorted};
+pub use multibase::Multibase;
+pub use object_key::{base62_prefix_for_range, object_key_prefixes};
+#[cfg(feature = "rayon")]
+pub use parallel::generate_parallel;
+pub use expiring::ExpiringKsuid;
+#[cfg(feature = "mmap")]
+pub use file::KsuidFile;
+pub use file::KsuidFileWriter;
+pub use filter::KsuidFilter;
+pub use generator::{Generator, GeneratorOptions, MAX_NODE_ID_LENGTH};
+pub use hash::{KsuidHashBuilder, KsuidHashMap, KsuidHashSet, KsuidHasher};
+pub use hook::{GenerationContext, GenerationHook};
+pub use idempotency::{IdempotencyKey, IdempotencyStore, InsertFuture, MemoryIdempotencyStore};
+#[cfg(feature = "tower")]
+pub use idempotency_layer::{Idempotency, IdempotencyFuture, IdempotencyLayer};
+#[cfg(feature = "chrono-humanize")]
+pub use humanize::HumanAge;
+pub use interval::KsuidIntervalSet;
+pub use io::{ReadKsuidExt, WriteKsuidExt};
+#[cfg(feature = "jwt")]
+pub use jwt::Jti;
+#[cfg(feature = "tokio")]
+pub use async_io::{AsyncReadKsuidExt, AsyncWriteKsuidExt, ReadKsuid, WriteKsuid};
+pub use public::{PublicId, PublicIdKey};
+pub use redact::Redacted;
+#[cfg(feature = "tower")]
+pub use request_id::{KsuidRequestId, KsuidRequestIdLayer, MakeRequestKsuid};
+pub use secure::SecureKsuid;
+pub use shared::SharedGenerator;
+pub use state::{FileStateStore, GeneratorState, StateStore};
+#[cfg(feature = "futures")]
+pub use stream::KsuidStream;
+pub use window::RecentKsuidWindow;
+#[cfg(feature = "warp")]
+pub use warp_filter::{handle_invalid_ksuid, ksuid_param, InvalidKsuid};
+#[cfg(feature = "tracing")]
+pub use trace::request_span;
+#[cfg(feature = "signed")]
+pub use signed::SignedKsuid;
+#[cfg(feature = "headers")]
+pub use typed_header::XRequestKsuid;
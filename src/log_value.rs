@@ -0,0 +1,25 @@
+use ksuid::KSUID;
+use log::kv::{ToValue, Value};
+
+/// Lets a `KSUID` travel through the `log` facade's structured key-values, captured by its
+/// base62 `Display` form so formatters render it as a plain string.
+impl ToValue for KSUID {
+    fn to_value(&self) -> Value<'_> {
+        Value::from_display(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Record;
+
+    #[test]
+    fn test_log_kv() {
+        let uid = KSUID::new();
+        let kvs = [("id", uid.to_value())];
+        let record = Record::builder().key_values(&kvs).build();
+        let value = record.key_values().get("id".into()).unwrap();
+        assert_eq!(value.to_string(), uid.to_base62());
+    }
+}
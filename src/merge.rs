@@ -0,0 +1,108 @@
+use ksuid::KSUID;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Iterator adaptor merging several ascending `KSUID` streams into one ascending stream.
+/// Created by `merge_sorted`. Inputs that are not themselves sorted produce unspecified (but
+/// memory safe) output order.
+#[derive(Debug)]
+pub struct MergeSorted<I: Iterator<Item = KSUID>> {
+    streams: Vec<I>,
+    // Head of each non-exhausted stream, tagged with the stream index to refill from.
+    heads: BinaryHeap<Reverse<(KSUID, usize)>>,
+    dedup: bool,
+    last: Option<KSUID>,
+}
+
+/// Merge any number of ascending `KSUID` streams lazily, holding only one pending id per stream.
+/// # Example
+/// ```
+/// use ksuid::{merge_sorted, KSUID};
+///
+/// let ids: Vec<KSUID> = (0..5).map(|i| KSUID::from_bytes(&[i; 20]).unwrap()).collect();
+/// let a = vec![ids[0], ids[2], ids[4]];
+/// let b = vec![ids[1], ids[2], ids[3]];
+/// let merged: Vec<KSUID> = merge_sorted(vec![a, b]).dedup().collect();
+/// assert_eq!(merged, ids);
+/// ```
+pub fn merge_sorted<S>(streams: S) -> MergeSorted<<S::Item as IntoIterator>::IntoIter>
+where
+    S: IntoIterator,
+    S::Item: IntoIterator<Item = KSUID>,
+{
+    let mut streams: Vec<_> = streams.into_iter().map(IntoIterator::into_iter).collect();
+    let mut heads = BinaryHeap::with_capacity(streams.len());
+    for (index, stream) in streams.iter_mut().enumerate() {
+        if let Some(id) = stream.next() {
+            heads.push(Reverse((id, index)));
+        }
+    }
+    MergeSorted { streams, heads, dedup: false, last: None }
+}
+
+impl<I: Iterator<Item = KSUID>> MergeSorted<I> {
+    /// Collapse runs of equal ids (within or across streams) into a single id.
+    pub fn dedup(mut self) -> Self {
+        self.dedup = true;
+        self
+    }
+}
+
+impl<I: Iterator<Item = KSUID>> Iterator for MergeSorted<I> {
+    type Item = KSUID;
+
+    fn next(&mut self) -> Option<KSUID> {
+        loop {
+            let Reverse((id, index)) = self.heads.pop()?;
+            if let Some(next) = self.streams[index].next() {
+                self.heads.push(Reverse((next, index)));
+            }
+            if self.dedup && self.last == Some(id) {
+                continue;
+            }
+            self.last = Some(id);
+            return Some(id);
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.heads.len();
+        let upper = self.streams.iter().try_fold(pending, |acc, s| s.size_hint().1.and_then(|n| acc.checked_add(n)));
+        let lower = if self.dedup { 0 } else { pending };
+        (lower, upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand;
+    use rand::Rng;
+
+    #[test]
+    fn test_merge_matches_sort() {
+        let mut rng = rand::thread_rng();
+        let mut streams: Vec<Vec<KSUID>> = Vec::new();
+        for _ in 0..7 {
+            let mut s: Vec<KSUID> = (0..rng.gen_range(0, 50)).map(|_| {
+                let mut bytes = [0u8; 20];
+                bytes[19] = rng.gen_range(0, 40);
+                KSUID(bytes)
+            }).collect();
+            s.sort();
+            streams.push(s);
+        }
+        let mut expected: Vec<KSUID> = streams.iter().flatten().cloned().collect();
+        expected.sort();
+        assert_eq!(merge_sorted(streams.clone()).collect::<Vec<_>>(), expected);
+        expected.dedup();
+        assert_eq!(merge_sorted(streams).dedup().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_merge_empty() {
+        let empty: Vec<Vec<KSUID>> = vec![vec![], vec![]];
+        assert_eq!(merge_sorted(empty).next(), None);
+        assert_eq!(merge_sorted(Vec::<Vec<KSUID>>::new()).next(), None);
+    }
+}
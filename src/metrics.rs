@@ -0,0 +1,46 @@
+//! Instrumentation emitted through the `metrics` facade by every `Generator`. Nothing is recorded
+//! until the application installs a recorder, e.g. `metrics-exporter-prometheus`; the names
+//! below are what shows up there.
+//!
+//! ```
+//! use ksuid::Generator;
+//!
+//! // With no recorder installed these are no-ops.
+//! let mut gen = Generator::new();
+//! gen.next_batch(10);
+//! assert_eq!(ksuid::metrics::IDS_ISSUED, "ksuid_ids_issued_total");
+//! ```
+
+use std::time::Duration;
+
+/// Counter of ids issued.
+pub const IDS_ISSUED: &str = "ksuid_ids_issued_total";
+/// Histogram of the sizes passed to `Generator::next_batch` and `try_next_batch`.
+pub const BATCH_SIZE: &str = "ksuid_batch_size";
+/// Histogram of seconds spent filling each id's random payload.
+pub const RNG_SECONDS: &str = "ksuid_rng_seconds";
+/// Counter of ids whose timestamp a counter generator moved ahead of the wall clock to stay
+/// monotonic, because the clock went backwards or a second's counter ran out.
+pub const MONOTONIC_ADJUSTMENTS: &str = "ksuid_monotonic_adjustments_total";
+/// Counter of `try_next_id` and `try_next_batch` calls refused by `max_per_second`.
+pub const RATE_LIMITED: &str = "ksuid_rate_limited_total";
+
+pub(crate) fn record_issued(count: usize) {
+    counter!(IDS_ISSUED).increment(count as u64);
+}
+
+pub(crate) fn record_batch(size: usize) {
+    histogram!(BATCH_SIZE).record(size as f64);
+}
+
+pub(crate) fn record_rng(elapsed: Duration) {
+    histogram!(RNG_SECONDS).record(elapsed);
+}
+
+pub(crate) fn record_adjustment() {
+    counter!(MONOTONIC_ADJUSTMENTS).increment(1);
+}
+
+pub(crate) fn record_rate_limited() {
+    counter!(RATE_LIMITED).increment(1);
+}
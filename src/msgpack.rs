@@ -0,0 +1,88 @@
+//! MessagePack representation for use with `#[serde(with = "ksuid::msgpack")]` and `rmp-serde`:
+//! an ext8 value of type `MSGPACK_EXT_TYPE` carrying the 20 raw bytes, so ids are told apart
+//! from other binary on the wire. Deserializing expects the same ext type.
+//!
+//! ```
+//! # extern crate ksuid;
+//! # extern crate rmp_serde;
+//! # extern crate serde;
+//! use ksuid::KSUID;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Call {
+//!     #[serde(with = "ksuid::msgpack")]
+//!     request_id: KSUID,
+//!     method: String,
+//! }
+//!
+//! # fn main() {
+//! let call = Call { request_id: KSUID::new(), method: "ping".to_owned() };
+//! let encoded = rmp_serde::to_vec(&call).unwrap();
+//! let decoded: Call = rmp_serde::from_slice(&encoded).unwrap();
+//! assert_eq!(decoded.request_id, call.request_id);
+//! # }
+//! ```
+//!
+//! Other serde formats see the ext value as a newtype around a `(type, bytes)` tuple.
+
+use ksuid::KSUID;
+use raw_bytes::RawBytes;
+use serde_crate::de::Error;
+use serde_crate::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The application ext type ids are written with. MessagePack leaves 0 to 127 to applications;
+/// change nothing here without migrating every reader.
+pub const MSGPACK_EXT_TYPE: i8 = 0x4b;
+
+// rmp-serde maps a newtype struct with this name (`rmp_serde::MSGPACK_EXT_STRUCT_NAME`) onto
+// an ext value.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "_ExtStruct")]
+struct Ext((i8, RawBytes));
+
+/// Serialize `uid` as a MessagePack ext8 value of type `MSGPACK_EXT_TYPE`.
+pub fn serialize<S: Serializer>(uid: &KSUID, serializer: S) -> Result<S::Ok, S::Error> {
+    Ext((MSGPACK_EXT_TYPE, RawBytes(uid.0))).serialize(serializer)
+}
+
+/// Deserialize an ext value of type `MSGPACK_EXT_TYPE` holding 20 bytes.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<KSUID, D::Error> {
+    let Ext((ext_type, bytes)) = Ext::deserialize(deserializer)?;
+    if ext_type != MSGPACK_EXT_TYPE {
+        return Err(D::Error::custom(format_args!("expected msgpack ext type {}, found {}", MSGPACK_EXT_TYPE, ext_type)));
+    }
+    Ok(KSUID(bytes.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmp_serde;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Wrapper(#[serde(with = "super")] KSUID);
+
+    #[test]
+    fn test_msgpack_ext8() {
+        let uid = KSUID::new();
+        let encoded = rmp_serde::to_vec(&Wrapper(uid)).unwrap();
+        // ext8 marker, length 20, type, then the raw bytes.
+        assert_eq!(encoded[..3], [0xc7, 20, MSGPACK_EXT_TYPE as u8]);
+        assert_eq!(encoded[3..], uid.0);
+        assert_eq!(rmp_serde::from_slice::<Wrapper>(&encoded).unwrap(), Wrapper(uid));
+    }
+
+    #[test]
+    fn test_msgpack_rejects_other_values() {
+        let uid = KSUID::new();
+        let mut other_type = rmp_serde::to_vec(&Wrapper(uid)).unwrap();
+        other_type[2] = 7;
+        assert!(rmp_serde::from_slice::<Wrapper>(&other_type).is_err());
+        let mut short = vec![0xc7, 16, MSGPACK_EXT_TYPE as u8];
+        short.extend_from_slice(&uid.0[..16]);
+        assert!(rmp_serde::from_slice::<Wrapper>(&short).is_err());
+        let bin = rmp_serde::to_vec(&RawBytes(uid.0)).unwrap();
+        assert!(rmp_serde::from_slice::<Wrapper>(&bin).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use base58;
+use errors;
+use ksuid::{BYTE_LENGTH, KSUID};
+
+/// Encodings `KSUID::to_multibase` can produce, from the multiformats multibase table. The
+/// table has no base62 entry, so the canonical base62 form can't be expressed as multibase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Multibase {
+    /// `f`: lowercase hex.
+    Base16,
+    /// `F`: uppercase hex.
+    Base16Upper,
+    /// `b`: RFC 4648 base32, lowercase, no padding.
+    Base32,
+    /// `B`: RFC 4648 base32, uppercase, no padding.
+    Base32Upper,
+    /// `z`: Bitcoin base58.
+    Base58Btc,
+}
+
+impl Multibase {
+    /// The single character prefix identifying this encoding.
+    pub fn prefix(self) -> char {
+        match self {
+            Multibase::Base16 => 'f',
+            Multibase::Base16Upper => 'F',
+            Multibase::Base32 => 'b',
+            Multibase::Base32Upper => 'B',
+            Multibase::Base58Btc => 'z',
+        }
+    }
+
+    /// Look up an encoding by its prefix.
+    pub fn from_prefix(prefix: char) -> Option<Self> {
+        match prefix {
+            'f' => Some(Multibase::Base16),
+            'F' => Some(Multibase::Base16Upper),
+            'b' => Some(Multibase::Base32),
+            'B' => Some(Multibase::Base32Upper),
+            'z' => Some(Multibase::Base58Btc),
+            _ => None,
+        }
+    }
+}
+
+const HEX_LOWER: &[u8] = b"0123456789abcdef";
+const HEX_UPPER: &[u8] = b"0123456789ABCDEF";
+const BASE32_LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE32_UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// Encode `bytes` as fixed `bits` wide digits, most significant first. 20 bytes is a whole
+// number of both 4 and 5 bit digits, so there's never a partial group to pad.
+fn encode_bits(bytes: &[u8], bits: u32, alphabet: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len() * 8 / bits as usize);
+    let mut buffer = 0u32;
+    let mut buffered = 0;
+    for &b in bytes {
+        buffer = (buffer << 8) | u32::from(b);
+        buffered += 8;
+        while buffered >= bits {
+            buffered -= bits;
+            encoded.push(alphabet[((buffer >> buffered) & ((1 << bits) - 1)) as usize] as char);
+        }
+    }
+    encoded
+}
+
+fn decode_bits(text: &str, bits: u32, alphabet: &[u8]) -> Option<[u8; BYTE_LENGTH]> {
+    if text.len() * bits as usize != BYTE_LENGTH * 8 {
+        return None;
+    }
+    let mut bytes = [0u8; BYTE_LENGTH];
+    let mut written = 0;
+    let mut buffer = 0u32;
+    let mut buffered = 0;
+    for c in text.bytes() {
+        let digit = alphabet.iter().position(|&a| a == c)? as u32;
+        buffer = (buffer << bits) | digit;
+        buffered += bits;
+        if buffered >= 8 {
+            buffered -= 8;
+            bytes[written] = (buffer >> buffered) as u8;
+            written += 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// Encode as 40 lowercase hex digits.
+#[cfg(feature = "convert")]
+pub(crate) fn encode_hex(bytes: &[u8; BYTE_LENGTH]) -> String {
+    encode_bits(bytes, 4, HEX_LOWER)
+}
+
+/// Parse 40 hex digits, all lowercase or all uppercase.
+#[cfg(any(feature = "serde", feature = "convert"))]
+pub(crate) fn decode_hex(text: &str) -> Option<[u8; BYTE_LENGTH]> {
+    decode_bits(text, 4, HEX_LOWER).or_else(|| decode_bits(text, 4, HEX_UPPER))
+}
+
+/// Multibase (self describing) text forms, for content addressed and IPFS adjacent systems: a
+/// one character prefix naming the encoding, followed by the 20 raw bytes in that encoding.
+impl KSUID {
+    /// Encode with the given multibase encoding, prefix included.
+    /// # Example
+    /// ```
+    /// use ksuid::{KSUID, Multibase};
+    ///
+    /// let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+    /// assert_eq!(uid.to_multibase(Multibase::Base16), "f0669f605d9719f3234fe964b6376cb6b9a2e5e86");
+    /// let text = uid.to_multibase(Multibase::Base58Btc);
+    /// assert!(text.starts_with('z'));
+    /// assert_eq!(KSUID::from_multibase(&text).unwrap(), uid);
+    /// ```
+    pub fn to_multibase(&self, encoding: Multibase) -> String {
+        let body = match encoding {
+            Multibase::Base16 => encode_bits(&self.0, 4, HEX_LOWER),
+            Multibase::Base16Upper => encode_bits(&self.0, 4, HEX_UPPER),
+            Multibase::Base32 => encode_bits(&self.0, 5, BASE32_LOWER),
+            Multibase::Base32Upper => encode_bits(&self.0, 5, BASE32_UPPER),
+            Multibase::Base58Btc => base58::encode_btc(&self.0),
+        };
+        format!("{}{}", encoding.prefix(), body)
+    }
+
+    /// Parse any multibase text `to_multibase` can produce, picking the encoding from the prefix.
+    pub fn from_multibase(text: &str) -> Result<Self, errors::KSUIDError> {
+        let mut chars = text.chars();
+        let encoding = chars.next().and_then(Multibase::from_prefix);
+        let body = chars.as_str();
+        let bytes = encoding.and_then(|encoding| match encoding {
+            Multibase::Base16 => decode_bits(body, 4, HEX_LOWER),
+            Multibase::Base16Upper => decode_bits(body, 4, HEX_UPPER),
+            Multibase::Base32 => decode_bits(body, 5, BASE32_LOWER),
+            Multibase::Base32Upper => decode_bits(body, 5, BASE32_UPPER),
+            Multibase::Base58Btc => base58::decode_btc(body),
+        });
+        bytes.map(KSUID).ok_or_else(|| errors::KSUIDError::InvalidMultibase { value: text.to_owned() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Multibase; 5] = [
+        Multibase::Base16, Multibase::Base16Upper, Multibase::Base32, Multibase::Base32Upper, Multibase::Base58Btc,
+    ];
+
+    #[test]
+    fn test_multibase_roundtrip() {
+        let mut leading_zeros = [0u8; 20];
+        leading_zeros[3] = 7;
+        for uid in [KSUID::new(), KSUID::nil(), KSUID([0xff; 20]), KSUID(leading_zeros)].iter() {
+            for &encoding in ALL.iter() {
+                let text = uid.to_multibase(encoding);
+                assert_eq!(Multibase::from_prefix(text.chars().next().unwrap()), Some(encoding));
+                assert_eq!(KSUID::from_multibase(&text).unwrap(), *uid, "{}", text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_multibase_known_values() {
+        // Checked against Python's base64.b32encode and a reference base58btc encoder.
+        let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+        assert_eq!(uid.to_multibase(Multibase::Base32), "bazu7mbozogptenh6szfwg5wlnonc4xug");
+        assert_eq!(uid.to_multibase(Multibase::Base58Btc), "z6BbY3YiUZBb4NyfCkFsRM3oVLiH");
+        assert_eq!(KSUID::nil().to_multibase(Multibase::Base58Btc), "z11111111111111111111");
+    }
+
+    #[test]
+    fn test_multibase_rejects() {
+        for text in ["", "q", "f00", "xabc", "zz", "z1111111111111111111111"].iter() {
+            assert_eq!(KSUID::from_multibase(text).unwrap_err().kind(), errors::KSUIDErrorKind::InvalidFormat, "{}", text);
+        }
+        // The prefix fixes the case of the body.
+        let upper = KSUID([0xab; 20]).to_multibase(Multibase::Base16Upper);
+        assert!(KSUID::from_multibase(&upper.replacen('F', "f", 1)).is_err());
+        // base58btc for a value that needs fewer leading zero bytes than it claims.
+        assert!(KSUID::from_multibase("z1111111111111111111112").is_err());
+    }
+}
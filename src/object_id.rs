@@ -0,0 +1,95 @@
+use byteorder::{BigEndian, ByteOrder};
+use chrono::DateTime;
+use errors;
+use ksuid::{checked_ksuid_time, BYTE_LENGTH, KSUID, TIMESTAMP_LENGTH};
+use std::convert::TryFrom;
+
+/// Length of a MongoDB ObjectId.
+const OBJECT_ID_LENGTH: usize = 12;
+
+// An ObjectId is a 4 byte big endian unix timestamp followed by 5 random (per process) and 3
+// counter bytes. Those 8 trailing bytes become the last 8 bytes of the ksuid payload, the ones
+// `shard_of` reads, and the first 8 payload bytes are zero.
+const TAIL_LENGTH: usize = OBJECT_ID_LENGTH - TIMESTAMP_LENGTH;
+
+/// Conversions to and from MongoDB ObjectIds as raw 12 byte arrays (`ObjectId::bytes()` and
+/// `ObjectId::from_bytes` in the `bson` crate), for dual reads while migrating keys.
+///
+/// The mapping keeps the creation time, so converted ids sort like the originals. ObjectId to
+/// KSUID and back is exact; KSUID to ObjectId drops the first 8 payload bytes, so only ids that
+/// came from ObjectIds (whose first 8 payload bytes are zero) survive the trip back.
+impl KSUID {
+
+    /// Convert an ObjectId. Fails for ObjectIds created before the ksuid epoch (2014-05-13).
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let oid = [0x65, 0x0b, 0x2a, 0x3c, 0x1f, 0x2e, 0x3d, 0x4c, 0x5b, 0x00, 0x00, 0x2a];
+    /// let uid = KSUID::from_object_id(&oid).unwrap();
+    /// assert_eq!(uid.timestamp().timestamp(), 0x650b2a3c);
+    /// assert_eq!(uid.to_object_id().unwrap(), oid);
+    /// ```
+    pub fn from_object_id(object_id: &[u8; OBJECT_ID_LENGTH]) -> Result<Self, errors::KSUIDError> {
+        let unix_seconds = i64::from(BigEndian::read_u32(object_id));
+        let t = DateTime::from_timestamp(unix_seconds, 0)
+            .ok_or(errors::KSUIDError::TimestampOutOfRange { unix_seconds })
+            .and_then(checked_ksuid_time)?;
+        let mut bytes = [0u8; BYTE_LENGTH];
+        BigEndian::write_u32(&mut bytes, t);
+        bytes[BYTE_LENGTH - TAIL_LENGTH..].copy_from_slice(&object_id[TIMESTAMP_LENGTH..]);
+        Ok(KSUID(bytes))
+    }
+
+    /// Convert to an ObjectId with the same creation time and the last 8 payload bytes. Fails
+    /// for ids created after 2106-02-07, past the ObjectId timestamp range.
+    pub fn to_object_id(&self) -> Result<[u8; OBJECT_ID_LENGTH], errors::KSUIDError> {
+        let unix_seconds = self.timestamp().timestamp();
+        let t = u32::try_from(unix_seconds).map_err(|_| errors::KSUIDError::TimestampOutOfRange { unix_seconds })?;
+        let mut object_id = [0u8; OBJECT_ID_LENGTH];
+        BigEndian::write_u32(&mut object_id, t);
+        object_id[TIMESTAMP_LENGTH..].copy_from_slice(&self.0[BYTE_LENGTH - TAIL_LENGTH..]);
+        Ok(object_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand;
+    use rand::Rng;
+
+    #[test]
+    fn test_object_id_roundtrip() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let mut oid = [0u8; OBJECT_ID_LENGTH];
+            rng.fill_bytes(&mut oid);
+            // Somewhere between the ksuid epoch and the end of the u32 range.
+            BigEndian::write_u32(&mut oid, rng.gen_range(1_400_000_000, u32::MAX));
+            let uid = KSUID::from_object_id(&oid).unwrap();
+            assert_eq!(uid.timestamp().timestamp(), i64::from(BigEndian::read_u32(&oid)));
+            assert_eq!(&uid.payload()[..8], &[0; 8]);
+            assert_eq!(uid.to_object_id().unwrap(), oid);
+        }
+    }
+
+    #[test]
+    fn test_object_id_order_and_range() {
+        let early = [0x54, 0, 0, 0, 9, 9, 9, 9, 9, 0, 0, 1];
+        let late = [0x60, 0, 0, 0, 1, 1, 1, 1, 1, 0, 0, 0];
+        assert!(KSUID::from_object_id(&early).unwrap() < KSUID::from_object_id(&late).unwrap());
+        // 2012, before the ksuid epoch.
+        let before_epoch = [0x4f, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            KSUID::from_object_id(&before_epoch).unwrap_err(),
+            errors::KSUIDError::TimestampOutOfRange { unix_seconds: 0x4f00_0000 },
+        );
+        let last = KSUID([0xff; 20]);
+        assert!(last.to_object_id().is_err());
+        // Other ids lose their leading payload bytes.
+        let uid = KSUID::new();
+        let back = KSUID::from_object_id(&uid.to_object_id().unwrap()).unwrap();
+        assert_eq!((back.timestamp(), &back.0[12..]), (uid.timestamp(), &uid.0[12..]));
+    }
+}
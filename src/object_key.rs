@@ -0,0 +1,135 @@
+use chrono::prelude::Utc;
+use chrono::{DateTime, Duration, Timelike};
+use datetime_ext::KsuidDateTimeExt;
+use ksuid::KSUID;
+use std::ops::Range;
+
+// Path layout of hierarchical keys, also used for the listing prefixes.
+const HOUR_FORMAT: &str = "%Y/%m/%d/%H/";
+const DAY_FORMAT: &str = "%Y/%m/%d/";
+
+/// Object store key helpers. Keys are either hierarchical by creation hour, which lets a time
+/// range be listed with a handful of prefixes, or fanned out by the leading base62 characters,
+/// which spreads writes across partitions while keeping keys sorted by time.
+impl KSUID {
+
+    /// Return a key of the form `yyyy/mm/dd/hh/<base62>` using the id's UTC creation hour.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+    /// assert_eq!(uid.to_object_key(), "2017/10/10/03/0ujsszwN8NRY24YaXiTIE2VWDTS");
+    /// ```
+    pub fn to_object_key(&self) -> String {
+        format!("{}{}", self.timestamp().format(HOUR_FORMAT), self)
+    }
+
+    /// Return a key of the form `<first n chars>/<base62>`. Since the leading characters are
+    /// dominated by the timestamp, the prefix groups ids created close together.
+    /// Panics if `n` is greater than 27.
+    pub fn to_fanout_key(&self, n: usize) -> String {
+        let encoded = self.to_base62();
+        format!("{}/{}", &encoded[..n], encoded)
+    }
+}
+
+/// Return the minimal set of prefixes that together list every `to_object_key` key created in
+/// `range` (end exclusive). Whole UTC days collapse to a `yyyy/mm/dd/` prefix; partial days are
+/// listed hour by hour. Listings may include keys slightly outside the range, at hour granularity.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::{TimeZone, Utc};
+///
+/// let start = Utc.with_ymd_and_hms(2017, 10, 9, 22, 0, 0).unwrap();
+/// let end = Utc.with_ymd_and_hms(2017, 10, 11, 1, 30, 0).unwrap();
+/// assert_eq!(ksuid::object_key_prefixes(start..end), vec![
+///     "2017/10/09/22/", "2017/10/09/23/", "2017/10/10/", "2017/10/11/00/", "2017/10/11/01/",
+/// ]);
+/// ```
+pub fn object_key_prefixes(range: Range<DateTime<Utc>>) -> Vec<String> {
+    let mut prefixes = Vec::new();
+    let mut cursor = range.start.with_minute(0).unwrap().with_second(0).unwrap().with_nanosecond(0).unwrap();
+    while cursor < range.end {
+        if cursor.hour() == 0 && cursor + Duration::days(1) <= range.end {
+            prefixes.push(cursor.format(DAY_FORMAT).to_string());
+            cursor += Duration::days(1);
+        } else {
+            prefixes.push(cursor.format(HOUR_FORMAT).to_string());
+            cursor += Duration::hours(1);
+        }
+    }
+    prefixes
+}
+
+/// Return the longest prefix shared by every base62 encoded id created in `range` (end
+/// exclusive), suitable for listing `to_fanout_key` keys or raw base62 keys. Times outside the
+/// representable range clamp to its first and last seconds, as with `KsuidDateTimeExt`.
+pub fn base62_prefix_for_range(range: Range<DateTime<Utc>>) -> String {
+    if range.start >= range.end {
+        return String::new();
+    }
+    // The last second with any time in the range: the one `range.end` falls in, unless it falls
+    // right on its start.
+    let last = match range.end.nanosecond() {
+        0 => range.end - Duration::seconds(1),
+        _ => range.end.with_nanosecond(0).unwrap(),
+    };
+    let low = range.start.min_ksuid().to_base62();
+    let high = last.max_ksuid().to_base62();
+    low.bytes()
+        .zip(high.bytes())
+        .take_while(|&(a, b)| a == b)
+        .map(|(a, _)| a as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_object_key() {
+        let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+        assert_eq!(uid.to_fanout_key(4), "0ujs/0ujsszwN8NRY24YaXiTIE2VWDTS");
+        assert!(uid.to_object_key().ends_with("/0ujsszwN8NRY24YaXiTIE2VWDTS"));
+    }
+
+    #[test]
+    fn test_object_key_prefixes() {
+        let start = Utc.with_ymd_and_hms(2017, 10, 10, 4, 59, 59).unwrap();
+        let end = Utc.with_ymd_and_hms(2017, 10, 10, 5, 0, 1).unwrap();
+        assert_eq!(object_key_prefixes(start..end), vec!["2017/10/10/04/", "2017/10/10/05/"]);
+        assert!(object_key_prefixes(end..start).is_empty());
+    }
+
+    #[test]
+    fn test_base62_prefix_for_range() {
+        let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+        let prefix = base62_prefix_for_range(uid.timestamp()..uid.timestamp() + Duration::seconds(1));
+        assert!(uid.to_base62().starts_with(&prefix));
+        assert!(prefix.len() >= 4);
+        let wide = base62_prefix_for_range(uid.timestamp()..uid.timestamp() + Duration::days(30));
+        assert!(prefix.starts_with(&wide) && wide.len() < prefix.len());
+        // An end partway through a second still covers that second.
+        let start = Utc.timestamp_opt(1_500_000_000, 0).unwrap();
+        let last = KSUID::from_parts(start + Duration::seconds(1), &[0xff; 16]).unwrap();
+        assert!(!last.to_base62().starts_with(&base62_prefix_for_range(start..start + Duration::seconds(1))));
+        let prefix = base62_prefix_for_range(start..start + Duration::milliseconds(1001));
+        assert!(last.to_base62().starts_with(&prefix));
+    }
+
+    #[test]
+    fn test_base62_prefix_for_range_clamps() {
+        let before = Utc.timestamp_opt(1_000_000_000, 0).unwrap();
+        let prefix = base62_prefix_for_range(before..KSUID::EPOCH + Duration::seconds(1));
+        assert!(KSUID::default().to_base62().starts_with(&prefix));
+        let after = KSUID::MAX_TIMESTAMP + Duration::days(1);
+        let prefix = base62_prefix_for_range(KSUID::MAX_TIMESTAMP..after);
+        assert!(KSUID::from_parts(KSUID::MAX_TIMESTAMP, &[0x42; 16]).unwrap().to_base62().starts_with(&prefix));
+        assert_eq!(base62_prefix_for_range(before..after), "");
+    }
+}
@@ -0,0 +1,97 @@
+use http::HeaderValue;
+use ksuid::KSUID;
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseFromParameter, ParseResult, ToHeader, ToJSON, Type};
+use schema::PATTERN;
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// poem-openapi integration: ids are documented as a `string` of format `ksuid` with the
+/// length and pattern from `ksuid::schema`, and request values are parsed with `from_base62`,
+/// so malformed ids fail poem's validation with the parse error.
+impl Type for KSUID {
+    const IS_REQUIRED: bool = true;
+
+    type RawValueType = Self;
+
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        "string_ksuid".into()
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            min_length: Some(KSUID::ENCODED_LENGTH),
+            max_length: Some(KSUID::ENCODED_LENGTH),
+            pattern: Some(PATTERN.to_owned()),
+            ..MetaSchema::new_with_format("string", "ksuid")
+        }))
+    }
+
+    fn as_raw_value(&self) -> Option<&Self> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(&'a self) -> Box<dyn Iterator<Item = &'a Self> + 'a> {
+        Box::new(self.as_raw_value().into_iter())
+    }
+}
+
+impl ParseFromJSON for KSUID {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        match value.unwrap_or_default() {
+            Value::String(s) => s.parse().map_err(ParseError::custom),
+            value => Err(ParseError::expected_type(value)),
+        }
+    }
+}
+
+impl ParseFromParameter for KSUID {
+    fn parse_from_parameter(value: &str) -> ParseResult<Self> {
+        value.parse().map_err(ParseError::custom)
+    }
+}
+
+impl ToJSON for KSUID {
+    fn to_json(&self) -> Option<Value> {
+        Some(Value::String(self.to_base62()))
+    }
+}
+
+impl ToHeader for KSUID {
+    fn to_header(&self) -> Option<HeaderValue> {
+        HeaderValue::from_bytes(&self.to_base62_array()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_schema() {
+        assert_eq!(KSUID::name(), "string_ksuid");
+        match KSUID::schema_ref() {
+            MetaSchemaRef::Inline(schema) => {
+                assert_eq!((schema.ty, schema.format), ("string", Some("ksuid")));
+                assert_eq!((schema.min_length, schema.max_length), (Some(27), Some(27)));
+                assert_eq!(schema.pattern.as_deref(), Some(PATTERN));
+            }
+            other => panic!("unexpected schema {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_openapi_parse() {
+        let uid = KSUID::new();
+        assert_eq!(uid.to_json(), Some(json!(uid.to_base62())));
+        assert_eq!(KSUID::parse_from_json(uid.to_json()).unwrap(), uid);
+        assert_eq!(KSUID::parse_from_parameter(&uid.to_base62()).unwrap(), uid);
+        assert_eq!(uid.to_header().unwrap(), uid.to_base62());
+        assert!(KSUID::parse_from_json(Some(json!("0ujsszwN8NRY24YaXiTIE2VWDT"))).is_err());
+        assert!(KSUID::parse_from_json(Some(json!(42))).is_err());
+        assert!(KSUID::parse_from_json(None).is_err());
+        assert!(KSUID::parse_from_parameter("nope").is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use errors;
+use ksuid::{BYTE_LENGTH, KSUID, TIMESTAMP_LENGTH};
+use opentelemetry::trace::{SpanId, TraceId};
+
+/// OpenTelemetry correlation. A request's trace and span ids are derived from its KSUID rather
+/// than generated separately, so either can be found from the other without storing a mapping.
+///
+/// The trace id is the 16 byte random payload, which is what W3C trace context expects of a
+/// trace id, and together with the creation time gives the KSUID back. The span id is the last
+/// 8 payload bytes, the same bytes `shard_of` reads; it can't be reversed on its own.
+///
+/// A nil payload (as in `KSUID::nil()`) gives the all zero ids OpenTelemetry treats as invalid.
+impl KSUID {
+
+    /// Return the trace id derived from this id.
+    /// # Example
+    /// ```
+    /// extern crate ksuid;
+    /// extern crate opentelemetry;
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// let trace_id = uid.trace_id();
+    /// assert_eq!(KSUID::from_trace_id(trace_id, uid.timestamp()).unwrap(), uid);
+    /// ```
+    pub fn trace_id(&self) -> TraceId {
+        let mut bytes = [0u8; BYTE_LENGTH - TIMESTAMP_LENGTH];
+        bytes.copy_from_slice(self.payload());
+        TraceId::from_bytes(bytes)
+    }
+
+    /// Return the span id derived from this id.
+    pub fn span_id(&self) -> SpanId {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.0[BYTE_LENGTH - 8..]);
+        SpanId::from_bytes(bytes)
+    }
+
+    /// Rebuild the id `trace_id` was derived from, given its creation time (to the second).
+    /// Fails if `timestamp` is outside the ksuid range.
+    pub fn from_trace_id(trace_id: TraceId, timestamp: DateTime<Utc>) -> Result<Self, errors::KSUIDError> {
+        KSUID::from_parts(timestamp, &trace_id.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_trace_and_span_ids() {
+        let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+        assert_eq!(uid.trace_id().to_string(), "d9719f3234fe964b6376cb6b9a2e5e86");
+        assert_eq!(uid.span_id().to_string(), "6376cb6b9a2e5e86");
+        assert_eq!(KSUID::nil().trace_id(), TraceId::INVALID);
+        assert_eq!(KSUID::nil().span_id(), SpanId::INVALID);
+    }
+
+    #[test]
+    fn test_from_trace_id() {
+        let uid = KSUID::new();
+        assert_eq!(KSUID::from_trace_id(uid.trace_id(), uid.timestamp()).unwrap(), uid);
+        // Only the second matters, so the trace's start time works if it's the same second.
+        let later = uid.timestamp() + Duration::milliseconds(400);
+        assert_eq!(KSUID::from_trace_id(uid.trace_id(), later).unwrap().payload(), uid.payload());
+        assert!(KSUID::from_trace_id(uid.trace_id(), DateTime::from_timestamp(0, 0).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+use ksuid::KSUID;
+use rayon::prelude::*;
+
+/// Generate `n` ids across the rayon thread pool, returned sorted and without duplicates.
+/// Meant for bulk synthetic data; each id is minted exactly as by `KSUID::new()`.
+/// # Example
+/// ```
+/// let ids = ksuid::generate_parallel(10_000);
+/// assert_eq!(ids.len(), 10_000);
+/// assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+/// ```
+pub fn generate_parallel(n: usize) -> Vec<KSUID> {
+    let mut ids: Vec<KSUID> = Vec::with_capacity(n);
+    // A collision needs two identical 128 bit payloads in the same second, so this loop runs a
+    // second time practically never; it is here so the length is still exactly `n` if it does.
+    while ids.len() < n {
+        let missing = n - ids.len();
+        ids.par_extend((0..missing).into_par_iter().map(|_| KSUID::new()));
+        ids.par_sort_unstable();
+        ids.dedup();
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_parallel() {
+        assert!(generate_parallel(0).is_empty());
+        let ids = generate_parallel(100_000);
+        assert_eq!(ids.len(), 100_000);
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -0,0 +1,83 @@
+//! proptest strategies that respect the structure of a `KSUID` (a timestamp followed by a random
+//! payload), so properties can be checked over realistic, time-bounded ids.
+use byteorder::{BigEndian, ByteOrder};
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use ksuid::{to_ksuid_time, KSUID};
+use proptest_crate::arbitrary::{any, Arbitrary};
+use proptest_crate::strategy::{BoxedStrategy, Strategy};
+use std::ops::Range;
+
+fn assemble(t: u32, payload: [u8; 16]) -> KSUID {
+    let mut bytes = [0u8; 20];
+    BigEndian::write_u32(&mut bytes, t);
+    bytes[4..].copy_from_slice(&payload);
+    KSUID(bytes)
+}
+
+/// Generate any `KSUID`, with a timestamp anywhere in the representable range.
+pub fn any_ksuid() -> impl Strategy<Value = KSUID> {
+    (any::<u32>(), any::<[u8; 16]>()).prop_map(|(t, payload)| assemble(t, payload))
+}
+
+/// Generate `KSUID`s whose timestamps fall within `range` (end exclusive, second resolution),
+/// with arbitrary payloads. Panics if the range holds no whole second representable by a ksuid.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// extern crate proptest;
+/// use chrono::{Duration, Utc};
+/// use proptest::strategy::{Strategy, ValueTree};
+/// use proptest::test_runner::TestRunner;
+///
+/// let now = Utc::now();
+/// let strategy = ksuid::proptest::ksuid_in_range(now - Duration::days(1)..now);
+/// let id = strategy.new_tree(&mut TestRunner::default()).unwrap().current();
+/// assert!(id.timestamp() < now);
+/// ```
+pub fn ksuid_in_range(range: Range<DateTime<Utc>>) -> impl Strategy<Value = KSUID> {
+    let start = to_ksuid_time(range.start.max(KSUID::default().timestamp()));
+    let end = to_ksuid_time(range.end);
+    assert!(start < end, "empty ksuid timestamp range");
+    (start..end, any::<[u8; 16]>()).prop_map(|(t, payload)| assemble(t, payload))
+}
+
+/// Makes `any::<KSUID>()` available, equivalent to `any_ksuid()`.
+impl Arbitrary for KSUID {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<KSUID>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any_ksuid().boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use proptest_crate::test_runner::TestRunner;
+
+    #[test]
+    fn test_roundtrip_property() {
+        TestRunner::default()
+            .run(&any::<KSUID>(), |id| {
+                assert_eq!(KSUID::from_base62(&id.to_base62()).unwrap(), id);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_in_range() {
+        let start = Utc::now() - Duration::hours(2);
+        let end = start + Duration::hours(1);
+        TestRunner::default()
+            .run(&ksuid_in_range(start..end), |id| {
+                assert!(id.timestamp() > start - Duration::seconds(1) && id.timestamp() < end);
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+use base62;
+use byteorder::{BigEndian, ByteOrder};
+use errors;
+use ksuid::{KSUID, TIMESTAMP_LENGTH};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+const TIMESTAMP_OUT_LENGTH: usize = 8;
+const BYTE_LENGTH: usize = TIMESTAMP_OUT_LENGTH + 16;
+
+// Length of the base62 encoded string version
+const ENCODED_LENGTH: usize = 33;
+
+/// Secret parameters for the `PublicId` transformation, derived from an arbitrary key.
+///
+/// The timestamp `t` maps to `scale * t + offset + (prf(t) % scale)`, which is strictly
+/// increasing in `t` (so order is preserved) and invertible by integer division, while hiding the
+/// absolute creation time and the scale of time between ids. It is *not* strong order-preserving
+/// encryption: anyone who learns the real creation times of two public ids can solve for `scale`
+/// and `offset`. Treat it as obfuscation against casual inspection and volume estimation.
+#[derive(Clone)]
+pub struct PublicIdKey {
+    scale: u64,
+    offset: u64,
+    secret: [u8; 32],
+}
+
+impl PublicIdKey {
+
+    /// Derive the transformation parameters from `key`.
+    pub fn new(key: &[u8]) -> Self {
+        let mut secret = [0u8; 32];
+        secret.copy_from_slice(&Sha256::new().chain_update(b"ksuid-public-id").chain_update(key).finalize());
+        // scale lands in [2^20, 2^21) and offset below 2^63, so neither the multiply nor the add
+        // can overflow for any u32 timestamp.
+        let scale = (1 << 20) | (BigEndian::read_u64(&secret[0..]) >> 44);
+        let offset = BigEndian::read_u64(&secret[8..]) >> 1;
+        PublicIdKey { scale, offset, secret }
+    }
+
+    fn jitter(&self, t: u32) -> u64 {
+        let digest = Sha256::new().chain_update(self.secret).chain_update(t.to_be_bytes()).finalize();
+        BigEndian::read_u64(&digest) % self.scale
+    }
+
+    fn forward(&self, t: u32) -> u64 {
+        self.scale * u64::from(t) + self.offset + self.jitter(t)
+    }
+
+    fn backward(&self, value: u64) -> Option<u32> {
+        let t = value.checked_sub(self.offset)? / self.scale;
+        if t > u64::from(u32::MAX) {
+            return None;
+        }
+        let t = t as u32;
+        if self.forward(t) == value { Some(t) } else { None }
+    }
+}
+
+impl fmt::Debug for PublicIdKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("PublicIdKey(..)")
+    }
+}
+
+/// An externally visible form of a `KSUID`: the timestamp is passed through a keyed,
+/// order-preserving transformation (see `PublicIdKey`) and the random payload is kept as is.
+/// Public ids sort in the same order as the ksuids they were made from, both as bytes and as
+/// their 33 character base62 strings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PublicId(pub [u8; BYTE_LENGTH]);
+
+impl fmt::Display for PublicId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.to_base62())
+    }
+}
+
+impl PublicId {
+
+    /// Obfuscate a `KSUID` under `key`.
+    /// # Example
+    /// ```
+    /// use ksuid::{KSUID, PublicId, PublicIdKey};
+    ///
+    /// let key = PublicIdKey::new(b"public id key");
+    /// let uid = KSUID::new();
+    /// let public = PublicId::from_ksuid(&uid, &key);
+    /// let parsed = PublicId::from_base62(&public.to_base62()).unwrap();
+    /// assert_eq!(parsed.to_ksuid(&key).unwrap(), uid);
+    /// ```
+    pub fn from_ksuid(uid: &KSUID, key: &PublicIdKey) -> Self {
+        let mut bytes = [0u8; BYTE_LENGTH];
+        BigEndian::write_u64(&mut bytes, key.forward(BigEndian::read_u32(&uid.0)));
+        bytes[TIMESTAMP_OUT_LENGTH..].copy_from_slice(uid.payload());
+        PublicId(bytes)
+    }
+
+    /// Recover the original `KSUID`. Fails if the id was not produced under `key`.
+    pub fn to_ksuid(&self, key: &PublicIdKey) -> Result<KSUID, errors::KSUIDError> {
+        let t = key.backward(BigEndian::read_u64(&self.0)).ok_or(errors::KSUIDError::InvalidPublicId)?;
+        let mut bytes = [0u8; 20];
+        BigEndian::write_u32(&mut bytes, t);
+        bytes[TIMESTAMP_LENGTH..].copy_from_slice(&self.0[TIMESTAMP_OUT_LENGTH..]);
+        Ok(KSUID(bytes))
+    }
+
+    /// Parse a public id from its base62 form. This does not need the key; use `to_ksuid` to
+    /// check it against one.
+    pub fn from_base62(string: &str) -> Result<Self, errors::KSUIDError> {
+        let bytes = base62::decode_bytes(string.as_bytes(), ENCODED_LENGTH, BYTE_LENGTH)?;
+        let mut arr = [0u8; BYTE_LENGTH];
+        arr.copy_from_slice(&bytes);
+        Ok(PublicId(arr))
+    }
+
+    /// Encode the public id as a 33 character base62 `String`
+    pub fn to_base62(&self) -> String {
+        base62::encode_bytes(&self.0, ENCODED_LENGTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ksuid::from_ksuid_time;
+
+    #[test]
+    fn test_public_roundtrip() {
+        let key = PublicIdKey::new(b"key");
+        let uid = KSUID::new();
+        let public = PublicId::from_ksuid(&uid, &key);
+        assert_eq!(public.to_ksuid(&key).unwrap(), uid);
+        assert!(public.to_ksuid(&PublicIdKey::new(b"other")).is_err());
+    }
+
+    #[test]
+    fn test_public_order_preserved() {
+        let key = PublicIdKey::new(b"key");
+        let mut last: Option<PublicId> = None;
+        for t in (0..2000).chain(u32::MAX - 10..u32::MAX) {
+            let uid = KSUID::from_parts(from_ksuid_time(t), &[0x80; 16]).unwrap();
+            let public = PublicId::from_ksuid(&uid, &key);
+            if let Some(prev) = last {
+                assert!(prev < public);
+                assert!(prev.to_base62() < public.to_base62());
+            }
+            last = Some(public);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::time::{Duration, Instant};
+
+/// A token bucket refilling at `rate` tokens per second and holding at most one second's worth,
+/// so a limiter that sat idle allows a burst of `rate` ids and no more.
+#[derive(Clone, Debug)]
+pub(crate) struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket. Panics if `per_second` is zero.
+    pub(crate) fn new(per_second: u32) -> Self {
+        assert!(per_second > 0, "rate limit must allow at least one id per second");
+        TokenBucket { rate: f64::from(per_second), tokens: f64::from(per_second), last_refill: Instant::now() }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+    }
+
+    /// Take `n` tokens if they are all available, otherwise take none and return how long until
+    /// they would be. A request larger than the bucket never succeeds.
+    pub(crate) fn try_take(&mut self, n: usize, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        let n = n as f64;
+        if n <= self.tokens {
+            self.tokens -= n;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((n - self.tokens) / self.rate))
+        }
+    }
+
+    /// Take `n` tokens unconditionally, going into debt if need be, and return how long the caller
+    /// must wait for the debt to be repaid.
+    pub(crate) fn take(&mut self, n: usize, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= n as f64;
+        if self.tokens < 0.0 {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let mut bucket = TokenBucket::new(10);
+        let start = bucket.last_refill;
+        assert!(bucket.try_take(10, start).is_ok());
+        assert_eq!(bucket.try_take(1, start), Err(Duration::from_millis(100)));
+        // Half a second refills five tokens, and a failed take leaves them in place.
+        let later = start + Duration::from_millis(500);
+        assert!(bucket.try_take(6, later).is_err());
+        assert!(bucket.try_take(5, later).is_ok());
+        // Blocking takes borrow against the future.
+        assert_eq!(bucket.take(3, later), Duration::from_millis(300));
+        assert!(bucket.try_take(1, later + Duration::from_millis(350)).is_err());
+        assert!(bucket.try_take(1, later + Duration::from_millis(500)).is_ok());
+        // Idle time never banks more than a second's worth.
+        let idle = later + Duration::from_secs(60);
+        assert!(bucket.try_take(11, idle).is_err());
+        assert_eq!(bucket.take(10, idle), Duration::from_secs(0));
+    }
+}
@@ -0,0 +1,38 @@
+use ksuid::BYTE_LENGTH;
+use serde_crate::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// The raw id bytes, serialized as a byte string rather than serde's default for arrays, a
+/// sequence of integers. Shared by the binary formats.
+pub(crate) struct RawBytes(pub(crate) [u8; BYTE_LENGTH]);
+
+impl Serialize for RawBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(RawBytesVisitor)
+    }
+}
+
+struct RawBytesVisitor;
+
+impl<'de> de::Visitor<'de> for RawBytesVisitor {
+    type Value = RawBytes;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a {} byte string", BYTE_LENGTH)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<RawBytes, E> {
+        if v.len() != BYTE_LENGTH {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        let mut bytes = [0u8; BYTE_LENGTH];
+        bytes.copy_from_slice(v);
+        Ok(RawBytes(bytes))
+    }
+}
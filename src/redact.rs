@@ -0,0 +1,66 @@
+use ksuid::KSUID;
+use std::fmt;
+
+// How many leading base62 characters survive redaction. The first six characters are almost
+// entirely determined by the timestamp, so they keep ids roughly identifiable by time.
+const VISIBLE_CHARS: usize = 6;
+
+/// Display adapter printing only the leading, timestamp-dominated part of a `KSUID` followed by a
+/// mask, e.g. `0ujssz…****`. Use it when logging ids that double as secrets.
+/// With the `redacted-debug` feature, `KSUID`'s own `Debug` impl uses this format as well.
+/// `Display` always prints the full id since it is the canonical text form.
+/// # Example
+/// ```
+/// use ksuid::KSUID;
+///
+/// let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+/// assert_eq!(uid.redacted().to_string(), "0ujssz…****");
+/// ```
+pub struct Redacted<'a>(&'a KSUID);
+
+impl<'a> fmt::Display for Redacted<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let encoded = self.0.to_base62();
+        f.pad(&format!("{}…****", &encoded[..VISIBLE_CHARS]))
+    }
+}
+
+impl<'a> fmt::Debug for Redacted<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "KSUID({})", self)
+    }
+}
+
+#[cfg(feature = "redacted-debug")]
+impl fmt::Debug for KSUID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.redacted(), f)
+    }
+}
+
+impl KSUID {
+    /// Return a display adapter that masks the random payload. See `Redacted`.
+    pub fn redacted(&self) -> Redacted<'_> {
+        Redacted(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted() {
+        let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+        assert_eq!(format!("{}", uid.redacted()), "0ujssz…****");
+        assert_eq!(format!("{:?}", uid.redacted()), "KSUID(0ujssz…****)");
+        assert!(!format!("{:?}", uid.redacted()).contains("wN8NRY"));
+    }
+
+    #[cfg(feature = "redacted-debug")]
+    #[test]
+    fn test_redacted_debug_feature() {
+        let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+        assert_eq!(format!("{:?}", uid), "KSUID(0ujssz…****)");
+    }
+}
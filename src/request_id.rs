@@ -0,0 +1,142 @@
+use http::header::HeaderName;
+use http::{HeaderValue, Request};
+use ksuid::KSUID;
+use std::task::{Context, Poll};
+use tower_http::request_id::{MakeRequestId, RequestId};
+use tower_layer::Layer;
+use tower_service::Service;
+
+static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+fn header_value(id: &KSUID) -> HeaderValue {
+    // Base62 is always a valid header value.
+    HeaderValue::from_bytes(&id.to_base62_array()).unwrap()
+}
+
+/// `MakeRequestId` that mints a fresh KSUID per request, for tower-http's `SetRequestIdLayer`:
+/// `SetRequestIdLayer::x_request_id(MakeRequestKsuid)`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MakeRequestKsuid;
+
+impl MakeRequestId for MakeRequestKsuid {
+    fn make_request_id<B>(&mut self, _request: &Request<B>) -> Option<RequestId> {
+        Some(RequestId::new(header_value(&KSUID::new())))
+    }
+}
+
+/// Layer giving every request a KSUID request id. An incoming `x-request-id` that is already a
+/// KSUID is kept, so ids carry across services; anything else is replaced with a fresh id. The
+/// id is written to `x-request-id` and inserted into the request extensions both as a `KSUID`
+/// and as tower-http's `RequestId`, so `PropagateRequestIdLayer` can copy it onto the response.
+/// # Example
+/// ```
+/// extern crate ksuid;
+/// extern crate tower_http;
+/// use ksuid::KsuidRequestIdLayer;
+/// use tower_http::request_id::PropagateRequestIdLayer;
+///
+/// // With tower's ServiceBuilder, outermost first:
+/// // ServiceBuilder::new()
+/// //     .layer(KsuidRequestIdLayer)
+/// //     .layer(PropagateRequestIdLayer::x_request_id())
+/// //     .service(app)
+/// // and in a handler: `request.extensions().get::<KSUID>()`.
+/// let _layers = (KsuidRequestIdLayer, PropagateRequestIdLayer::x_request_id());
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KsuidRequestIdLayer;
+
+impl<S> Layer<S> for KsuidRequestIdLayer {
+    type Service = KsuidRequestId<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        KsuidRequestId { inner }
+    }
+}
+
+/// Service returned by `KsuidRequestIdLayer`.
+#[derive(Clone, Debug)]
+pub struct KsuidRequestId<S> {
+    inner: S,
+}
+
+impl<S, B> Service<Request<B>> for KsuidRequestId<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        let incoming = request.headers().get(&X_REQUEST_ID).and_then(|v| KSUID::from_base62_bytes(v.as_bytes()).ok());
+        // Not `unwrap_or_default`: the default id is nil, not a fresh one.
+        let id = match incoming {
+            Some(id) => id,
+            None => KSUID::new(),
+        };
+        let value = header_value(&id);
+        request.headers_mut().insert(X_REQUEST_ID.clone(), value.clone());
+        request.extensions_mut().insert(RequestId::new(value));
+        request.extensions_mut().insert(id);
+        self.inner.call(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+
+    // Hands the request it was given straight back.
+    struct Echo;
+
+    impl Service<Request<()>> for Echo {
+        type Response = Request<()>;
+        type Error = Infallible;
+        type Future = Ready<Result<Request<()>, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<()>) -> Self::Future {
+            ready(Ok(request))
+        }
+    }
+
+    fn send(request: Request<()>) -> Request<()> {
+        KsuidRequestIdLayer.layer(Echo).call(request).into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_mints_request_id() {
+        let request = send(Request::new(()));
+        let id = *request.extensions().get::<KSUID>().unwrap();
+        assert_eq!(request.headers()["x-request-id"], id.to_base62());
+        assert_eq!(request.extensions().get::<RequestId>().unwrap().header_value(), &id.to_base62());
+    }
+
+    #[test]
+    fn test_keeps_incoming_ksuid_only() {
+        let upstream = KSUID::new();
+        let request = send(Request::builder().header("x-request-id", upstream.to_base62()).body(()).unwrap());
+        assert_eq!(request.extensions().get::<KSUID>(), Some(&upstream));
+
+        let request = send(Request::builder().header("x-request-id", "not-a-ksuid").body(()).unwrap());
+        let id = *request.extensions().get::<KSUID>().unwrap();
+        assert_ne!(id, upstream);
+        assert_eq!(request.headers()["x-request-id"], id.to_base62());
+    }
+
+    #[test]
+    fn test_make_request_ksuid() {
+        let id = MakeRequestKsuid.make_request_id(&Request::new(())).unwrap();
+        assert!(KSUID::from_base62_bytes(id.header_value().as_bytes()).is_ok());
+    }
+}
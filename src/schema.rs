@@ -0,0 +1,78 @@
+//! Validation patterns and schema snippets for columns and fields holding base62 encoded ids, so
+//! every validation layer agrees on what a ksuid looks like.
+//!
+//! The regular expression only checks shape. A handful of 27 character strings above
+//! `aWgEPTl1tmebfsQzFP4bxwgy80V` (the largest id) match it but don't decode; the SQL constraints
+//! also rule those out, and `ksuid::is_valid` is the exact check.
+use ksuid::MAX_STRING_ENCODED;
+
+/// Anchored regular expression matching the 27 character base62 form.
+pub const PATTERN: &str = "^[0-9A-Za-z]{27}$";
+
+/// SQL dialects `sql_check` can generate a constraint for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SqlDialect {
+    Postgres,
+    /// MySQL 8.0 or later, which has `REGEXP_LIKE`.
+    MySql,
+    Sqlite,
+}
+
+/// Return a JSON Schema fragment for a string field holding a ksuid.
+/// # Example
+/// ```
+/// assert_eq!(
+///     ksuid::schema::json_schema(),
+///     r#"{"type":"string","minLength":27,"maxLength":27,"pattern":"^[0-9A-Za-z]{27}$"}"#,
+/// );
+/// ```
+pub fn json_schema() -> String {
+    format!(r#"{{"type":"string","minLength":27,"maxLength":27,"pattern":"{}"}}"#, PATTERN)
+}
+
+/// Return a `CHECK (...)` constraint for a text column holding ksuids. `column` is inserted
+/// verbatim, so quote it yourself if it needs quoting. The comparisons are case sensitive and
+/// byte ordered in every dialect, whatever the column's collation.
+/// # Example
+/// ```
+/// use ksuid::schema::{sql_check, SqlDialect};
+///
+/// assert_eq!(
+///     sql_check("id", SqlDialect::Postgres),
+///     r#"CHECK (id ~ '^[0-9A-Za-z]{27}$' AND id COLLATE "C" <= 'aWgEPTl1tmebfsQzFP4bxwgy80V')"#,
+/// );
+/// ```
+pub fn sql_check(column: &str, dialect: SqlDialect) -> String {
+    match dialect {
+        SqlDialect::Postgres => format!(
+            r#"CHECK ({col} ~ '{pattern}' AND {col} COLLATE "C" <= '{max}')"#,
+            col = column, pattern = PATTERN, max = MAX_STRING_ENCODED,
+        ),
+        SqlDialect::MySql => format!(
+            "CHECK (REGEXP_LIKE({col}, '{pattern}', 'c') AND BINARY {col} <= '{max}')",
+            col = column, pattern = PATTERN, max = MAX_STRING_ENCODED,
+        ),
+        // SQLite has no built in regex operator; GLOB is case sensitive and compares bytes.
+        SqlDialect::Sqlite => format!(
+            "CHECK (length({col}) = 27 AND {col} NOT GLOB '*[^0-9A-Za-z]*' AND {col} <= '{max}')",
+            col = column, max = MAX_STRING_ENCODED,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_check() {
+        assert_eq!(
+            sql_check("`id`", SqlDialect::MySql),
+            "CHECK (REGEXP_LIKE(`id`, '^[0-9A-Za-z]{27}$', 'c') AND BINARY `id` <= 'aWgEPTl1tmebfsQzFP4bxwgy80V')",
+        );
+        assert_eq!(
+            sql_check("id", SqlDialect::Sqlite),
+            "CHECK (length(id) = 27 AND id NOT GLOB '*[^0-9A-Za-z]*' AND id <= 'aWgEPTl1tmebfsQzFP4bxwgy80V')",
+        );
+    }
+}
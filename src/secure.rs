@@ -0,0 +1,140 @@
+use base62;
+use byteorder::{BigEndian, ByteOrder};
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use errors;
+use ksuid::{checked_ksuid_time, from_ksuid_time, to_ksuid_time, TIMESTAMP_LENGTH};
+use rand;
+use rand::Rng;
+use std::fmt;
+
+const PAYLOAD_LENGTH: usize = 32;
+const BYTE_LENGTH: usize = TIMESTAMP_LENGTH + PAYLOAD_LENGTH;
+
+// Length of the base62 encoded string version
+const ENCODED_LENGTH: usize = 49;
+
+/// A `KSUID` variant carrying a 32 byte (256 bit) random payload, intended for session tokens
+/// and API keys. It shares the 4 byte timestamp prefix with `KSUID`, so values still sort by
+/// creation time, both as bytes and in their 49 character base62 form.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SecureKsuid(pub [u8; BYTE_LENGTH]);
+
+impl fmt::Display for SecureKsuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.to_base62())
+    }
+}
+
+impl SecureKsuid {
+
+    /// Create a new random `SecureKsuid` based on the current time and some random data
+    /// # Example
+    /// ```
+    /// use ksuid::SecureKsuid;
+    ///
+    /// let token = SecureKsuid::new();
+    /// assert_eq!(token.to_base62().len(), 49);
+    /// ```
+    pub fn new() -> Self {
+        let mut bytes = [0u8; BYTE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut bytes[TIMESTAMP_LENGTH..]);
+        BigEndian::write_u32(&mut bytes, to_ksuid_time(Utc::now()));
+        SecureKsuid(bytes)
+    }
+
+    /// Create a new `SecureKsuid` from it's raw components. Only the first 32 bytes of the
+    /// payload are used.
+    pub fn from_parts(ts: DateTime<Utc>, payload: &[u8]) -> Result<Self, errors::KSUIDError> {
+        if payload.len() < PAYLOAD_LENGTH {
+            return Err(errors::KSUIDError::SliceTooSmall{length: payload.len()})
+        }
+        let mut bytes = [0u8; BYTE_LENGTH];
+        BigEndian::write_u32(&mut bytes, checked_ksuid_time(ts)?);
+        bytes[TIMESTAMP_LENGTH..].clone_from_slice(&payload[..PAYLOAD_LENGTH]);
+        Ok(SecureKsuid(bytes))
+    }
+
+    /// Return a `SecureKsuid` built from the first 36 bytes of a byte slice.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, errors::KSUIDError> {
+        if bytes.len() < BYTE_LENGTH {
+            return Err(errors::KSUIDError::SliceTooSmall{length: bytes.len()})
+        }
+        let mut arr = [0u8; BYTE_LENGTH];
+        arr.copy_from_slice(&bytes[..BYTE_LENGTH]);
+        Ok(SecureKsuid(arr))
+    }
+
+    /// Parse a `SecureKsuid` from its 49 character base62 form.
+    /// # Example
+    /// ```
+    /// use ksuid::SecureKsuid;
+    ///
+    /// let token = SecureKsuid::new();
+    /// let other = SecureKsuid::from_base62(&token.to_base62()).unwrap();
+    /// assert_eq!(token, other);
+    /// ```
+    pub fn from_base62(string: &str) -> Result<Self, errors::KSUIDError> {
+        base62::decode_bytes(string.as_bytes(), ENCODED_LENGTH, BYTE_LENGTH).and_then(|bytes| {
+            Self::from_bytes(bytes.as_slice())
+        })
+    }
+
+    /// Return the timestamp portion of the id
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        from_ksuid_time(BigEndian::read_u32(&self.0))
+    }
+
+    /// Return the 32 byte random payload
+    pub fn payload(&self) -> &[u8] {
+        &self.0[TIMESTAMP_LENGTH..]
+    }
+
+    /// Encode the underlying bytes as a 49 character base62 `String`
+    pub fn to_base62(&self) -> String {
+        base62::encode_bytes(&self.0, ENCODED_LENGTH)
+    }
+
+    /// Return a reference to the bytes that make up the id.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Default for SecureKsuid {
+    fn default() -> Self {
+        SecureKsuid([0; BYTE_LENGTH])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secure_roundtrip() {
+        let zero = SecureKsuid::default();
+        assert_eq!(zero.to_base62(), "0".repeat(ENCODED_LENGTH));
+
+        let uid = SecureKsuid::new();
+        let other = SecureKsuid::from_base62(&uid.to_base62()).unwrap();
+        assert_eq!(uid, other);
+        assert_eq!(uid.payload().len(), PAYLOAD_LENGTH);
+    }
+
+    #[test]
+    fn test_secure_sort_order() {
+        let early = SecureKsuid::from_parts(from_ksuid_time(10), &[0xff; 32]).unwrap();
+        let late = SecureKsuid::from_parts(from_ksuid_time(11), &[0x00; 32]).unwrap();
+        assert!(early < late);
+        assert!(early.to_base62() < late.to_base62());
+    }
+
+    #[test]
+    fn test_secure_max_encodes() {
+        let max = SecureKsuid([0xff; BYTE_LENGTH]);
+        let encoded = max.to_base62();
+        assert_eq!(encoded.len(), ENCODED_LENGTH);
+        assert_eq!(SecureKsuid::from_base62(&encoded).unwrap(), max);
+    }
+}
@@ -0,0 +1,4 @@
+//! Helpers for `#[serde(with = "...")]` on `KSUID` fields, available with the `serde` feature.
+//! The binary formats have their own modules, `ksuid::cbor` and `ksuid::msgpack`.
+
+pub mod flexible;
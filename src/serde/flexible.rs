@@ -0,0 +1,138 @@
+//! Lenient `KSUID` fields for consumers that see several shapes of the same id, e.g. while
+//! producers migrate between representations. Deserializing accepts any of:
+//!
+//! * the 27 character base62 string,
+//! * a 40 character hex string (all lowercase or all uppercase),
+//! * the 20 raw bytes, as a byte string or a sequence of integers.
+//!
+//! Serializing always writes the base62 string. The input shape is found with
+//! `deserialize_any`, so this needs a self describing format (JSON, CBOR, MessagePack, ...).
+//!
+//! ```
+//! # extern crate ksuid;
+//! # extern crate serde;
+//! # extern crate serde_json;
+//! use ksuid::KSUID;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Event {
+//!     #[serde(with = "ksuid::serde::flexible")]
+//!     id: KSUID,
+//! }
+//!
+//! # fn main() {
+//! let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+//! for json in &[
+//!     r#"{"id": "0ujsszwN8NRY24YaXiTIE2VWDTS"}"#,
+//!     r#"{"id": "0669f605d9719f3234fe964b6376cb6b9a2e5e86"}"#,
+//!     r#"{"id": [6,105,246,5,217,113,159,50,52,254,150,75,99,118,203,107,154,46,94,134]}"#,
+//! ] {
+//!     let event: Event = serde_json::from_str(json).unwrap();
+//!     assert_eq!(event.id, uid);
+//! }
+//! # }
+//! ```
+
+use ksuid::{BYTE_LENGTH, KSUID};
+use multibase;
+use serde_crate::de::{self, Deserializer, SeqAccess, Visitor};
+use serde_crate::Serializer;
+use std::fmt;
+
+// Length of the hex form.
+const HEX_LENGTH: usize = BYTE_LENGTH * 2;
+
+/// Serialize `uid` as its base62 string.
+pub fn serialize<S: Serializer>(uid: &KSUID, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&uid.to_base62())
+}
+
+/// Deserialize a base62 string, hex string, or 20 raw bytes.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<KSUID, D::Error> {
+    deserializer.deserialize_any(FlexibleVisitor)
+}
+
+struct FlexibleVisitor;
+
+impl<'de> Visitor<'de> for FlexibleVisitor {
+    type Value = KSUID;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a base62 or hex ksuid string, or {} bytes", BYTE_LENGTH)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<KSUID, E> {
+        match v.len() {
+            KSUID::ENCODED_LENGTH => KSUID::from_base62(v).map_err(E::custom),
+            HEX_LENGTH => multibase::decode_hex(v)
+                .map(KSUID)
+                .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self)),
+            len => Err(E::invalid_length(len, &self)),
+        }
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<KSUID, E> {
+        if v.len() != BYTE_LENGTH {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        let mut bytes = [0u8; BYTE_LENGTH];
+        bytes.copy_from_slice(v);
+        Ok(KSUID(bytes))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<KSUID, A::Error> {
+        let mut bytes = [0u8; BYTE_LENGTH];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(BYTE_LENGTH + 1, &self));
+        }
+        Ok(KSUID(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_crate::{Deserialize, Serialize};
+    use serde_json;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Wrapper(#[serde(with = "super")] KSUID);
+
+    fn parse(value: serde_json::Value) -> Result<KSUID, serde_json::Error> {
+        serde_json::from_value::<Wrapper>(value).map(|w| w.0)
+    }
+
+    #[test]
+    fn test_flexible_shapes() {
+        let uid = KSUID::new();
+        assert_eq!(serde_json::to_value(Wrapper(uid)).unwrap(), json!(uid.to_base62()));
+        assert_eq!(parse(json!(uid.to_base62())).unwrap(), uid);
+        assert_eq!(parse(json!(uid.to_multibase(::Multibase::Base16)[1..])).unwrap(), uid);
+        assert_eq!(parse(json!(uid.to_multibase(::Multibase::Base16Upper)[1..])).unwrap(), uid);
+        assert_eq!(parse(json!(uid.0.to_vec())).unwrap(), uid);
+    }
+
+    #[test]
+    fn test_flexible_rejects() {
+        let uid = KSUID::new();
+        let hex = uid.to_multibase(::Multibase::Base16)[1..].to_owned();
+        let bad = [
+            json!(""),
+            json!(&hex[..39]),
+            json!(hex.replace(|c: char| c.is_ascii_digit(), "g")),
+            json!("0ujsszwN8NRY24YaXiTIE2VWDT!"),
+            json!(uid.0[..19].to_vec()),
+            json!(vec![0; 21]),
+            json!(vec![256; 20]),
+            json!(42),
+            json!("abababababababababababababababababABABAB"),
+        ];
+        for value in bad.iter() {
+            assert!(parse(value.clone()).is_err(), "{}", value);
+        }
+    }
+}
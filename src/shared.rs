@@ -0,0 +1,193 @@
+use byteorder::{BigEndian, ByteOrder};
+use chrono::prelude::Utc;
+use entropy;
+use errors::KSUIDError;
+use generator::{COUNTER_LENGTH, COUNTER_START_LIMIT};
+use ksuid::{to_ksuid_time, BYTE_LENGTH, EPOCH_START, KSUID, TIMESTAMP_LENGTH};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A `Send + Sync` generator meant to be shared by every thread of a service, typically from a
+/// `static` or an `Arc`, instead of wrapping a `Generator` in a `Mutex`.
+///
+/// Randomness always comes from a per-thread buffer, so threads never wait on each other for it.
+/// In the default mode nothing else is shared and throughput scales with the number of threads,
+/// at the cost of a `KSUID::new()` per id. In monotonic mode every id is
+/// strictly greater than all ids issued before it by this generator, using the same two byte
+/// counter layout as `GeneratorOptions::counter`. The last issued `(second, counter)` pair is kept
+/// in a single atomic, and claiming the next one is a compare-and-swap loop: there are no locks,
+/// but all threads contend on that one cache line, so total throughput stops growing once a few
+/// threads are minting.
+/// # Example
+/// ```
+/// use ksuid::SharedGenerator;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let gen = Arc::new(SharedGenerator::monotonic());
+/// let handles: Vec<_> = (0..4).map(|_| {
+///     let gen = gen.clone();
+///     thread::spawn(move || (0..100).map(|_| gen.next_id()).collect::<Vec<_>>())
+/// }).collect();
+/// for handle in handles {
+///     let ids = handle.join().unwrap();
+///     assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct SharedGenerator {
+    monotonic: bool,
+    // (ksuid seconds << 16) | counter of the last monotonic id.
+    state: AtomicU64,
+}
+
+impl SharedGenerator {
+
+    /// Create a generator whose ids are independent, equivalent to calling `KSUID::new()`.
+    pub const fn new() -> Self {
+        SharedGenerator { monotonic: false, state: AtomicU64::new(0) }
+    }
+
+    /// Create a generator whose ids are strictly increasing across all threads.
+    pub const fn monotonic() -> Self {
+        SharedGenerator { monotonic: true, state: AtomicU64::new(0) }
+    }
+
+    /// Whether this generator issues strictly increasing ids.
+    pub fn is_monotonic(&self) -> bool {
+        self.monotonic
+    }
+
+    /// Mint the next id. Panics if a monotonic generator runs out of ids in the last
+    /// representable second; use `try_next_id` to handle that.
+    pub fn next_id(&self) -> KSUID {
+        self.try_next_id().unwrap_or_else(|err| panic!("{}; use try_next_id to handle it", err))
+    }
+
+    /// Mint the next id, or fail with `KSUIDError::TimestampOutOfRange` if a monotonic generator
+    /// would have to borrow a second past the last representable one.
+    pub fn try_next_id(&self) -> Result<KSUID, KSUIDError> {
+        if !self.monotonic {
+            return Ok(KSUID::new());
+        }
+        let now = to_ksuid_time(Utc::now());
+        let mut current = self.state.load(Ordering::Relaxed);
+        let next = loop {
+            let next = advance(current, now)?;
+            match self.state.compare_exchange_weak(current, next, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => break next,
+                Err(actual) => current = actual,
+            }
+        };
+        let mut bytes = [0u8; BYTE_LENGTH];
+        entropy::fill(&mut bytes[TIMESTAMP_LENGTH + COUNTER_LENGTH..]);
+        BigEndian::write_u32(&mut bytes, (next >> 16) as u32);
+        BigEndian::write_u16(&mut bytes[TIMESTAMP_LENGTH..], next as u16);
+        Ok(KSUID(bytes))
+    }
+}
+
+// Mirror of `Generator::advance_counter` over the packed state. A clock that went backwards keeps
+// using the last issued second, and an exhausted counter borrows the next one.
+fn advance(state: u64, now: u32) -> Result<u64, KSUIDError> {
+    let last_time = (state >> 16) as u32;
+    let counter = state as u16;
+    let start = || u64::from(entropy::gen_u16_below(COUNTER_START_LIMIT));
+    if now > last_time {
+        Ok((u64::from(now) << 16) | start())
+    } else if counter == u16::MAX {
+        let next_time = last_time.checked_add(1).ok_or(KSUIDError::TimestampOutOfRange {
+            unix_seconds: EPOCH_START + i64::from(u32::MAX) + 1,
+        })?;
+        Ok((u64::from(next_time) << 16) | start())
+    } else {
+        Ok(state + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test::Bencher;
+    use super::*;
+    use generator::{Generator, GeneratorOptions};
+    use std::sync::Mutex;
+    use std::thread;
+
+    #[test]
+    fn test_shared_generator_monotonic_across_threads() {
+        let gen = SharedGenerator::monotonic();
+        let mut all: Vec<KSUID> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..8).map(|_| {
+                scope.spawn(|| {
+                    let ids: Vec<KSUID> = (0..10_000).map(|_| gen.next_id()).collect();
+                    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+                    ids
+                })
+            }).collect();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        });
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 80_000);
+    }
+
+    #[test]
+    fn test_shared_generator_counter_overflow_borrows_second() {
+        let now = to_ksuid_time(Utc::now());
+        let gen = SharedGenerator::monotonic();
+        gen.state.store((u64::from(now) << 16) | u64::from(u16::MAX), Ordering::Relaxed);
+        let next = gen.next_id();
+        assert_eq!(BigEndian::read_u32(next.as_bytes()), now + 1);
+    }
+
+    #[test]
+    fn test_shared_generator_counter_exhausts_last_second() {
+        let gen = SharedGenerator::monotonic();
+        let exhausted = (u64::from(u32::MAX) << 16) | u64::from(u16::MAX);
+        gen.state.store(exhausted, Ordering::Relaxed);
+        let err = gen.try_next_id().unwrap_err();
+        assert_eq!(err, KSUIDError::TimestampOutOfRange { unix_seconds: EPOCH_START + i64::from(u32::MAX) + 1 });
+        assert_eq!(gen.state.load(Ordering::Relaxed), exhausted);
+    }
+
+    #[test]
+    fn test_shared_generator_is_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SharedGenerator>();
+        assert!(!SharedGenerator::new().is_monotonic());
+        static SHARED: SharedGenerator = SharedGenerator::new();
+        assert_ne!(SHARED.next_id(), SHARED.next_id());
+    }
+
+    // Four threads minting 1000 ids each per iteration, all contending on one generator.
+    fn bench_contended<F: Fn() -> KSUID + Sync>(b: &mut Bencher, next_id: F) {
+        b.iter(|| {
+            thread::scope(|scope| {
+                for _ in 0..4 {
+                    scope.spawn(|| {
+                        for _ in 0..1000 {
+                            next_id();
+                        }
+                    });
+                }
+            })
+        });
+    }
+
+    #[bench]
+    fn bench_shared_generator_contended(b: &mut Bencher) {
+        let gen = SharedGenerator::new();
+        bench_contended(b, || gen.next_id());
+    }
+
+    #[bench]
+    fn bench_shared_generator_monotonic_contended(b: &mut Bencher) {
+        let gen = SharedGenerator::monotonic();
+        bench_contended(b, || gen.next_id());
+    }
+
+    #[bench]
+    fn bench_mutex_generator_contended(b: &mut Bencher) {
+        let gen = Mutex::new(Generator::with_options(GeneratorOptions { counter: true, ..Default::default() }).unwrap());
+        bench_contended(b, || gen.lock().unwrap().next_id());
+    }
+}
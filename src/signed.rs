@@ -0,0 +1,136 @@
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use errors;
+use hmac::{Hmac, Mac};
+use byteorder::{BigEndian, ByteOrder};
+use ksuid::{checked_ksuid_time, to_ksuid_time, KSUID, TIMESTAMP_LENGTH};
+use rand;
+use rand::Rng;
+use sha2::Sha256;
+use std::fmt;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// The payload is split into a random half and a truncated HMAC-SHA256 tag over
+// timestamp || random.
+const RANDOM_LENGTH: usize = 8;
+const TAG_OFFSET: usize = TIMESTAMP_LENGTH + RANDOM_LENGTH;
+
+/// A `KSUID` whose payload carries a truncated HMAC over its own timestamp and random bytes.
+/// It is still an ordinary 20 byte / 27 character KSUID on the wire, but ids that were not
+/// minted with the key (forged or enumerated ones) fail `verify`. The tradeoff is 64 bits of
+/// randomness instead of 128, plus a 64 bit tag.
+#[derive(Debug, PartialEq)]
+pub struct SignedKsuid(KSUID);
+
+fn tag(key: &[u8], signed: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(signed);
+    mac
+}
+
+impl fmt::Display for SignedKsuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl SignedKsuid {
+
+    /// Create a new signed id from the current time and some random data
+    /// # Example
+    /// ```
+    /// use ksuid::SignedKsuid;
+    ///
+    /// let uid = SignedKsuid::new(b"secret key");
+    /// let parsed = SignedKsuid::from_base62(&uid.to_string(), b"secret key").unwrap();
+    /// assert_eq!(uid, parsed);
+    /// assert!(SignedKsuid::from_base62(&uid.to_string(), b"other key").is_err());
+    /// ```
+    pub fn new(key: &[u8]) -> Self {
+        let mut random = [0u8; RANDOM_LENGTH];
+        rand::thread_rng().fill_bytes(&mut random);
+        Self::sign(to_ksuid_time(Utc::now()), &random, key)
+    }
+
+    /// Create a signed id from a timestamp and 8 bytes of caller supplied randomness. Fails with
+    /// `KSUIDError::TimestampOutOfRange` if `ts` is outside the range a ksuid can represent.
+    pub fn from_parts(ts: DateTime<Utc>, random: &[u8; RANDOM_LENGTH], key: &[u8]) -> Result<Self, errors::KSUIDError> {
+        Ok(Self::sign(checked_ksuid_time(ts)?, random, key))
+    }
+
+    fn sign(time: u32, random: &[u8; RANDOM_LENGTH], key: &[u8]) -> Self {
+        let mut uid = KSUID::default();
+        BigEndian::write_u32(&mut uid.0, time);
+        uid.0[TIMESTAMP_LENGTH..TAG_OFFSET].copy_from_slice(random);
+        let code = tag(key, &uid.0[..TAG_OFFSET]).finalize().into_bytes();
+        let tag_length = uid.0.len() - TAG_OFFSET;
+        uid.0[TAG_OFFSET..].copy_from_slice(&code[..tag_length]);
+        SignedKsuid(uid)
+    }
+
+    /// Parse a base62 string and verify its signature under `key`.
+    pub fn from_base62(string: &str, key: &[u8]) -> Result<Self, errors::KSUIDError> {
+        KSUID::from_base62(string).and_then(|uid| Self::verify(uid, key))
+    }
+
+    /// Verify that `uid` was signed with `key`, wrapping it on success. The tag comparison is
+    /// constant-time.
+    pub fn verify(uid: KSUID, key: &[u8]) -> Result<Self, errors::KSUIDError> {
+        tag(key, &uid.0[..TAG_OFFSET])
+            .verify_truncated_left(&uid.0[TAG_OFFSET..])
+            .map(|_| SignedKsuid(uid))
+            .map_err(|_| errors::KSUIDError::InvalidSignature)
+    }
+
+    /// Return the timestamp portion of the id
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.0.timestamp()
+    }
+
+    /// Return the underlying, verified `KSUID`
+    pub fn as_ksuid(&self) -> &KSUID {
+        &self.0
+    }
+
+    /// Unwrap into the underlying `KSUID`
+    pub fn into_ksuid(self) -> KSUID {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_roundtrip() {
+        let uid = SignedKsuid::new(b"key");
+        let raw = KSUID::from_bytes(uid.as_ksuid().as_bytes()).unwrap();
+        assert_eq!(SignedKsuid::verify(raw, b"key").unwrap(), uid);
+    }
+
+    #[test]
+    fn test_signed_tamper() {
+        let uid = SignedKsuid::new(b"key");
+        for i in 0..20 {
+            let mut bytes = uid.as_ksuid().0;
+            bytes[i] ^= 0x01;
+            assert!(SignedKsuid::verify(KSUID(bytes), b"key").is_err());
+        }
+        assert!(SignedKsuid::verify(KSUID::new(), b"key").is_err());
+    }
+
+    #[test]
+    fn test_signed_from_parts_range() {
+        let ts = DateTime::from_timestamp(1_500_000_000, 0).unwrap();
+        let uid = SignedKsuid::from_parts(ts, &[7; 8], b"key").unwrap();
+        assert_eq!(uid.timestamp(), ts);
+        assert_eq!(SignedKsuid::verify(*uid.as_ksuid(), b"key").unwrap(), uid);
+        let early = DateTime::from_timestamp(1_000_000_000, 0).unwrap();
+        assert_eq!(
+            SignedKsuid::from_parts(early, &[7; 8], b"key"),
+            Err(errors::KSUIDError::TimestampOutOfRange { unix_seconds: 1_000_000_000 })
+        );
+    }
+}
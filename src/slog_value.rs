@@ -0,0 +1,45 @@
+use ksuid::KSUID;
+use slog::{Key, Record, Serializer, Value};
+
+/// Lets a `KSUID` be used directly as a slog key-value, serialized as its base62 string.
+impl Value for KSUID {
+    fn serialize(&self, _record: &Record, key: Key, serializer: &mut dyn Serializer) -> slog::Result {
+        serializer.emit_arguments(key, &format_args!("{}", self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::{Drain, Logger, OwnedKVList, KV};
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    struct Capture(Arc<Mutex<Vec<String>>>);
+
+    impl Serializer for Capture {
+        fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> slog::Result {
+            self.0.lock().unwrap().push(format!("{}={}", key, val));
+            Ok(())
+        }
+    }
+
+    impl Drain for Capture {
+        type Ok = ();
+        type Err = slog::Never;
+
+        fn log(&self, record: &Record, _values: &OwnedKVList) -> Result<(), slog::Never> {
+            record.kv().serialize(record, &mut Capture(self.0.clone())).unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_slog_value() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Logger::root(Capture(seen.clone()), slog::o!());
+        let uid = KSUID::new();
+        slog::info!(log, "created"; "id" => uid);
+        assert_eq!(*seen.lock().unwrap(), vec![format!("id={}", uid)]);
+    }
+}
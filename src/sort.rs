@@ -0,0 +1,163 @@
+//! Sorting for large slices of `KSUID`s, and searching them by time once sorted. Ids are fixed
+//! width byte strings, so a radix sort beats comparison sorting once there are more than a few
+//! thousand of them.
+use chrono::prelude::Utc;
+use chrono::DateTime;
+use ksuid::{BYTE_LENGTH, KSUID};
+use std::ops::Range;
+
+// Buckets at or below this size are finished with a comparison sort, where the bookkeeping of
+// another radix pass costs more than it saves.
+const SMALL_SORT_THRESHOLD: usize = 64;
+
+/// Sort `ids` ascending, in place, with an MSB first radix sort (American flag sort) over the 20
+/// raw bytes. The result is identical to `ids.sort_unstable()`, and like it this does not
+/// allocate; ids that share leading bytes, such as everything minted in the same second, cost a
+/// single counting pass per shared byte.
+/// # Example
+/// ```
+/// use ksuid::KSUID;
+///
+/// let mut ids: Vec<KSUID> = (0..1000).map(|_| KSUID::new()).collect();
+/// let mut expected = ids.clone();
+/// expected.sort_unstable();
+/// ksuid::sort::sort_unstable(&mut ids);
+/// assert_eq!(ids, expected);
+/// ```
+pub fn sort_unstable(ids: &mut [KSUID]) {
+    sort_from(ids, 0);
+}
+
+// Sort `ids`, all of which share their first `depth` bytes.
+fn sort_from(ids: &mut [KSUID], depth: usize) {
+    if ids.len() <= SMALL_SORT_THRESHOLD || depth == BYTE_LENGTH {
+        ids.sort_unstable();
+        return;
+    }
+
+    let mut counts = [0usize; 256];
+    for id in ids.iter() {
+        counts[id.0[depth] as usize] += 1;
+    }
+    // Everything lands in one bucket, so there's nothing to permute at this depth.
+    if counts[ids[0].0[depth] as usize] == ids.len() {
+        return sort_from(ids, depth + 1);
+    }
+
+    let mut starts = [0usize; 256];
+    let mut ends = [0usize; 256];
+    let mut offset = 0;
+    for digit in 0..256 {
+        starts[digit] = offset;
+        offset += counts[digit];
+        ends[digit] = offset;
+    }
+
+    // Walk each bucket, swapping misplaced ids straight into the bucket they belong to.
+    let mut next = starts;
+    for digit in 0..256 {
+        while next[digit] < ends[digit] {
+            let target = ids[next[digit]].0[depth] as usize;
+            if target == digit {
+                next[digit] += 1;
+            } else {
+                ids.swap(next[digit], next[target]);
+                next[target] += 1;
+            }
+        }
+    }
+
+    for digit in 0..256 {
+        if counts[digit] > 1 {
+            sort_from(&mut ids[starts[digit]..ends[digit]], depth + 1);
+        }
+    }
+}
+
+/// Return the index of the first id in the ascending slice `ids` created at or after `time`, i.e.
+/// the number of ids created before it. Ids are ordered by timestamp first, so this is a binary
+/// search that doesn't need a hand-built probe id.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::Duration;
+/// use ksuid::KSUID;
+///
+/// let uid = KSUID::new();
+/// let earlier = KSUID::from_parts(uid.timestamp() - Duration::hours(1), uid.payload()).unwrap();
+/// let ids = vec![earlier, uid];
+/// assert_eq!(ksuid::sort::partition_point_by_time(&ids, uid.timestamp()), 1);
+/// ```
+pub fn partition_point_by_time(ids: &[KSUID], time: DateTime<Utc>) -> usize {
+    ids.partition_point(|id| id.timestamp() < time)
+}
+
+/// Return the index range of the ids in the ascending slice `ids` created within `range` (end
+/// exclusive), ready for slicing: `&ids[range_by_time(ids, start..end)]`.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::Duration;
+/// use ksuid::KSUID;
+///
+/// let now = KSUID::new().timestamp();
+/// let ids: Vec<KSUID> = (0..5).rev()
+///     .map(|h| KSUID::from_parts(now - Duration::hours(h), &[0; 16]).unwrap())
+///     .collect();
+/// let last_two_hours = ksuid::sort::range_by_time(&ids, now - Duration::hours(2)..now);
+/// assert_eq!(last_two_hours, 2..4);
+/// ```
+pub fn range_by_time(ids: &[KSUID], range: Range<DateTime<Utc>>) -> Range<usize> {
+    let start = partition_point_by_time(ids, range.start);
+    // An empty or inverted range yields an empty result rather than a backwards one.
+    let end = start + partition_point_by_time(&ids[start..], range.end);
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand;
+    use rand::Rng;
+
+    #[test]
+    fn test_sort_unstable_matches_std() {
+        let mut rng = rand::thread_rng();
+        for &len in [0, 1, 2, 63, 64, 65, 1000, 50_000].iter() {
+            // Few distinct timestamps and duplicated ids, so buckets share prefixes and collide.
+            let mut ids: Vec<KSUID> = (0..len).map(|_| {
+                let mut bytes = [0u8; 20];
+                rng.fill_bytes(&mut bytes[4..]);
+                bytes[3] = rng.gen_range(0, 4);
+                KSUID(bytes)
+            }).collect();
+            for i in 0..len / 10 {
+                ids[i * 10] = ids[i];
+            }
+            let mut expected = ids.clone();
+            expected.sort_unstable();
+            sort_unstable(&mut ids);
+            assert_eq!(ids, expected, "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_range_by_time() {
+        let ids: Vec<KSUID> = [1u32, 2, 2, 2, 5].iter().map(|&t| {
+            let mut bytes = [0xAB; 20];
+            bytes[..4].copy_from_slice(&t.to_be_bytes());
+            KSUID(bytes)
+        }).collect();
+        let at = |t: u32| ::ksuid::from_ksuid_time(t);
+        assert_eq!(partition_point_by_time(&ids, at(0)), 0);
+        assert_eq!(partition_point_by_time(&ids, at(2)), 1);
+        assert_eq!(partition_point_by_time(&ids, at(3)), 4);
+        assert_eq!(partition_point_by_time(&ids, at(9)), 5);
+        assert_eq!(range_by_time(&ids, at(2)..at(3)), 1..4);
+        assert_eq!(range_by_time(&ids, at(3)..at(5)), 4..4);
+        assert_eq!(range_by_time(&ids, at(5)..at(1)), 4..4);
+        assert_eq!(range_by_time(&[], at(0)..at(9)), 0..0);
+    }
+}
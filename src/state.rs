@@ -0,0 +1,109 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The part of a counter or `hlc` `Generator`'s state that must survive a restart for its ids to
+/// keep increasing: the latest timestamp, in seconds since the ksuid epoch, it may have issued
+/// ids under.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GeneratorState {
+    /// The latest timestamp, in seconds since the ksuid epoch, that ids may have been issued
+    /// under. A restored generator only issues ids after it.
+    pub last_time: u32,
+}
+
+/// Durable storage for `GeneratorState`, attached with `Generator::with_store`. The generator
+/// saves before issuing the first id of each new second, so implementations should not return
+/// from `save` until the state would survive a crash.
+pub trait StateStore: fmt::Debug + Send + Sync {
+    /// Return the last saved state, or `None` if nothing has been saved yet.
+    fn load(&mut self) -> io::Result<Option<GeneratorState>>;
+    /// Durably record `state`, replacing whatever was saved before.
+    fn save(&mut self, state: &GeneratorState) -> io::Result<()>;
+}
+
+/// A `StateStore` keeping the state in a small text file. Saves write a temporary file next to
+/// it, sync it, rename it into place and sync the directory, so a crash leaves either the old
+/// state or the new one, and the new one once `save` has returned.
+/// # Example
+/// ```
+/// use ksuid::{FileStateStore, Generator, GeneratorOptions};
+///
+/// let path = std::env::temp_dir().join(format!("ksuid-doc-{}.state", ksuid::KSUID::new()));
+/// let options = GeneratorOptions { counter: true, ..Default::default() };
+/// let before = Generator::with_store(options.clone(), FileStateStore::new(&path)).unwrap().next_id();
+/// // A restarted generator carries on after everything issued before.
+/// let after = Generator::with_store(options, FileStateStore::new(&path)).unwrap().next_id();
+/// assert!(after > before);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct FileStateStore {
+    path: PathBuf,
+}
+
+impl FileStateStore {
+    /// Keep the state at `path`. Nothing is read or written until the generator needs it.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        FileStateStore { path: path.as_ref().to_owned() }
+    }
+}
+
+impl StateStore for FileStateStore {
+    fn load(&mut self) -> io::Result<Option<GeneratorState>> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let last_time = text.trim().parse().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "generator state file is not a timestamp")
+        })?;
+        Ok(Some(GeneratorState { last_time }))
+    }
+
+    fn save(&mut self, state: &GeneratorState) -> io::Result<()> {
+        let mut tmp_name = self.path.file_name().map(|name| name.to_owned()).unwrap_or_default();
+        tmp_name.push(".tmp");
+        let tmp = self.path.with_file_name(tmp_name);
+        let mut file = File::create(&tmp)?;
+        writeln!(file, "{}", state.last_time)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        sync_parent(&self.path)
+    }
+}
+
+// Make a rename into the directory holding `path` durable. Directories can only be opened for
+// syncing on unix; elsewhere the rename is left to the file system.
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> io::Result<()> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or_else(|| Path::new("."));
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ksuid::KSUID;
+    use std::env;
+
+    #[test]
+    fn test_file_state_store() {
+        let path = env::temp_dir().join(format!("ksuid-test-{}.state", KSUID::new()));
+        let mut store = FileStateStore::new(&path);
+        assert_eq!(store.load().unwrap(), None);
+        store.save(&GeneratorState { last_time: 7 }).unwrap();
+        store.save(&GeneratorState { last_time: 1234 }).unwrap();
+        assert_eq!(FileStateStore::new(&path).load().unwrap(), Some(GeneratorState { last_time: 1234 }));
+        fs::write(&path, "garbage").unwrap();
+        assert_eq!(store.load().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+}
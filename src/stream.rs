@@ -0,0 +1,112 @@
+use futures_core::Stream;
+use futures_timer::Delay;
+use generator::Generator;
+use ksuid::KSUID;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time;
+
+/// An endless `Stream` of ids from a `Generator`, returned by `Generator::into_stream`. It
+/// honors the generator's options, so a counter generator yields strictly increasing ids.
+/// # Example
+/// ```
+/// extern crate futures;
+/// extern crate ksuid;
+/// use futures::executor::block_on;
+/// use futures::stream::{self, StreamExt};
+/// use ksuid::Generator;
+///
+/// let jobs = stream::iter(vec!["resize", "upload", "notify"]);
+/// let tagged: Vec<_> = block_on(jobs.zip(Generator::new().into_stream()).collect());
+/// assert_eq!(tagged.len(), 3);
+/// ```
+#[derive(Debug)]
+pub struct KsuidStream {
+    generator: Generator,
+    interval: Option<time::Duration>,
+    delay: Option<Delay>,
+}
+
+impl KsuidStream {
+    /// Yield at most `per_second` ids a second, evenly spaced: the first id is ready at once
+    /// and each later one a `1 / per_second` interval after the previous was taken. The timer
+    /// runs on its own thread, so this works under any executor.
+    ///
+    /// Panics if `per_second` is zero.
+    pub fn rate_limited(self, per_second: u32) -> Self {
+        assert!(per_second > 0, "rate must be non-zero");
+        KsuidStream {
+            interval: Some(time::Duration::from_secs(1) / per_second),
+            ..self
+        }
+    }
+
+    /// Return the generator back.
+    pub fn into_inner(self) -> Generator {
+        self.generator
+    }
+}
+
+impl Generator {
+    /// Turn this generator into a `Stream` of fresh ids.
+    pub fn into_stream(self) -> KsuidStream {
+        KsuidStream { generator: self, interval: None, delay: None }
+    }
+}
+
+impl Stream for KsuidStream {
+    type Item = KSUID;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<KSUID>> {
+        let this = self.get_mut();
+        if let Some(ref mut delay) = this.delay {
+            if Pin::new(delay).poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+        if let Some(interval) = this.interval {
+            match this.delay {
+                Some(ref mut delay) => delay.reset(interval),
+                None => this.delay = Some(Delay::new(interval)),
+            }
+        }
+        Poll::Ready(Some(this.generator.next_id()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::stream::StreamExt;
+    use generator::GeneratorOptions;
+    use std::time::Instant;
+
+    #[test]
+    fn test_stream_counter_ordering() {
+        let gen = Generator::with_options(GeneratorOptions { counter: true, ..Default::default() }).unwrap();
+        let ids: Vec<KSUID> = block_on(gen.into_stream().take(1000).collect());
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_stream_rate_limited() {
+        let stream = Generator::new().into_stream().rate_limited(100);
+        let start = Instant::now();
+        let ids: Vec<KSUID> = block_on(stream.take(11).collect());
+        assert_eq!(ids.len(), 11);
+        // Ten 10ms gaps after the first id.
+        assert!(start.elapsed() >= time::Duration::from_millis(100));
+    }
+
+    #[test]
+    #[should_panic(expected = "rate must be non-zero")]
+    fn test_stream_rejects_zero_rate() {
+        Generator::new().into_stream().rate_limited(0);
+    }
+}
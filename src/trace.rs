@@ -0,0 +1,78 @@
+use ksuid::KSUID;
+use tracing::field::{display, DisplayValue};
+use tracing::Span;
+
+/// tracing integration. `tracing::Value` is sealed, so ids are recorded through their `Display`
+/// form; `as_field` saves spelling out `%id` / `field::display(id)` at every call site.
+impl KSUID {
+
+    /// Return this id as a value that can be recorded as a span or event field.
+    /// # Example
+    /// ```
+    /// #[macro_use] extern crate tracing;
+    /// extern crate ksuid;
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::new();
+    /// info!(order_id = uid.as_field(), "order created");
+    /// ```
+    pub fn as_field(&self) -> DisplayValue<&KSUID> {
+        display(self)
+    }
+}
+
+/// Mint a fresh request id and open an info level `request` span carrying it as the
+/// `request_id` field. The span is returned unentered so the caller decides how to enter it.
+/// # Example
+/// ```
+/// let (request_id, span) = ksuid::request_span();
+/// let _guard = span.enter();
+/// ```
+pub fn request_span() -> (KSUID, Span) {
+    let id = KSUID::new();
+    let span = info_span!("request", request_id = id.as_field());
+    (id, span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{subscriber, Event, Metadata, Subscriber};
+
+    // Records the `request_id` field of every new span.
+    struct Capture(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Capture {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "request_id" {
+                self.0.lock().unwrap().push(format!("{:?}", value));
+            }
+        }
+    }
+
+    impl Subscriber for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+        fn new_span(&self, span: &Attributes) -> Id {
+            span.record(&mut Capture(self.0.clone()));
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, _: &Record) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_request_span_records_id() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let id = subscriber::with_default(Capture(seen.clone()), || request_span().0);
+        assert_eq!(*seen.lock().unwrap(), vec![id.to_base62()]);
+    }
+}
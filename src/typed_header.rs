@@ -0,0 +1,88 @@
+use headers::{Error, Header, HeaderName, HeaderValue};
+use ksuid::KSUID;
+use std::ops::Deref;
+
+static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Typed `x-request-id` header holding a KSUID, for the `headers` crate and the frameworks built
+/// on it (axum's `TypedHeader`, warp's `header::<T>`, `HeaderMapExt`). Decoding fails unless
+/// there is exactly one value and it is a valid base62 KSUID.
+/// # Example
+/// ```
+/// extern crate headers;
+/// extern crate ksuid;
+/// use headers::{HeaderMap, HeaderMapExt};
+/// use ksuid::{XRequestKsuid, KSUID};
+///
+/// let mut map = HeaderMap::new();
+/// let id = KSUID::new();
+/// map.typed_insert(XRequestKsuid(id));
+/// assert_eq!(map["x-request-id"], id.to_base62());
+/// assert_eq!(map.typed_get::<XRequestKsuid>(), Some(XRequestKsuid(id)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct XRequestKsuid(pub KSUID);
+
+impl Header for XRequestKsuid {
+    fn name() -> &'static HeaderName {
+        &X_REQUEST_ID
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, Error>
+    where
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values.next().ok_or_else(Error::invalid)?;
+        if values.next().is_some() {
+            return Err(Error::invalid());
+        }
+        KSUID::from_base62_bytes(value.as_bytes()).map(XRequestKsuid).map_err(|_| Error::invalid())
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        // Base62 is always a valid header value.
+        values.extend(Some(HeaderValue::from_bytes(&self.0.to_base62_array()).unwrap()));
+    }
+}
+
+impl Deref for XRequestKsuid {
+    type Target = KSUID;
+
+    fn deref(&self) -> &KSUID {
+        &self.0
+    }
+}
+
+impl From<KSUID> for XRequestKsuid {
+    fn from(id: KSUID) -> Self {
+        XRequestKsuid(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use headers::{HeaderMap, HeaderMapExt};
+
+    #[test]
+    fn test_header_roundtrip() {
+        let id = KSUID::new();
+        let mut map = HeaderMap::new();
+        map.typed_insert(XRequestKsuid::from(id));
+        assert_eq!(map.get_all("x-request-id").iter().count(), 1);
+        assert_eq!(*map.typed_get::<XRequestKsuid>().unwrap(), id);
+    }
+
+    #[test]
+    fn test_header_rejects() {
+        let id = KSUID::new().to_base62();
+        let cases: [&[&str]; 4] = [&[], &["not-a-ksuid"], &[&id[1..]], &[&id, &id]];
+        for values in cases.iter() {
+            let mut map = HeaderMap::new();
+            for &value in values.iter() {
+                map.append("x-request-id", HeaderValue::from_str(value).unwrap());
+            }
+            assert!(map.typed_try_get::<XRequestKsuid>().ok().flatten().is_none(), "{:?}", values);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use errors;
+use ksuid::KSUID;
+
+// "urn" and the namespace identifier are case insensitive (RFC 8141); the id itself is not.
+const URN_PREFIX: &str = "urn:ksuid:";
+
+/// URN form, `urn:ksuid:<base62>`, for systems that require URN shaped identifiers.
+impl KSUID {
+
+    /// Return the URN form of this id.
+    /// # Example
+    /// ```
+    /// use ksuid::KSUID;
+    ///
+    /// let uid = KSUID::from_base62("0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap();
+    /// assert_eq!(uid.to_urn(), "urn:ksuid:0ujsszwN8NRY24YaXiTIE2VWDTS");
+    /// assert_eq!(KSUID::from_urn("URN:KSUID:0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap(), uid);
+    /// ```
+    pub fn to_urn(&self) -> String {
+        format!("{}{}", URN_PREFIX, self)
+    }
+
+    /// Parse a URN produced by `to_urn`. The `urn:ksuid:` prefix is matched case insensitively;
+    /// the base62 part is parsed exactly as by `from_base62`.
+    pub fn from_urn(urn: &str) -> Result<Self, errors::KSUIDError> {
+        match urn.get(..URN_PREFIX.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(URN_PREFIX) => Self::from_base62(&urn[URN_PREFIX.len()..]),
+            _ => Err(errors::KSUIDError::InvalidUrn { value: urn.to_owned() }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urn_roundtrip() {
+        let uid = KSUID::new();
+        assert_eq!(KSUID::from_urn(&uid.to_urn()).unwrap(), uid);
+        assert_eq!(KSUID::from_urn(&uid.to_urn().replace("urn:ksuid", "Urn:Ksuid")).unwrap(), uid);
+    }
+
+    #[test]
+    fn test_urn_rejects() {
+        let uid = KSUID::new();
+        assert_eq!(KSUID::from_urn(&uid.to_base62()).unwrap_err().kind(), errors::KSUIDErrorKind::InvalidFormat);
+        assert_eq!(KSUID::from_urn("urn:uuid:0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap_err().kind(), errors::KSUIDErrorKind::InvalidFormat);
+        assert_eq!(KSUID::from_urn("urn:k").unwrap_err().kind(), errors::KSUIDErrorKind::InvalidFormat);
+        // Only the prefix is case insensitive.
+        assert_ne!(KSUID::from_urn("urn:ksuid:0UJSSZWN8NRY24YAXITIE2VWDTS").unwrap(), KSUID::from_urn("urn:ksuid:0ujsszwN8NRY24YaXiTIE2VWDTS").unwrap());
+        assert!(KSUID::from_urn("urn:ksuid:").is_err());
+        assert!(KSUID::from_urn("urn:ksuid:é").is_err());
+    }
+}
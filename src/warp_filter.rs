@@ -0,0 +1,114 @@
+use errors;
+use ksuid::KSUID;
+use std::future::{ready, Ready};
+use warp::http::StatusCode;
+use warp::reject::{self, Reject, Rejection};
+use warp::reply::{self, Reply, Response};
+use warp::Filter;
+
+/// Rejection raised by `ksuid_param` for a path segment that isn't a valid KSUID. Pair the
+/// filter with `recover(handle_invalid_ksuid)` to answer it with a 400; unrecovered, warp
+/// reports it as a 500 like any unknown rejection.
+#[derive(Debug)]
+pub struct InvalidKsuid(pub errors::KSUIDError);
+
+impl Reject for InvalidKsuid {}
+
+fn parse_segment(segment: String) -> Ready<Result<KSUID, Rejection>> {
+    ready(segment.parse().map_err(|err| reject::custom(InvalidKsuid(err))))
+}
+
+/// Extract a KSUID from the next path segment. Unlike `warp::path::param::<KSUID>()`, which
+/// rejects a malformed id as not found so routing falls through to a 404, this rejects with
+/// `InvalidKsuid`.
+/// # Example
+/// ```
+/// extern crate ksuid;
+/// extern crate warp;
+/// use ksuid::{handle_invalid_ksuid, ksuid_param, KSUID};
+/// use warp::Filter;
+///
+/// let orders = warp::path("orders")
+///     .and(ksuid_param())
+///     .map(|id: KSUID| format!("order {}", id))
+///     .recover(handle_invalid_ksuid);
+/// ```
+pub fn ksuid_param() -> impl Filter<Extract = (KSUID,), Error = Rejection> + Clone {
+    warp::path::param::<String>().and_then(parse_segment)
+}
+
+/// `recover` handler answering an `InvalidKsuid` rejection with 400 Bad Request and the parse
+/// error as the body; any other rejection is passed on unchanged. The error message never
+/// includes the rejected input.
+pub fn handle_invalid_ksuid(rejection: Rejection) -> Ready<Result<Response, Rejection>> {
+    ready(match rejection.find::<InvalidKsuid>() {
+        Some(invalid) => Ok(reply::with_status(invalid.0.to_string(), StatusCode::BAD_REQUEST).into_response()),
+        None => Err(rejection),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use http_body::Body;
+    use std::convert::Infallible;
+    use std::future::poll_fn;
+    use tower_service::Service;
+    use warp::http::Request;
+
+    // Send a GET for `path` through `service`, returning the status and the collected body.
+    fn get<S>(service: &mut S, path: &str) -> (StatusCode, Vec<u8>)
+    where
+        S: Service<Request<String>, Response = Response, Error = Infallible>,
+    {
+        let request = Request::builder().uri(path).body(String::new()).unwrap();
+        let response = block_on(service.call(request)).unwrap();
+        let status = response.status();
+        let mut body = Box::pin(response.into_body());
+        let mut bytes = Vec::new();
+        while let Some(frame) = block_on(poll_fn(|cx| body.as_mut().poll_frame(cx))) {
+            if let Ok(data) = frame.unwrap().into_data() {
+                bytes.extend_from_slice(&data);
+            }
+        }
+        (status, bytes)
+    }
+
+    #[test]
+    fn test_ksuid_param() {
+        let uid = KSUID::new();
+        let filter = warp::path("orders").and(ksuid_param()).and(warp::path::end()).map(|id: KSUID| id.to_string());
+        let mut service = warp::service(filter);
+        assert_eq!(get(&mut service, &format!("/orders/{}", uid)), (StatusCode::OK, uid.to_base62().into_bytes()));
+        // Unrecovered, `InvalidKsuid` is an unknown rejection rather than a not found.
+        assert_eq!(get(&mut service, "/orders/nope").0, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_bad_request() {
+        let uid = KSUID::new();
+        let route = warp::path("orders")
+            .and(ksuid_param())
+            .map(|id: KSUID| id.to_string())
+            .recover(handle_invalid_ksuid);
+        let mut service = warp::service(route);
+        let (status, body) = get(&mut service, &format!("/orders/{}", uid));
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, uid.to_base62().as_bytes());
+        let (status, body) = get(&mut service, "/orders/s3cretTokenThatIsNotAKsuid");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(!String::from_utf8_lossy(&body).contains("s3cret"));
+        // Other routes still 404.
+        assert_eq!(get(&mut service, "/users/1").0, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_builtin_param() {
+        // `KSUID: FromStr`, so warp's own param filter works too, rejecting as not found.
+        let uid = KSUID::new();
+        let mut service = warp::service(warp::path::param::<KSUID>().map(|id: KSUID| id.to_string()));
+        assert_eq!(get(&mut service, &format!("/{}", uid)), (StatusCode::OK, uid.to_base62().into_bytes()));
+        assert_eq!(get(&mut service, "/nope").0, StatusCode::NOT_FOUND);
+    }
+}
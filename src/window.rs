@@ -0,0 +1,129 @@
+use byteorder::{BigEndian, ByteOrder};
+use chrono::prelude::Utc;
+use chrono::{DateTime, Duration};
+use ksuid::{to_ksuid_time, KSUID};
+use std::collections::BTreeSet;
+
+/// Remembers the ids seen within a sliding time window, for deduplicating at-least-once
+/// deliveries. Expiry is driven by the ids' own timestamps: the window trails the newest id
+/// inserted so far, and since `KSUID`s sort by time, expiring is a single split of an ordered
+/// set rather than a scan.
+/// # Example
+/// ```
+/// extern crate chrono;
+/// extern crate ksuid;
+/// use chrono::Duration;
+/// use ksuid::{KSUID, RecentKsuidWindow};
+///
+/// let mut window = RecentKsuidWindow::new(Duration::minutes(5));
+/// let uid = KSUID::new();
+/// assert!(window.insert_if_new(uid));
+/// assert!(!window.insert_if_new(uid));
+/// ```
+#[derive(Clone, Debug)]
+pub struct RecentKsuidWindow {
+    window: u32,
+    newest: u32,
+    seen: BTreeSet<KSUID>,
+}
+
+fn time_of(id: &KSUID) -> u32 {
+    BigEndian::read_u32(&id.0)
+}
+
+impl RecentKsuidWindow {
+
+    /// Create a window remembering ids up to `window` older than the newest id seen. The window
+    /// is measured in whole seconds, matching the resolution of the embedded timestamps.
+    pub fn new(window: Duration) -> Self {
+        RecentKsuidWindow {
+            window: window.num_seconds().clamp(0, i64::from(u32::MAX)) as u32,
+            newest: 0,
+            seen: BTreeSet::new(),
+        }
+    }
+
+    /// Record `id`, returning `true` if it was not already in the window. Ids older than the
+    /// window can no longer be checked and are reported as new, so late first deliveries are
+    /// not dropped; they are not remembered.
+    pub fn insert_if_new(&mut self, id: KSUID) -> bool {
+        let t = time_of(&id);
+        if t > self.newest {
+            self.newest = t;
+            self.expire();
+        }
+        if t < self.cutoff() {
+            return true;
+        }
+        self.seen.insert(id)
+    }
+
+    /// Check whether `id` is currently remembered.
+    pub fn contains(&self, id: &KSUID) -> bool {
+        self.seen.contains(id)
+    }
+
+    /// Advance the window to wall clock time `now`, forgetting ids older than `now - window`.
+    /// Useful when inserts are sparse and memory should be released promptly.
+    pub fn expire_at(&mut self, now: DateTime<Utc>) {
+        let now = to_ksuid_time(now);
+        if now > self.newest {
+            self.newest = now;
+            self.expire();
+        }
+    }
+
+    /// Return the number of remembered ids.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Check whether no ids are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    fn cutoff(&self) -> u32 {
+        self.newest.saturating_sub(self.window)
+    }
+
+    fn expire(&mut self) {
+        let mut first_kept = [0u8; 20];
+        BigEndian::write_u32(&mut first_kept, self.cutoff());
+        self.seen = self.seen.split_off(&KSUID(first_kept));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ksuid::from_ksuid_time;
+
+    fn id_at(t: u32, fill: u8) -> KSUID {
+        KSUID::from_parts(from_ksuid_time(t), &[fill; 16]).unwrap()
+    }
+
+    #[test]
+    fn test_window_expiry() {
+        let mut window = RecentKsuidWindow::new(Duration::seconds(10));
+        assert!(window.insert_if_new(id_at(100, 1)));
+        assert!(window.insert_if_new(id_at(105, 1)));
+        assert!(!window.insert_if_new(id_at(100, 1)));
+        assert!(window.insert_if_new(id_at(111, 1)));
+        // 100 fell out of the window when 111 arrived.
+        assert!(!window.contains(&id_at(100, 1)));
+        assert_eq!(window.len(), 2);
+        assert!(window.insert_if_new(id_at(100, 1)));
+        assert_eq!(window.len(), 2);
+    }
+
+    #[test]
+    fn test_window_expire_at() {
+        let mut window = RecentKsuidWindow::new(Duration::seconds(10));
+        window.insert_if_new(id_at(100, 1));
+        window.expire_at(from_ksuid_time(105));
+        assert_eq!(window.len(), 1);
+        window.expire_at(from_ksuid_time(200));
+        assert!(window.is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+//! Minimal encode/decode for hand rolled binary protocols, without serde. `KSUID` itself writes
+//! its 20 raw bytes; wrap it in `Base62` to write the 27 ASCII characters instead. Both always
+//! use exactly `WIRE_LENGTH` bytes at the front of the buffer, so fields can be laid out at fixed
+//! offsets.
+//!
+//! ```
+//! use ksuid::wire::{Base62, Decode, Encode};
+//! use ksuid::KSUID;
+//!
+//! let uid = KSUID::new();
+//! let mut frame = [0u8; 1 + 20 + 27];
+//! frame[0] = 0x01; // message type
+//! let mut offset = 1;
+//! offset += uid.encode(&mut frame[offset..]).unwrap();
+//! offset += Base62(uid).encode(&mut frame[offset..]).unwrap();
+//! assert_eq!(offset, frame.len());
+//!
+//! let (first, read) = KSUID::decode(&frame[1..]).unwrap();
+//! let (second, _) = Base62::decode(&frame[1 + read..]).unwrap();
+//! assert_eq!(first, uid);
+//! assert_eq!(second.0, uid);
+//! ```
+use errors;
+use ksuid::{BYTE_LENGTH, KSUID};
+
+const BASE62_LENGTH: usize = 27;
+
+/// Writes a value as a fixed number of bytes.
+pub trait Encode {
+    /// How many bytes `encode` writes.
+    const WIRE_LENGTH: usize;
+
+    /// Write the value to the start of `buf`, returning `WIRE_LENGTH`. Fails with
+    /// `KSUIDError::SliceTooSmall`, leaving `buf` untouched, if it is shorter than that.
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, errors::KSUIDError>;
+}
+
+/// Reads a value written by `Encode`.
+pub trait Decode: Sized {
+    /// Read a value from the start of `buf`, returning it and the number of bytes consumed.
+    /// Bytes past the value are ignored.
+    fn decode(buf: &[u8]) -> Result<(Self, usize), errors::KSUIDError>;
+}
+
+// The first `length` bytes of `buf`, or the error for a short buffer.
+fn prefix(buf: &[u8], length: usize) -> Result<&[u8], errors::KSUIDError> {
+    buf.get(..length).ok_or(errors::KSUIDError::SliceTooSmall { length: buf.len() })
+}
+
+fn prefix_mut(buf: &mut [u8], length: usize) -> Result<&mut [u8], errors::KSUIDError> {
+    let available = buf.len();
+    buf.get_mut(..length).ok_or(errors::KSUIDError::SliceTooSmall { length: available })
+}
+
+impl Encode for KSUID {
+    const WIRE_LENGTH: usize = BYTE_LENGTH;
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, errors::KSUIDError> {
+        prefix_mut(buf, BYTE_LENGTH)?.copy_from_slice(&self.0);
+        Ok(BYTE_LENGTH)
+    }
+}
+
+impl Decode for KSUID {
+    fn decode(buf: &[u8]) -> Result<(Self, usize), errors::KSUIDError> {
+        KSUID::from_bytes(prefix(buf, BYTE_LENGTH)?).map(|id| (id, BYTE_LENGTH))
+    }
+}
+
+/// A `KSUID` encoded as its 27 character base62 form, for text friendly protocols.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Base62(pub KSUID);
+
+impl From<KSUID> for Base62 {
+    fn from(id: KSUID) -> Self {
+        Base62(id)
+    }
+}
+
+impl From<Base62> for KSUID {
+    fn from(id: Base62) -> Self {
+        id.0
+    }
+}
+
+impl Encode for Base62 {
+    const WIRE_LENGTH: usize = BASE62_LENGTH;
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, errors::KSUIDError> {
+        prefix_mut(buf, BASE62_LENGTH)?.copy_from_slice(&self.0.to_base62_array());
+        Ok(BASE62_LENGTH)
+    }
+}
+
+impl Decode for Base62 {
+    fn decode(buf: &[u8]) -> Result<(Self, usize), errors::KSUIDError> {
+        KSUID::from_base62_bytes(prefix(buf, BASE62_LENGTH)?).map(|id| (Base62(id), BASE62_LENGTH))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_round_trip() {
+        let uid = KSUID::new();
+        let mut buf = [0xaa; 30];
+        assert_eq!(uid.encode(&mut buf).unwrap(), 20);
+        assert_eq!(&buf[..20], uid.as_bytes());
+        assert_eq!(buf[20], 0xaa);
+        assert_eq!(KSUID::decode(&buf).unwrap(), (uid, 20));
+        assert_eq!(Base62(uid).encode(&mut buf).unwrap(), 27);
+        assert_eq!(&buf[..27], uid.to_base62().as_bytes());
+        assert_eq!(Base62::decode(&buf).unwrap(), (Base62(uid), 27));
+    }
+
+    #[test]
+    fn test_wire_short_buffers() {
+        let uid = KSUID::new();
+        let mut buf = [0u8; 26];
+        assert_eq!(Base62(uid).encode(&mut buf), Err(errors::KSUIDError::SliceTooSmall { length: 26 }));
+        assert_eq!(buf, [0; 26]);
+        assert_eq!(KSUID::decode(&buf[..19]), Err(errors::KSUIDError::SliceTooSmall { length: 19 }));
+        assert!(Base62::decode(&[b'!'; 27]).is_err());
+    }
+}